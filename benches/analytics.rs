@@ -0,0 +1,221 @@
+// Progress analytics over a generated multi-year history, against the per-workout queries
+// they replaced. Run with `cargo bench --bench analytics`; BENCH_YEARS changes the history
+// length (default 5).
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::{Duration as Days, NaiveDate};
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+
+use gymbuddy::db::connection::init_db;
+use gymbuddy::db::schedule::get_workouts_since;
+use gymbuddy::db::summary::{get_user_progress, get_workout_progress, get_workout_summaries, get_workout_summary};
+use gymbuddy::models::analytics::{ExerciseVolume, WorkoutProgress, WorkoutTotals};
+
+const RUNS: usize = 5;
+const WORKOUTS_PER_WEEK: i64 = 4;
+const EXERCISES: [(&str, &str); 8] = [
+    ("Squat", "Legs"), ("Bench Press", "Chest"), ("Deadlift", "Back"), ("Overhead Press", "Shoulders"),
+    ("Row", "Back"), ("Pull Up", "Back"), ("Lunge", "Legs"), ("Dip", "Chest"),
+];
+
+// Four sessions a week for each user; every third session ends in a three-round superset
+async fn seed(pool: &SqlitePool, users: i64, years: i64) -> Result<i64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    for (name, muscle) in EXERCISES {
+        sqlx::query("INSERT INTO exercises (name, muscle_group) VALUES (?, ?)")
+            .bind(name)
+            .bind(muscle)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let start = NaiveDate::from_ymd_opt(2020, 1, 6).unwrap();
+    let sessions = years * 52 * WORKOUTS_PER_WEEK;
+    for user_id in 1..=users {
+        sqlx::query("INSERT INTO users (username, email, password_hash) VALUES (?, ?, 'x')")
+            .bind(format!("lifter{}", user_id))
+            .bind(format!("lifter{}@example.com", user_id))
+            .execute(&mut *tx)
+            .await?;
+
+        for session in 0..sessions {
+            let date = start + Days::days(session / WORKOUTS_PER_WEEK * 7 + session % WORKOUTS_PER_WEEK * 2);
+            let workout_id = sqlx::query("INSERT INTO workouts (user_id, date) VALUES (?, ?)")
+                .bind(user_id)
+                .bind(date.to_string())
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+
+            let group_id = if session % 3 == 0 {
+                let id = sqlx::query("INSERT INTO entry_groups (workout_id, kind, rounds, duration_seconds) VALUES (?, 'superset', 3, 600)")
+                    .bind(workout_id)
+                    .execute(&mut *tx)
+                    .await?
+                    .last_insert_rowid();
+                Some(id)
+            } else {
+                None
+            };
+
+            for position in 0..6_i64 {
+                let exercise_id = (session + position) % EXERCISES.len() as i64 + 1;
+                // Bodyweight every seventh entry, otherwise a slow climb over the years
+                let weight = ((session + position) % 7 != 0).then_some(40.0 + (session % 200) as f64 * 0.5 + position as f64 * 5.0);
+                sqlx::query(
+                    "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, position, group_id)
+                    VALUES (?, ?, ?, ?, ?, ?, ?)"
+                )
+                .bind(workout_id)
+                .bind(exercise_id)
+                .bind(3 + position % 3)
+                .bind(5 + session % 6)
+                .bind(weight)
+                .bind(position)
+                .bind(if position >= 4 { group_id } else { None })
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    Ok(sessions)
+}
+
+// What analytics did before aggregation moved into SQL: the workout list, then one query
+// for the entries of each workout
+async fn per_workout_progress(pool: &SqlitePool, user_id: i64) -> Result<Vec<WorkoutProgress>, sqlx::Error> {
+    let workouts: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, date FROM workouts WHERE user_id = ? ORDER BY date, id")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+
+    let mut progress = Vec::new();
+    for (workout_id, date) in workouts {
+        let entries: Vec<(String, String, i64, i64, f64)> = sqlx::query_as(
+            "SELECT e.name, e.muscle_group, we.sets * COALESCE(g.rounds, 1), we.reps, COALESCE(we.weight, 0.0)
+            FROM workout_entries we
+            JOIN exercises e ON we.exercise_id = e.id
+            LEFT JOIN entry_groups g ON we.group_id = g.id
+            WHERE we.workout_id = ?
+            ORDER BY we.position, we.id"
+        )
+        .bind(workout_id)
+        .fetch_all(pool)
+        .await?;
+
+        let volume = |e: &(String, String, i64, i64, f64)| e.2 as f64 * e.3 as f64 * e.4;
+        let mut muscle_groups = BTreeMap::new();
+        for entry in &entries {
+            *muscle_groups.entry(entry.1.clone()).or_insert(0.0) += volume(entry);
+        }
+        let mut top_exercises: Vec<ExerciseVolume> = entries
+            .iter()
+            .map(|e| ExerciseVolume { name: e.0.clone(), volume: volume(e) })
+            .collect();
+        top_exercises.sort_by(|a, b| b.volume.total_cmp(&a.volume));
+
+        progress.push(WorkoutProgress {
+            totals: WorkoutTotals {
+                workout_id,
+                date,
+                total_sets: entries.iter().map(|e| e.2).sum(),
+                total_reps: entries.iter().map(|e| e.3).sum(),
+                total_volume: entries.iter().map(volume).sum(),
+            },
+            muscle_groups,
+            top_exercises,
+        });
+    }
+
+    Ok(progress)
+}
+
+// Median of RUNS runs, plus the last result
+async fn time<T, F, Fut>(mut run: F) -> (Duration, T)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, sqlx::Error>>,
+{
+    let mut times = Vec::with_capacity(RUNS);
+    let mut result = None;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        result = Some(run().await.expect("query failed"));
+        times.push(started.elapsed());
+    }
+    times.sort();
+    (times[RUNS / 2], result.unwrap())
+}
+
+fn report(name: &str, time: Duration, baseline: Duration) {
+    println!(
+        "{:<36} {:>10.2} ms {:>8.1}x",
+        name,
+        time.as_secs_f64() * 1000.0,
+        baseline.as_secs_f64() / time.as_secs_f64(),
+    );
+}
+
+#[tokio::main]
+async fn main() {
+    let years: i64 = std::env::var("BENCH_YEARS").ok().and_then(|y| y.parse().ok()).unwrap_or(5);
+
+    let pool = SqlitePoolOptions::new()
+        .connect("sqlite:file:bench_analytics?mode=memory&cache=shared")
+        .await
+        .unwrap();
+    init_db(&pool).await.unwrap();
+
+    let started = Instant::now();
+    let sessions = seed(&pool, 3, years).await.unwrap();
+    println!(
+        "{} years: 3 users x {} workouts x 6 entries, seeded in {:.1}s\n",
+        years,
+        sessions,
+        started.elapsed().as_secs_f64()
+    );
+
+    let (baseline, expected) = time(|| per_workout_progress(&pool, 2)).await;
+    let (grouped, totals) = time(|| get_user_progress(&pool, 2)).await;
+    let (progress_time, progress) = time(|| get_workout_progress(&pool, 2)).await;
+
+    // Same numbers, or the speedup means nothing
+    assert_eq!((totals.len(), progress.len()), (expected.len(), expected.len()));
+    for ((totals, progress), expected) in totals.iter().zip(&progress).zip(&expected) {
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-6;
+        assert_eq!(totals.workout_id, expected.totals.workout_id);
+        assert_eq!((totals.total_sets, totals.total_reps), (expected.totals.total_sets, expected.totals.total_reps));
+        assert!(close(totals.total_volume, expected.totals.total_volume));
+        assert_eq!(progress.totals, *totals);
+
+        assert!(progress.muscle_groups.keys().eq(expected.muscle_groups.keys()));
+        assert!(progress.muscle_groups.values().zip(expected.muscle_groups.values()).all(|(a, b)| close(*a, *b)));
+        let names = |p: &WorkoutProgress| p.top_exercises.iter().map(|e| e.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(progress), names(expected));
+    }
+
+    let ids: Vec<i64> = get_workouts_since(&pool, 2, "0000-00-00").await.unwrap().iter().map(|w| w.id).collect();
+    let (single, _) = time(|| async {
+        for id in &ids {
+            get_workout_summary(&pool, *id).await?;
+        }
+        Ok(())
+    })
+    .await;
+    let (batched, summaries) = time(|| get_workout_summaries(&pool, &ids)).await;
+    assert_eq!(summaries.len(), ids.len());
+
+    println!("{:<36} {:>13} {:>9}", "", "median", "speedup");
+    report("per-workout queries (before)", baseline, baseline);
+    report("get_user_progress", grouped, baseline);
+    report("get_workout_progress", progress_time, baseline);
+    println!();
+    report("get_workout_summary per workout", single, single);
+    report("get_workout_summaries", batched, single);
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::auth::verify_password;
+use crate::db::account::{delete_account, export_account, get_account_profile, get_password_hash};
+use crate::middleware::auth::AuthUser;
+use crate::models::account::{AccountExport, DeleteAccount};
+
+// GET /api/me/export
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/export",
+    tag = "Users",
+    responses(
+        (status = 200, description = "Everything stored about you", body = AccountExport),
+    ),
+)]
+pub async fn export_account_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<AccountExport>, (StatusCode, String)> {
+    tracing::info!("Exporting account data for user {}", auth.user_id);
+
+    let user = get_account_profile(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Account not found".to_string()))?;
+
+    let archive = export_account(&pool, user)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(archive))
+}
+
+// DELETE /api/me (body: the account password as confirmation)
+#[utoipa::path(
+    delete,
+    path = "/api/v1/me",
+    tag = "Users",
+    request_body = DeleteAccount,
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 401, description = "Wrong password"),
+    ),
+)]
+pub async fn delete_account_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Json(confirmation): Json<DeleteAccount>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    let password_hash = get_password_hash(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Account not found".to_string()))?;
+
+    if !verify_password(&password_hash, &confirmation.password) {
+        tracing::warn!("Rejected account deletion for user {}: wrong password", auth.user_id);
+        return Err((StatusCode::FORBIDDEN, "Password does not match".to_string()));
+    }
+
+    tracing::info!("Deleting account of user {}", auth.user_id);
+
+    delete_account(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(format!("Account {} deleted", auth.user_id)))
+}
@@ -0,0 +1,177 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::db::analytics::{
+    get_fatigue_report, get_muscle_volume_report, get_volume_targets, set_volume_target,
+    parse_date, DEFAULT_ACWR_THRESHOLD, DEFAULT_STALL_SESSIONS, DEFAULT_TREND_WEEKS, MAX_TREND_WEEKS,
+};
+use crate::db::rollups::get_rollups;
+use crate::middleware::auth::AuthUser;
+use crate::models::analytics::{
+    FatigueQuery, FatigueReport, MuscleVolumeQuery, MuscleVolumeReport, NewVolumeTarget,
+    RollupQuery, RollupRow, VolumeTarget,
+};
+
+// GET /api/analytics/fatigue
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/fatigue",
+    tag = "Analytics",
+    params(FatigueQuery),
+    responses(
+        (status = 200, description = "Stall, regression and workload spike warnings", body = FatigueReport),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn fatigue_analytics(
+    auth: AuthUser,
+    Query(query): Query<FatigueQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<FatigueReport>, (StatusCode, String)> {
+    tracing::info!("Fetching fatigue analytics for user {}", auth.user_id);
+
+    let stall_sessions = query.stall_sessions.unwrap_or(DEFAULT_STALL_SESSIONS);
+    let acwr_threshold = query.acwr_threshold.unwrap_or(DEFAULT_ACWR_THRESHOLD);
+
+    if stall_sessions == 0 || acwr_threshold <= 0.0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "stall_sessions and acwr_threshold must be positive".into(),
+        ));
+    }
+
+    let report = get_fatigue_report(&pool, auth.user_id, stall_sessions, acwr_threshold)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(report))
+}
+
+// GET /api/analytics/muscle_volume
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/muscle_volume",
+    tag = "Analytics",
+    params(MuscleVolumeQuery),
+    responses(
+        (status = 200, description = "Weekly hard sets per muscle against targets", body = MuscleVolumeReport),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn muscle_volume_analytics(
+    auth: AuthUser,
+    Query(query): Query<MuscleVolumeQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<MuscleVolumeReport>, (StatusCode, String)> {
+    tracing::info!("Fetching muscle volume analytics for user {}", auth.user_id);
+
+    let weeks = query.weeks.unwrap_or(DEFAULT_TREND_WEEKS);
+    if !(1..=MAX_TREND_WEEKS).contains(&weeks) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("weeks must be between 1 and {}", MAX_TREND_WEEKS),
+        ));
+    }
+
+    let report = get_muscle_volume_report(&pool, auth.user_id, weeks)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(report))
+}
+
+// GET /api/analytics/rollups
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/rollups",
+    tag = "Analytics",
+    params(RollupQuery),
+    responses(
+        (status = 200, description = "Daily or weekly totals, overall or per exercise or muscle group", body = [RollupRow]),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn rollup_analytics(
+    auth: AuthUser,
+    Query(query): Query<RollupQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<RollupRow>>, (StatusCode, String)> {
+    tracing::info!("Fetching analytics rollups for user {}", auth.user_id);
+
+    for date in [&query.from, &query.to].into_iter().flatten() {
+        if parse_date(date).is_none() {
+            return Err((StatusCode::BAD_REQUEST, "from and to must be YYYY-MM-DD".into()));
+        }
+    }
+
+    let rows = get_rollups(
+        &pool,
+        auth.user_id,
+        query.period.unwrap_or_default(),
+        query.by.unwrap_or_default(),
+        query.from.as_deref(),
+        query.to.as_deref(),
+    )
+    .await
+    .map_err(internal_error)?;
+
+    Ok(Json(rows))
+}
+
+// GET /api/volume_targets
+#[utoipa::path(
+    get,
+    path = "/api/v1/volume_targets",
+    tag = "Analytics",
+    responses(
+        (status = 200, description = "Your MEV/MAV/MRV targets", body = [VolumeTarget]),
+    ),
+)]
+pub async fn list_volume_targets(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<VolumeTarget>>, (StatusCode, String)> {
+    let targets = get_volume_targets(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(targets))
+}
+
+// PUT /api/volume_targets/:muscle_group
+#[utoipa::path(
+    put,
+    path = "/api/v1/volume_targets/{muscle_group}",
+    tag = "Analytics",
+    params(("muscle_group" = String, Path, description = "Muscle group")),
+    request_body = NewVolumeTarget,
+    responses(
+        (status = 200, description = "The saved target", body = VolumeTarget),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn set_volume_target_route(
+    auth: AuthUser,
+    Path(muscle_group): Path<String>,
+    State(pool): State<SqlitePool>,
+    Json(target): Json<NewVolumeTarget>,
+) -> Result<Json<VolumeTarget>, (StatusCode, String)> {
+    target.validate()
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    tracing::info!(
+        "Setting {} volume target for user {}",
+        muscle_group,
+        auth.user_id
+    );
+
+    let target = set_volume_target(&pool, auth.user_id, muscle_group.trim(), &target)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(target))
+}
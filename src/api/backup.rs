@@ -0,0 +1,98 @@
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::backup::{parse_backup, to_ndjson};
+use crate::db::backup::{create_backup, restore_backup, RestoreError, BACKUP_TABLES};
+use crate::middleware::auth::AdminUser;
+use crate::models::backup::{BackupFormat, BackupQuery, RestoreQuery, RestoreReport};
+
+// Whole-instance archives are bigger than a single user's import
+pub const MAX_BACKUP_BYTES: usize = 200 * 1024 * 1024;
+
+// GET /api/admin/backup
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/backup",
+    tag = "Admin",
+    params(BackupQuery),
+    responses(
+        (status = 200, description = "Every table as a versioned archive (JSON, or NDJSON with format=ndjson)", body = Object),
+        (status = 403, description = "Admin access required"),
+    ),
+)]
+pub async fn backup_route(
+    admin: AdminUser,
+    Query(query): Query<BackupQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Response, (StatusCode, String)> {
+    tracing::info!("Creating {:?} backup for admin {}", query.format, admin.user_id);
+
+    let archive = create_backup(&pool).await.map_err(internal_error)?;
+
+    let response = match query.format {
+        BackupFormat::Json => (
+            [(header::CONTENT_DISPOSITION, "attachment; filename=\"gymbuddy-backup.json\"")],
+            Json(archive),
+        )
+            .into_response(),
+        BackupFormat::Ndjson => {
+            let order: Vec<&str> = BACKUP_TABLES.iter().map(|t| t.name).collect();
+            (
+                [
+                    (header::CONTENT_TYPE, "application/x-ndjson"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"gymbuddy-backup.ndjson\""),
+                ],
+                to_ndjson(&archive, &order),
+            )
+                .into_response()
+        }
+    };
+
+    Ok(response)
+}
+
+// POST /api/admin/restore (body: a JSON or NDJSON archive from /api/admin/backup)
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/restore",
+    tag = "Admin",
+    params(RestoreQuery),
+    request_body = String,
+    responses(
+        (status = 200, description = "Rows restored per table", body = RestoreReport),
+        (status = 422, description = "Invalid archive or other schema version"),
+        (status = 403, description = "Admin access required"),
+        (status = 409, description = "Login emails already exist; use replace=true"),
+    ),
+)]
+pub async fn restore_route(
+    admin: AdminUser,
+    Query(query): Query<RestoreQuery>,
+    State(pool): State<SqlitePool>,
+    body: String,
+) -> Result<Json<RestoreReport>, (StatusCode, String)> {
+    let archive = parse_backup(&body)
+        .map_err(|message| (StatusCode::UNPROCESSABLE_ENTITY, message))?;
+
+    tracing::info!(
+        "Restoring backup from {} for admin {} (replace: {})",
+        archive.header.created_at,
+        admin.user_id,
+        query.replace
+    );
+
+    let report = restore_backup(&pool, &archive, query.replace)
+        .await
+        .map_err(|e| match e {
+            RestoreError::Invalid(message) => (StatusCode::UNPROCESSABLE_ENTITY, message),
+            RestoreError::Conflict(message) => (StatusCode::CONFLICT, message),
+            RestoreError::Database(e) => internal_error(e),
+        })?;
+
+    Ok(Json(report))
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::{find_own_workout, internal_error};
+use crate::db::connection::get_workout_entries_by_workout_id;
+use crate::db::entry_groups::{
+    create_entry_group, delete_entry_group, get_groups_for_workout, reorder_entries,
+};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    entry_group::{EntryGroup, EntryOrder, NewEntryGroup},
+    workout_entry::WorkoutEntryDetailed,
+};
+
+// GET /api/workouts/:id/groups
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts/{id}/groups",
+    tag = "Workouts",
+    params(("id" = i64, Path, description = "Workout id")),
+    responses(
+        (status = 200, description = "Supersets, giant sets and circuits", body = [EntryGroup]),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn list_entry_groups(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<EntryGroup>>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    let groups = get_groups_for_workout(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(groups))
+}
+
+// POST /api/workouts/:id/groups
+#[utoipa::path(
+    post,
+    path = "/api/v1/workouts/{id}/groups",
+    tag = "Workouts",
+    params(("id" = i64, Path, description = "Workout id")),
+    request_body = NewEntryGroup,
+    responses(
+        (status = 200, description = "The new group", body = EntryGroup),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn create_entry_group_route(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(new_group): Json<NewEntryGroup>,
+) -> Result<Json<EntryGroup>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    let unique: HashSet<_> = new_group.entry_ids.iter().collect();
+    if unique.len() != new_group.entry_ids.len() {
+        return Err((StatusCode::BAD_REQUEST, "entry_ids contains duplicates".into()));
+    }
+    if new_group.entry_ids.len() < new_group.kind.min_entries() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("A {:?} needs at least {} entries", new_group.kind, new_group.kind.min_entries()),
+        ));
+    }
+    if new_group.rounds.is_some_and(|r| r <= 0) || new_group.duration_seconds.is_some_and(|d| d <= 0) {
+        return Err((StatusCode::BAD_REQUEST, "rounds and duration_seconds must be positive".into()));
+    }
+
+    let entries = get_workout_entries_by_workout_id(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    for entry_id in &new_group.entry_ids {
+        match entries.iter().find(|e| e.id == *entry_id) {
+            None => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Entry {} is not part of workout {}", entry_id, workout_id),
+                ));
+            }
+            Some(WorkoutEntryDetailed { group_id: Some(group_id), .. }) => {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Entry {} is already in group {}", entry_id, group_id),
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+
+    tracing::info!(
+        "Grouping {} entries of workout {} as {:?}",
+        new_group.entry_ids.len(),
+        workout_id,
+        new_group.kind
+    );
+
+    let group = create_entry_group(&pool, workout_id, &new_group)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(group))
+}
+
+// DELETE /api/workouts/:id/groups/:group_id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workouts/{id}/groups/{group_id}",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("group_id" = i64, Path, description = "Group id"),
+    ),
+    responses(
+        (status = 200, description = "Ungrouped", body = String),
+        (status = 404, description = "Group not found"),
+    ),
+)]
+pub async fn delete_entry_group_route(
+    auth: AuthUser,
+    Path((workout_id, group_id)): Path<(i64, i64)>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    let deleted = delete_entry_group(&pool, workout_id, group_id)
+        .await
+        .map_err(internal_error)?;
+
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, format!("Group {} not found", group_id)));
+    }
+
+    Ok(Json(format!("Group {} deleted", group_id)))
+}
+
+// PUT /api/workouts/:id/order
+#[utoipa::path(
+    put,
+    path = "/api/v1/workouts/{id}/order",
+    tag = "Workouts",
+    params(("id" = i64, Path, description = "Workout id")),
+    request_body = EntryOrder,
+    responses(
+        (status = 200, description = "Entries in their new order", body = [WorkoutEntryDetailed]),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn reorder_entries_route(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(order): Json<EntryOrder>,
+) -> Result<Json<Vec<WorkoutEntryDetailed>>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    let entries = get_workout_entries_by_workout_id(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    let expected: HashSet<_> = entries.iter().map(|e| e.id).collect();
+    let given: HashSet<_> = order.entry_ids.iter().copied().collect();
+
+    if given != expected || given.len() != order.entry_ids.len() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "entry_ids must list every entry of the workout exactly once".into(),
+        ));
+    }
+
+    reorder_entries(&pool, workout_id, &order.entry_ids)
+        .await
+        .map_err(internal_error)?;
+
+    let entries = get_workout_entries_by_workout_id(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(entries))
+}
@@ -0,0 +1,62 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use axum::http::{header, HeaderMap, HeaderValue, StatusCode};
+
+// Strong validator of one version of a row
+pub(crate) fn etag(id: i64, version: i64) -> String {
+    format!("\"{}-{}\"", id, version)
+}
+
+// Validator of a collection, from the (id, version) of every row in it
+pub(crate) fn collection_etag(prefix: &str, rows: &[(i64, i64)]) -> String {
+    let mut hasher = DefaultHasher::new();
+    rows.hash(&mut hasher);
+    format!("\"{}-{:016x}\"", prefix, hasher.finish())
+}
+
+fn header_tags(headers: &HeaderMap, name: header::HeaderName) -> Option<Vec<&str>> {
+    let value = headers.get(name)?.to_str().ok()?;
+    Some(value.split(',').map(str::trim).filter(|t| !t.is_empty()).collect())
+}
+
+// Checks If-Match against the current version. Ok(Some(version)) means the write must
+// still find the row at that version; Ok(None) means no precondition was given.
+pub(crate) fn if_match(
+    headers: &HeaderMap,
+    id: i64,
+    version: i64,
+) -> Result<Option<i64>, (StatusCode, String)> {
+    let Some(tags) = header_tags(headers, header::IF_MATCH) else {
+        return Ok(None);
+    };
+
+    let current = etag(id, version);
+    if tags.iter().any(|t| *t == "*" || *t == current) {
+        Ok(Some(version))
+    } else {
+        Err(precondition_failed())
+    }
+}
+
+pub(crate) fn precondition_failed() -> (StatusCode, String) {
+    (
+        StatusCode::PRECONDITION_FAILED,
+        "The resource has changed since it was read; fetch it again".to_string(),
+    )
+}
+
+// If-None-Match uses weak comparison, so W/ prefixes are ignored
+pub(crate) fn not_modified(headers: &HeaderMap, current: &str) -> bool {
+    header_tags(headers, header::IF_NONE_MATCH).is_some_and(|tags| {
+        tags.iter()
+            .any(|t| *t == "*" || t.trim_start_matches("W/") == current)
+    })
+}
+
+pub(crate) fn etag_header(tag: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = HeaderValue::from_str(tag) {
+        headers.insert(header::ETAG, value);
+    }
+    headers
+}
@@ -0,0 +1,115 @@
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use futures::StreamExt;
+use sqlx::SqlitePool;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use crate::db::analytics::parse_date;
+use crate::db::export::stream_workout_history;
+use crate::middleware::auth::AuthUser;
+use crate::models::export::{ExportQuery, ExportRow, WeightUnit};
+
+const CSV_HEADER: [&str; 9] = [
+    "date", "workout_id", "exercise", "muscle_group", "sets", "reps", "weight", "unit", "notes",
+];
+
+// Rows buffered between the database task and the response body
+const EXPORT_BUFFER_ROWS: usize = 64;
+
+fn csv_record<I, T>(fields: I) -> Bytes
+where
+    I: IntoIterator<Item = T>,
+    T: AsRef<[u8]>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields).expect("writing to a Vec cannot fail");
+    Bytes::from(writer.into_inner().expect("writing to a Vec cannot fail"))
+}
+
+fn export_record(row: &ExportRow, unit: WeightUnit) -> Bytes {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+
+    csv_record([
+        row.date.clone(),
+        row.workout_id.to_string(),
+        optional(row.exercise.clone()),
+        optional(row.muscle_group.clone()),
+        optional(row.sets.map(|s| s.to_string())),
+        optional(row.reps.map(|r| r.to_string())),
+        optional(row.weight.map(|w| unit.from_kg(w).to_string())),
+        if row.exercise.is_some() { unit.as_str().to_string() } else { String::new() },
+        optional(row.notes.clone()),
+    ])
+}
+
+// GET /api/export/workouts.csv
+#[utoipa::path(
+    get,
+    path = "/api/v1/export/workouts.csv",
+    tag = "Export",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Your workout history, one set per row", body = String, content_type = "text/csv"),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn export_workouts_csv(
+    auth: AuthUser,
+    Query(query): Query<ExportQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    for date in [&query.from, &query.to].into_iter().flatten() {
+        if parse_date(date).is_none() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Invalid date '{}', expected YYYY-MM-DD", date),
+            ));
+        }
+    }
+
+    tracing::info!("Exporting workout history for user {}", auth.user_id);
+
+    let (sender, receiver) = mpsc::channel::<Result<Bytes, std::io::Error>>(EXPORT_BUFFER_ROWS);
+    let user_id = auth.user_id;
+
+    tokio::spawn(async move {
+        if sender.send(Ok(csv_record(CSV_HEADER))).await.is_err() {
+            return;
+        }
+
+        let mut rows = stream_workout_history(
+            &pool,
+            user_id,
+            query.from.as_deref(),
+            query.to.as_deref(),
+        );
+
+        while let Some(row) = rows.next().await {
+            let chunk = match row {
+                Ok(row) => Ok(export_record(&row, query.unit)),
+                Err(e) => {
+                    // Cutting the body short tells the client the file is incomplete
+                    tracing::error!("CSV export for user {} failed: {:?}", user_id, e);
+                    Err(std::io::Error::other(e))
+                }
+            };
+
+            let failed = chunk.is_err();
+            if sender.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+            (header::CONTENT_DISPOSITION, "attachment; filename=\"workouts.csv\""),
+        ],
+        StreamBody::new(ReceiverStream::new(receiver)),
+    ))
+}
@@ -0,0 +1,40 @@
+use axum::{extract::State, Extension, Json};
+use sqlx::SqlitePool;
+use crate::graphql::{request_data, GymBuddySchema};
+use crate::middleware::auth::AuthUser;
+
+// POST /api/graphql
+#[utoipa::path(
+    post,
+    path = "/api/graphql",
+    tag = "GraphQL",
+    request_body(content = Object, description = "{\"query\", \"variables\", \"operationName\"}"),
+    responses(
+        (status = 200, description = "{\"data\", \"errors\"}; queries over the depth or complexity limit only get errors", body = Object),
+        (status = 401, description = "Missing or invalid token"),
+    ),
+)]
+pub async fn graphql_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Extension(schema): Extension<GymBuddySchema>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    tracing::info!("GraphQL query for user {}", auth.user_id);
+
+    Json(schema.execute(request_data(request, pool, auth)).await)
+}
+
+// GET /api/graphql/schema (SDL, for client codegen)
+#[utoipa::path(
+    get,
+    path = "/api/graphql/schema",
+    tag = "GraphQL",
+    responses(
+        (status = 200, description = "The schema in GraphQL SDL", body = String, content_type = "text/plain"),
+    ),
+    security(()),
+)]
+pub async fn graphql_schema_route(Extension(schema): Extension<GymBuddySchema>) -> String {
+    schema.sdl()
+}
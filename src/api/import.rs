@@ -0,0 +1,62 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::api::webhooks::records_created;
+use crate::db::import::import_history;
+use crate::import::{detect_source, parse_import};
+use crate::live::LiveHub;
+use crate::middleware::auth::AuthUser;
+use crate::models::import::{ImportQuery, ImportReport};
+
+// Exports spanning years of training are larger than axum's default body limit
+pub const MAX_IMPORT_BYTES: usize = 20 * 1024 * 1024;
+
+// POST /api/import (body: the CSV file exported by Strong or Hevy)
+#[utoipa::path(
+    post,
+    path = "/api/v1/import",
+    tag = "Export",
+    params(ImportQuery),
+    request_body = String,
+    responses(
+        (status = 200, description = "What was (or, on a dry run, would be) imported", body = ImportReport),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn import_route(
+    auth: AuthUser,
+    Query(query): Query<ImportQuery>,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+    body: String,
+) -> Result<Json<ImportReport>, (StatusCode, String)> {
+    let source = query
+        .source
+        .or_else(|| detect_source(&body))
+        .ok_or((
+            StatusCode::BAD_REQUEST,
+            "Could not tell whether this is a Strong or Hevy export; pass ?source=".to_string(),
+        ))?;
+
+    let parsed = parse_import(&body, source, query.unit.unwrap_or_default())
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    tracing::info!(
+        "Importing {} sets from {:?} for user {} (dry run: {})",
+        parsed.sets.len(),
+        source,
+        auth.user_id,
+        query.dry_run
+    );
+
+    let (report, created) = import_history(&pool, auth.user_id, source, &parsed, query.dry_run)
+        .await
+        .map_err(internal_error)?;
+    records_created(&pool, &live, auth.user_id, &created).await;
+
+    Ok(Json(report))
+}
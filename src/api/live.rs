@@ -0,0 +1,124 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
+    Extension,
+};
+use futures::{SinkExt, StreamExt};
+use sqlx::SqlitePool;
+use crate::api::routes::{find_own_workout, internal_error};
+use crate::db::connection::get_workout_entries_by_workout_id;
+use crate::jwt::verify_jwt;
+use crate::live::{LiveHub, Subscription};
+use crate::api::sessions::{start_timer, stop_timer};
+use crate::models::live::{ClientMessage, LiveEvent, LiveQuery, RestTimerAction};
+
+// Same JWT as AuthUser, from the Authorization header or ?token=
+pub(crate) fn live_user(headers: &HeaderMap, query: &LiveQuery) -> Option<i64> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or(query.token.as_deref())?;
+
+    verify_jwt(token)
+}
+
+// GET /api/workouts/:id/live (WebSocket)
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts/{id}/live",
+    tag = "Live",
+    params(("id" = i64, Path, description = "Workout id"), LiveQuery),
+    responses(
+        (status = 101, description = "WebSocket of LiveEvent messages; send ClientMessage"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn live_session(
+    ws: WebSocketUpgrade,
+    Path(workout_id): Path<i64>,
+    Query(query): Query<LiveQuery>,
+    headers: HeaderMap,
+    State(pool): State<SqlitePool>,
+    Extension(hub): Extension<LiveHub>,
+) -> Result<Response, (StatusCode, String)> {
+    let user_id = live_user(&headers, &query)
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string()))?;
+
+    find_own_workout(&pool, user_id, workout_id).await?;
+
+    // Subscribe before reading the snapshot, so a change made right after it is still sent
+    let events = hub.subscribe(workout_id);
+    let entries = get_workout_entries_by_workout_id(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    tracing::info!("Opening live session on workout {} for user {}", workout_id, user_id);
+
+    let snapshot = LiveEvent::Snapshot { workout_id, entries };
+    Ok(ws.on_upgrade(move |socket| run_session(socket, pool, hub, events, user_id, workout_id, snapshot)))
+}
+
+fn to_message(event: &LiveEvent) -> Option<Message> {
+    serde_json::to_string(event).ok().map(Message::Text)
+}
+
+// Rest timer commands from a device; the resulting events reach every device, the sender too
+async fn handle_message(pool: &SqlitePool, hub: &LiveHub, user_id: i64, workout_id: i64, text: &str) {
+    let result = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(ClientMessage::RestTimer { action: RestTimerAction::Start, entry_id: Some(entry_id), seconds }) => {
+            start_timer(pool, hub, user_id, workout_id, entry_id, seconds).await.map(|_| ())
+        }
+        Ok(ClientMessage::RestTimer { action: RestTimerAction::Start, entry_id: None, .. }) => {
+            Err((StatusCode::BAD_REQUEST, "entry_id is required to start a timer".to_string()))
+        }
+        Ok(ClientMessage::RestTimer { action: RestTimerAction::Stop, .. }) => {
+            stop_timer(pool, hub, workout_id).await.map(|_| ())
+        }
+        Err(e) => Err((StatusCode::BAD_REQUEST, e.to_string())),
+    };
+
+    if let Err((_, message)) = result {
+        tracing::warn!("Ignoring live message on workout {}: {}", workout_id, message);
+    }
+}
+
+async fn run_session(
+    socket: WebSocket,
+    pool: SqlitePool,
+    hub: LiveHub,
+    mut events: Subscription,
+    user_id: i64,
+    workout_id: i64,
+    snapshot: LiveEvent,
+) {
+    let (mut sender, mut receiver) = socket.split();
+
+    if let Some(message) = to_message(&snapshot)
+        && sender.send(message).await.is_err()
+    {
+        return;
+    }
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else { break };
+                let Some(message) = to_message(&event) else { continue };
+                if sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+            message = receiver.next() => match message {
+                Some(Ok(Message::Text(text))) => handle_message(&pool, &hub, user_id, workout_id, &text).await,
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {} // pings are answered by axum
+            },
+        }
+    }
+}
@@ -1,2 +1,22 @@
 pub mod routes; // points to routes.rs in api 
+pub mod progression;
+pub mod templates;
+pub mod analytics;
+pub mod recovery;
+pub mod entry_groups;
+pub mod export;
+pub mod import;
+pub mod account;
+pub mod report;
+pub mod backup;
+pub mod schedule;
+pub mod pagination;
+pub mod etag;
+pub mod sync;
+pub mod live;
+pub mod sessions;
+pub mod webhooks;
+pub mod openapi;
+pub mod versioning;
+pub mod graphql;
 pub use routes::create_api_router;
@@ -0,0 +1,221 @@
+use axum::{response::Html, Json};
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme},
+    Modify, OpenApi,
+};
+use crate::api::{
+    account, analytics, backup, entry_groups, export, import, live, progression, recovery, report,
+    graphql, routes, schedule, sessions, sync, templates, webhooks,
+};
+use crate::models::{
+    account::*, analytics::*, backup::*, entry_group::*, exercise::*, export::*, import::*,
+    live::*, progression::*, recovery::*, rest::*, schedule::*, sync::*, template::*, user::*,
+    webhook::*, workout::*, workout_entry::*,
+};
+
+// The OpenAPI 3 document, generated from the #[utoipa::path] attribute on every handler.
+// A handler added to create_api_router has to be listed here too; tests/openapi.rs checks that.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "GymBuddy API",
+        description = "Workout logging, analytics and training tools. This document describes v1; \
+            /api/v2 serves the same operations, with workout entries as WorkoutEntryV2 and \
+            WorkoutEntryDetailedV2.",
+    ),
+    paths(
+        routes::health_check,
+        openapi_json,
+        api_docs,
+        routes::register_user,
+        routes::login_user,
+        routes::list_users,
+        routes::create_user,
+        routes::get_user_by_id,
+        routes::patch_user,
+        routes::get_user_progress_route,
+        account::export_account_route,
+        account::delete_account_route,
+        routes::list_exercises,
+        routes::create_exercise,
+        progression::get_next_session_route,
+        progression::get_progression_rule_route,
+        progression::set_progression_rule_route,
+        templates::list_templates,
+        templates::create_template,
+        templates::get_template,
+        templates::start_template_route,
+        routes::list_workouts,
+        routes::create_workout,
+        routes::get_workout_by_id_route,
+        routes::update_workout,
+        routes::patch_workout,
+        routes::delete_workout,
+        routes::finish_workout,
+        routes::get_entries_for_workout,
+        routes::get_workout_summary_route,
+        entry_groups::list_entry_groups,
+        entry_groups::create_entry_group_route,
+        entry_groups::delete_entry_group_route,
+        entry_groups::reorder_entries_route,
+        live::live_session,
+        routes::list_workout_entries_detailed,
+        routes::create_workout_entry,
+        routes::get_workout_entry_route,
+        routes::update_workout_entry,
+        routes::patch_workout_entry,
+        routes::delete_workout_entry,
+        routes::weekly_analytics,
+        analytics::fatigue_analytics,
+        analytics::muscle_volume_analytics,
+        analytics::rollup_analytics,
+        analytics::list_volume_targets,
+        analytics::set_volume_target_route,
+        report::progress_report,
+        export::export_workouts_csv,
+        import::import_route,
+        recovery::recovery_route,
+        recovery::training_suggestions_route,
+        recovery::list_recovery_settings,
+        recovery::set_recovery_setting_route,
+        sessions::get_rest_timer_route,
+        sessions::start_rest_timer_route,
+        sessions::stop_rest_timer_route,
+        sessions::session_events,
+        sessions::list_rest_defaults,
+        sessions::set_rest_default_route,
+        sync::sync_pull,
+        sync::sync_push,
+        webhooks::list_webhooks,
+        webhooks::create_webhook_route,
+        webhooks::delete_webhook_route,
+        webhooks::list_deliveries,
+        schedule::list_schedule,
+        schedule::create_schedule,
+        schedule::delete_schedule,
+        schedule::create_calendar_feed,
+        schedule::revoke_calendar_feed,
+        schedule::calendar_feed,
+        backup::backup_route,
+        backup::restore_route,
+        graphql::graphql_route,
+        graphql::graphql_schema_route,
+    ),
+    components(schemas(
+        routes::HealthResponse, routes::WeeklyAnalytics,
+        User, NewUser, LoginUser, UserPatch,
+        AccountProfile, ExportedWorkout, ExportedProgressionRule, AccountExport, DeleteAccount,
+        Exercise, NewExercise,
+        ProgressionRule, SessionSnapshot, NextSessionRecommendation,
+        WorkoutTemplate, TemplateExercise, WorkoutTemplateDetailed, NewTemplateExercise,
+        NewWorkoutTemplate, StartTemplate, StartedWorkout,
+        Workout, NewWorkout, WorkoutPatch,
+        WorkoutEntry, NewWorkoutEntry, WorkoutEntryDetailed, WorkoutEntryPatch,
+        WorkoutEntryV2, EntryExercise, WorkoutEntryDetailedV2,
+        GroupKind, EntryGroup, NewEntryGroup, EntryOrder,
+        FatigueWarningKind, FatigueWarning, DeloadSuggestion, FatigueReport,
+        VolumeTarget, NewVolumeTarget, VolumeStatus, MuscleWeekSets, MuscleVolumeStatus,
+        MuscleVolumeReport,
+        WorkoutTotals, SummaryExercise, SummaryGroup, WorkoutSummary, ExerciseVolume, WorkoutProgress,
+        RollupPeriod, RollupGroup, RollupRow,
+        WeightUnit, ImportSource, MappingStatus, ExerciseMapping, ImportReport,
+        RecoverySetting, NewRecoverySetting, MuscleRecovery, RecoveryReport, MuscleSuggestion,
+        TemplateSuggestion, TrainingSuggestions,
+        RestDefault, SetRestDefault, StartRestTimer, RestTimer, FinishedRest,
+        RestTimerAction, LiveEvent, ClientMessage,
+        SyncWorkout, SyncEntry, Tombstone, SyncPull, SyncWorkoutData, SyncEntryData, SyncChange,
+        SyncPush, AppliedChange, ConflictReason, SyncRecord, SyncConflict, SyncPushResult,
+        Webhook, CreatedWebhook, NewWebhook, WebhookDelivery, PersonalRecordAchieved, WebhookEvent,
+        ScheduledSession, NewScheduledSession, CalendarFeed,
+        BackupFormat, RestoredTable, RestoreReport,
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "Auth", description = "Registration, and login for the JWT the other endpoints want"),
+        (name = "Users"),
+        (name = "Exercises"),
+        (name = "Templates"),
+        (name = "Workouts"),
+        (name = "Workout Entries"),
+        (name = "Live", description = "Shared workout sessions over WebSocket"),
+        (name = "Rest Timers"),
+        (name = "Analytics"),
+        (name = "Recovery"),
+        (name = "Export", description = "CSV export and import, printable reports"),
+        (name = "Sync", description = "Offline sync for the mobile apps"),
+        (name = "Webhooks"),
+        (name = "Schedule"),
+        (name = "Admin"),
+        (name = "GraphQL", description = "Nested reads of your workouts in one request"),
+        (name = "Health"),
+        (name = "Docs"),
+    ),
+)]
+pub struct ApiDoc;
+
+// Every endpoint wants the JWT from /api/login unless it opts out with security(())
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "bearer",
+                SecurityScheme::Http(
+                    HttpBuilder::new()
+                        .scheme(HttpAuthScheme::Bearer)
+                        .bearer_format("JWT")
+                        .build(),
+                ),
+            );
+        }
+        openapi.security = Some(vec![SecurityRequirement::new("bearer", Vec::<String>::new())]);
+    }
+}
+
+// GET /api/openapi.json
+#[utoipa::path(
+    get,
+    path = "/api/openapi.json",
+    tag = "Docs",
+    responses(
+        (status = 200, description = "This document", body = Object),
+    ),
+    security(()),
+)]
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
+
+// GET /api/docs (Swagger UI reading /api/openapi.json)
+#[utoipa::path(
+    get,
+    path = "/api/docs",
+    tag = "Docs",
+    responses(
+        (status = 200, description = "Interactive documentation", body = String, content_type = "text/html"),
+    ),
+    security(()),
+)]
+pub async fn api_docs() -> Html<&'static str> {
+    Html(DOCS_PAGE)
+}
+
+// Swagger UI runs on the API origin, where it can use the bearer token, so the assets are
+// pinned to one exact release instead of a moving tag
+const DOCS_PAGE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>GymBuddy API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui.css" crossorigin="anonymous">
+</head>
+<body>
+  <div id="docs"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5.17.14/swagger-ui-bundle.js" crossorigin="anonymous"></script>
+  <script>
+    window.ui = SwaggerUIBundle({ url: "/api/openapi.json", dom_id: "#docs", persistAuthorization: true });
+  </script>
+</body>
+</html>
+"##;
@@ -0,0 +1,45 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode, Uri},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use crate::api::routes::internal_error;
+use crate::db::pagination::PageError;
+use crate::models::pagination::Page;
+
+pub(crate) fn page_error(e: PageError) -> (StatusCode, String) {
+    match e {
+        PageError::Invalid(message) => (StatusCode::BAD_REQUEST, message),
+        PageError::Database(e) => internal_error(e),
+    }
+}
+
+// Same request with the cursor swapped for the next one
+fn next_page_uri(uri: &Uri, cursor: &str) -> String {
+    let mut params: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty() && !p.starts_with("cursor="))
+        .collect();
+    let cursor = format!("cursor={}", cursor);
+    params.push(&cursor);
+
+    format!("{}?{}", uri.path(), params.join("&"))
+}
+
+// The body stays a plain array; paging metadata goes in the Link and X-Next-Cursor headers
+pub(crate) fn paged_response<T: Serialize>(uri: &Uri, page: Page<T>) -> Response {
+    let mut headers = HeaderMap::new();
+
+    if let Some(cursor) = &page.next_cursor {
+        let link = format!("<{}>; rel=\"next\"", next_page_uri(uri, cursor));
+        if let (Ok(link), Ok(cursor)) = (HeaderValue::from_str(&link), HeaderValue::from_str(cursor)) {
+            headers.insert(header::LINK, link);
+            headers.insert("x-next-cursor", cursor);
+        }
+    }
+
+    (headers, Json(page.items)).into_response()
+}
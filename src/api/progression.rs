@@ -0,0 +1,114 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::db::connection::get_exercise_by_id;
+use crate::db::progression::{get_next_session, get_progression_rule, set_progression_rule};
+use crate::middleware::auth::AuthUser;
+use crate::models::{
+    exercise::Exercise,
+    progression::{NextSessionRecommendation, ProgressionRule},
+};
+
+async fn find_exercise(pool: &SqlitePool, id: i64) -> Result<Exercise, (StatusCode, String)> {
+    get_exercise_by_id(pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, format!("Exercise {} not found", id)))
+}
+
+// GET /api/exercises/:id/next
+#[utoipa::path(
+    get,
+    path = "/api/v1/exercises/{id}/next",
+    tag = "Exercises",
+    params(("id" = i64, Path, description = "Exercise id")),
+    responses(
+        (status = 200, description = "Recommended weight and reps", body = NextSessionRecommendation),
+        (status = 404, description = "Exercise not found"),
+    ),
+)]
+pub async fn get_next_session_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<NextSessionRecommendation>, (StatusCode, String)> {
+    tracing::info!(
+        "Recommending next session of exercise {} for user {}",
+        id,
+        auth.user_id
+    );
+
+    let exercise = find_exercise(&pool, id).await?;
+
+    let recommendation = get_next_session(&pool, auth.user_id, &exercise, None)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(recommendation))
+}
+
+// GET /api/exercises/:id/progression
+#[utoipa::path(
+    get,
+    path = "/api/v1/exercises/{id}/progression",
+    tag = "Exercises",
+    params(("id" = i64, Path, description = "Exercise id")),
+    responses(
+        (status = 200, description = "Your rule, or the default", body = ProgressionRule),
+        (status = 404, description = "Exercise not found"),
+    ),
+)]
+pub async fn get_progression_rule_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<ProgressionRule>, (StatusCode, String)> {
+    find_exercise(&pool, id).await?;
+
+    let rule = get_progression_rule(&pool, auth.user_id, id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(rule))
+}
+
+// PUT /api/exercises/:id/progression
+#[utoipa::path(
+    put,
+    path = "/api/v1/exercises/{id}/progression",
+    tag = "Exercises",
+    params(("id" = i64, Path, description = "Exercise id")),
+    request_body = ProgressionRule,
+    responses(
+        (status = 200, description = "The saved rule", body = ProgressionRule),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Exercise not found"),
+    ),
+)]
+pub async fn set_progression_rule_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(rule): Json<ProgressionRule>,
+) -> Result<Json<ProgressionRule>, (StatusCode, String)> {
+    find_exercise(&pool, id).await?;
+
+    rule.validate()
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+
+    tracing::info!(
+        "Setting progression rule for exercise {} of user {}",
+        id,
+        auth.user_id
+    );
+
+    set_progression_rule(&pool, auth.user_id, id, &rule)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(rule))
+}
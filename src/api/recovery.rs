@@ -0,0 +1,107 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::db::recovery::{
+    get_recovery_report, get_recovery_settings, get_training_suggestions, set_recovery_setting,
+};
+use crate::middleware::auth::AuthUser;
+use crate::models::recovery::{
+    NewRecoverySetting, RecoveryReport, RecoverySetting, TrainingSuggestions,
+};
+
+// GET /api/recovery
+#[utoipa::path(
+    get,
+    path = "/api/v1/recovery",
+    tag = "Recovery",
+    responses(
+        (status = 200, description = "Estimated recovery per muscle group", body = RecoveryReport),
+    ),
+)]
+pub async fn recovery_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<RecoveryReport>, (StatusCode, String)> {
+    tracing::info!("Fetching muscle recovery for user {}", auth.user_id);
+
+    let report = get_recovery_report(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(report))
+}
+
+// GET /api/recovery/suggestions
+#[utoipa::path(
+    get,
+    path = "/api/v1/recovery/suggestions",
+    tag = "Recovery",
+    responses(
+        (status = 200, description = "Muscles and templates to train today", body = TrainingSuggestions),
+    ),
+)]
+pub async fn training_suggestions_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<TrainingSuggestions>, (StatusCode, String)> {
+    tracing::info!("Suggesting what to train for user {}", auth.user_id);
+
+    let suggestions = get_training_suggestions(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(suggestions))
+}
+
+// GET /api/recovery/half_lives
+#[utoipa::path(
+    get,
+    path = "/api/v1/recovery/half_lives",
+    tag = "Recovery",
+    responses(
+        (status = 200, description = "Your recovery half-lives", body = [RecoverySetting]),
+    ),
+)]
+pub async fn list_recovery_settings(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<RecoverySetting>>, (StatusCode, String)> {
+    let settings = get_recovery_settings(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(settings))
+}
+
+// PUT /api/recovery/half_lives/:muscle_group
+#[utoipa::path(
+    put,
+    path = "/api/v1/recovery/half_lives/{muscle_group}",
+    tag = "Recovery",
+    params(("muscle_group" = String, Path, description = "Muscle group")),
+    request_body = NewRecoverySetting,
+    responses(
+        (status = 200, description = "The saved half-life", body = RecoverySetting),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn set_recovery_setting_route(
+    auth: AuthUser,
+    Path(muscle_group): Path<String>,
+    State(pool): State<SqlitePool>,
+    Json(setting): Json<NewRecoverySetting>,
+) -> Result<Json<RecoverySetting>, (StatusCode, String)> {
+    if !setting.half_life_hours.is_finite() || setting.half_life_hours <= 0.0 {
+        return Err((StatusCode::BAD_REQUEST, "half_life_hours must be positive".into()));
+    }
+
+    let setting = set_recovery_setting(&pool, auth.user_id, muscle_group.trim(), &setting)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(setting))
+}
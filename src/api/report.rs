@@ -0,0 +1,65 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::Html,
+};
+use chrono::Duration;
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::db::account::get_account_profile;
+use crate::db::analytics::{parse_date, today};
+use crate::db::report::build_progress_report;
+use crate::middleware::auth::AuthUser;
+use crate::models::report::ReportQuery;
+use crate::report::render_progress_report;
+
+const DEFAULT_REPORT_DAYS: i64 = 30;
+const MAX_REPORT_DAYS: i64 = 366;
+
+// GET /api/reports/progress
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/progress",
+    tag = "Export",
+    params(ReportQuery),
+    responses(
+        (status = 200, description = "Printable progress report", body = String, content_type = "text/html"),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn progress_report(
+    auth: AuthUser,
+    Query(query): Query<ReportQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Html<String>, (StatusCode, String)> {
+    let parse = |date: &Option<String>| match date {
+        Some(date) => parse_date(date).map(Some).ok_or((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid date '{}', expected YYYY-MM-DD", date),
+        )),
+        None => Ok(None),
+    };
+
+    let to = parse(&query.to)?.unwrap_or_else(today);
+    let from = parse(&query.from)?.unwrap_or(to - Duration::days(DEFAULT_REPORT_DAYS - 1));
+
+    if from > to || (to - from).num_days() >= MAX_REPORT_DAYS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("from must not be after to, and the range is limited to {} days", MAX_REPORT_DAYS),
+        ));
+    }
+
+    tracing::info!("Fetching progress report for user {} ({} to {})", auth.user_id, from, to);
+
+    let user = get_account_profile(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Account not found".to_string()))?;
+
+    let report = build_progress_report(&pool, auth.user_id, user.username, from, to)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Html(render_progress_report(&report, query.unit)))
+}
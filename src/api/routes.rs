@@ -1,53 +1,171 @@
 use axum::{
-    extract::{Path, State},
-    routing::{get, post, delete, put},
-    Json, Router,
+    extract::{DefaultBodyLimit, OriginalUri, Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
+    Extension, Json, Router,
 };
 use serde::Serialize;
 use sqlx::SqlitePool;
+use utoipa::ToSchema;
 use crate::db::connection::*;
 use crate::models::{
-    user::{User, NewUser, LoginUser},
-    workout::{Workout, NewWorkout},
+    user::{User, NewUser, LoginUser, UserPatch},
+    workout::{Workout, NewWorkout, WorkoutPatch},
     exercise::{Exercise, NewExercise},
-    workout_entry::{WorkoutEntry, NewWorkoutEntry, WorkoutEntryDetailed},
+    workout_entry::{WorkoutEntry, NewWorkoutEntry, WorkoutEntryDetailed, WorkoutEntryPatch},
 };
 use crate::auth::{hash_password, verify_password};
-use crate::jwt::{generate_jwt, verify_jwt};
+use crate::jwt::generate_jwt;
 use crate::middleware::auth::AuthUser;
-
-#[derive(Serialize)]
-struct HealthResponse {
+use crate::middleware::idempotency::idempotency;
+use crate::api::progression::{
+    get_next_session_route, get_progression_rule_route, set_progression_rule_route,
+};
+use crate::api::account::{delete_account_route, export_account_route};
+use crate::api::report::progress_report;
+use crate::api::analytics::{
+    fatigue_analytics, list_volume_targets, muscle_volume_analytics, rollup_analytics,
+    set_volume_target_route,
+};
+use crate::db::analytics::{get_muscle_volume_report, parse_date};
+use crate::models::analytics::{MuscleVolumeStatus, WorkoutProgress, WorkoutSummary};
+use crate::db::summary::{get_workout_progress, get_workout_summary};
+use crate::api::entry_groups::{
+    create_entry_group_route, delete_entry_group_route, list_entry_groups, reorder_entries_route,
+};
+use crate::api::export::export_workouts_csv;
+use crate::api::import::{import_route, MAX_IMPORT_BYTES};
+use crate::api::backup::{backup_route, restore_route, MAX_BACKUP_BYTES};
+use crate::api::etag::{
+    collection_etag, etag, etag_header, if_match, not_modified, precondition_failed,
+};
+use crate::api::pagination::{page_error, paged_response};
+use crate::db::pagination::{
+    list_exercises_page, list_users_page, list_workout_entries_page, list_workouts_page,
+};
+use crate::models::pagination::{EntryListQuery, ExerciseListQuery, UserListQuery, WorkoutListQuery};
+use crate::api::schedule::{
+    calendar_feed, create_calendar_feed, create_schedule, delete_schedule, list_schedule,
+    revoke_calendar_feed,
+};
+use crate::api::recovery::{
+    list_recovery_settings, recovery_route, set_recovery_setting_route, training_suggestions_route,
+};
+use crate::api::live::live_session;
+use crate::api::sessions::{
+    get_rest_timer_route, list_rest_defaults, resume_rest_timers, session_events,
+    set_rest_default_route, start_rest_timer_route, stop_rest_timer_route, stop_timer,
+};
+use crate::live::LiveHub;
+use crate::models::live::LiveEvent;
+use crate::api::sync::{sync_pull, sync_push};
+use crate::api::webhooks::{
+    create_webhook_route, delete_webhook_route, list_deliveries, list_webhooks, notify,
+    entry_created,
+};
+use crate::models::webhook::WebhookEvent;
+use crate::api::openapi::{api_docs, openapi_json};
+use crate::api::graphql::{graphql_route, graphql_schema_route};
+use crate::graphql::build_schema;
+use crate::api::versioning::{deprecated, ApiVersion, CreatedEntry};
+use crate::api::templates::{create_template, get_template, list_templates, start_template_route};
+
+#[derive(Serialize, ToSchema)]
+pub struct HealthResponse {
     status: String,
     message: String,
 }
 
-#[derive(sqlx::FromRow)]
-struct UserRow {
+#[derive(Serialize, ToSchema)]
+pub struct WeeklyAnalytics {
+    total_volume: f64,
+    muscles: Vec<MuscleVolumeStatus>, // this week's hard sets per muscle, most trained first
+}
+
+// Logs the database error and hides it from the client
+pub(crate) fn internal_error(e: sqlx::Error) -> (StatusCode, String) {
+    tracing::error!("Database error: {:?}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error".to_string())
+}
+
+// Workouts are private, so someone else's workout is reported as missing
+pub(crate) async fn find_own_workout(
+    pool: &SqlitePool,
+    user_id: i64,
     id: i64,
-    password_hash: String,
+) -> Result<Workout, (StatusCode, String)> {
+    get_workout_by_id(pool, id)
+        .await
+        .map_err(internal_error)?
+        .filter(|w| w.user_id == user_id)
+        .ok_or((StatusCode::NOT_FOUND, format!("Workout {} not found", id)))
 }
 
-#[derive(Serialize)]
-struct WeeklyAnalytics {
-    total_volume: f64,
-    top_muscle: String,
+pub(crate) async fn find_own_entry(
+    pool: &SqlitePool,
+    user_id: i64,
+    id: i64,
+) -> Result<WorkoutEntry, (StatusCode, String)> {
+    let not_found = || (StatusCode::NOT_FOUND, format!("Workout entry {} not found", id));
+
+    let entry = get_workout_entry(pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(not_found)?;
+    find_own_workout(pool, user_id, entry.workout_id)
+        .await
+        .map_err(|_| not_found())?;
+
+    Ok(entry)
 }
 
+// A conditional write that matched no row lost a race: 412 if it was conditional, else the row is gone
+fn write_missed(expected_version: Option<i64>, what: &str, id: i64) -> (StatusCode, String) {
+    match expected_version {
+        Some(_) => precondition_failed(),
+        None => (StatusCode::NOT_FOUND, format!("{} {} not found", what, id)),
+    }
+}
 
-// GET: list all users
+// GET: list users, one page at a time
+#[utoipa::path(
+    get,
+    path = "/api/v1/users",
+    tag = "Users",
+    params(UserListQuery),
+    responses(
+        (status = 200, description = "One page of users; the next page is in the Link and X-Next-Cursor headers", body = [User]),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
 pub async fn list_users(
-    auth: AuthUser,
+    _auth: AuthUser,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<UserListQuery>,
     State(pool): State<SqlitePool>
-) -> Json<Vec<User>> {
-    let users = get_all_users(&pool).await.unwrap_or_default();
-    Json(users)
+) -> Result<Response, (StatusCode, String)> {
+    let page = list_users_page(&pool, &query)
+        .await
+        .map_err(page_error)?;
+
+    Ok(paged_response(&uri, page))
 }
 
 // ---------------- EXERCISES ----------------
 // Add new user
+#[utoipa::path(
+    post,
+    path = "/api/v1/users",
+    tag = "Users",
+    request_body = NewUser,
+    responses(
+        (status = 200, description = "The new user", body = User),
+    ),
+)]
 pub async fn create_user(
-    auth: AuthUser,
+    _auth: AuthUser,
     State(pool): State<SqlitePool>,
     Json(new_user): Json<NewUser>,
 ) -> Json<User> {
@@ -59,7 +177,7 @@ pub async fn create_user(
 }
 
 pub async fn update_user(
-    auth: AuthUser,
+    _auth: AuthUser,
     Path(id): Path<String>,
     State(pool): State<SqlitePool>,
     Json(updated_user): Json<NewUser>,
@@ -73,8 +191,55 @@ pub async fn update_user(
     Json(user)
 }
 
-pub async fn delete_user(
+// PATCH /api/users/:id (only your own account)
+#[utoipa::path(
+    patch,
+    path = "/api/v1/users/{id}",
+    tag = "Users",
+    params(("id" = i64, Path, description = "User id; only your own")),
+    request_body = UserPatch,
+    responses(
+        (status = 200, description = "The updated user", body = User),
+        (status = 400, description = "Invalid input"),
+        (status = 403, description = "Not your account"),
+        (status = 409, description = "Username or email taken"),
+    ),
+)]
+pub async fn patch_user(
     auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(patch): Json<UserPatch>,
+) -> Result<Json<User>, (StatusCode, String)> {
+    get_user(&pool, id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, format!("User {} not found", id)))?;
+
+    if id != auth.user_id {
+        return Err((StatusCode::FORBIDDEN, "You can only update your own account".into()));
+    }
+
+    let blank = |value: &Option<String>| value.as_ref().is_some_and(|v| v.trim().is_empty());
+    if blank(&patch.username) || blank(&patch.email) || blank(&patch.password) {
+        return Err((StatusCode::BAD_REQUEST, "Fields must not be empty".into()));
+    }
+
+    tracing::info!("Updating user {}", id);
+
+    let user = patch_user_db(&pool, id, &patch)
+        .await
+        .map_err(|e| match e {
+            PatchUserError::Taken => (StatusCode::CONFLICT, "Username or email taken".to_string()),
+            PatchUserError::Database(e) => internal_error(e),
+        })?
+        .ok_or((StatusCode::NOT_FOUND, format!("User {} not found", id)))?;
+
+    Ok(Json(user))
+}
+
+pub async fn delete_user(
+    _auth: AuthUser,
     State(pool): State<SqlitePool>,
     Path(id): Path<i64>,
 ) -> Json<String> {
@@ -84,15 +249,28 @@ pub async fn delete_user(
 
 // ---------------- WORKOUTS ----------------
 
+// Only the caller's workouts, newest first unless `sort` says otherwise
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts",
+    tag = "Workouts",
+    params(WorkoutListQuery),
+    responses(
+        (status = 200, description = "One page of your workouts; the next page is in the Link and X-Next-Cursor headers", body = [Workout]),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
 pub async fn list_workouts(
     auth: AuthUser,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<WorkoutListQuery>,
     State(pool): State<SqlitePool>
-) -> Json<Vec<Workout>> {
-    let workouts = get_all_workouts(&pool)
+) -> Result<Response, (StatusCode, String)> {
+    let page = list_workouts_page(&pool, auth.user_id, &query)
         .await
-        .unwrap_or_default();
+        .map_err(page_error)?;
 
-    Json(workouts)
+    Ok(paged_response(&uri, page))
 }
 
 pub async fn get_workout(
@@ -105,6 +283,15 @@ pub async fn get_workout(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/workouts",
+    tag = "Workouts",
+    request_body = NewWorkout,
+    responses(
+        (status = 200, description = "The new workout", body = Workout),
+    ),
+)]
 pub async fn create_workout(
     auth: AuthUser,
     State(pool): State<SqlitePool>,
@@ -121,33 +308,68 @@ pub async fn create_workout(
     let workout = create_workout_db(&pool, &new_workout)
         .await
         .expect("Failed to create workout");
+    notify(&pool, auth.user_id, WebhookEvent::WorkoutCreated(workout.clone())).await;
 
     Json(workout)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workouts/{id}",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Workout not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
 pub async fn delete_workout(
     auth: AuthUser,
     Path(id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
-) -> Json<String> {
-    delete_workout_db(&pool, id)
+) -> Result<Json<String>, (StatusCode, String)> {
+    let workout = find_own_workout(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, workout.version)?;
+
+    let deleted = delete_workout_db(&pool, id, expected)
         .await
-        .expect("Failed to delete workout");
+        .map_err(internal_error)?;
+
+    if !deleted {
+        return Err(write_missed(expected, "Workout", id));
+    }
 
-    Json(format!("Workout {} deleted", id))
+    Ok(Json(format!("Workout {} deleted", id)))
 }
 
 
 // ---------------- EXERCISES ----------------
 
-pub async fn list_exercises(State(pool): State<SqlitePool>) -> Json<Vec<Exercise>> {
-    match get_all_exercises(&pool).await {
-        Ok(exercises) => Json(exercises),
-        Err(e) => {
-            tracing::error!("Error fetching exercises: {:?}", e);
-            Json(vec![])
-        }
-    }
+#[utoipa::path(
+    get,
+    path = "/api/v1/exercises",
+    tag = "Exercises",
+    params(ExerciseListQuery),
+    responses(
+        (status = 200, description = "One page of exercises; the next page is in the Link and X-Next-Cursor headers", body = [Exercise]),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn list_exercises(
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<ExerciseListQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Response, (StatusCode, String)> {
+    let page = list_exercises_page(&pool, &query)
+        .await
+        .map_err(page_error)?;
+
+    Ok(paged_response(&uri, page))
 }
 
 pub async fn get_exercise(State(pool): State<SqlitePool>, Path(id): Path<i64>) -> Json<Option<Exercise>> {
@@ -156,6 +378,15 @@ pub async fn get_exercise(State(pool): State<SqlitePool>, Path(id): Path<i64>) -
 }
 
 // POST: add single or multiple exercises
+#[utoipa::path(
+    post,
+    path = "/api/v1/exercises",
+    tag = "Exercises",
+    request_body = [NewExercise],
+    responses(
+        (status = 200, description = "The new exercises", body = [Exercise]),
+    ),
+)]
 pub async fn create_exercise(
     State(pool): State<SqlitePool>,
     Json(exercises): Json<Vec<NewExercise>>, // <-- accepts an array now
@@ -178,15 +409,137 @@ pub async fn delete_exercise(State(pool): State<SqlitePool>, Path(id): Path<i64>
     }
 }
 
-pub async fn update_workout_entry(
+// GET /api/workout_entries/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/workout_entries/{id}",
+    tag = "Workout Entries",
+    params(
+        ("id" = i64, Path, description = "Entry id"),
+        ("If-None-Match" = Option<String>, Header, description = "Answer 304 if the ETag still matches"),
+    ),
+    responses(
+        (status = 200, description = "The entry, with its ETag", body = WorkoutEntry),
+        (status = 304, description = "Not modified"),
+        (status = 404, description = "Workout entry not found"),
+    ),
+)]
+pub async fn get_workout_entry_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
+) -> Result<Response, (StatusCode, String)> {
+    let entry = find_own_entry(&pool, auth.user_id, id).await?;
+    let tag = etag(entry.id, entry.version);
+
+    if not_modified(&headers, &tag) {
+        return Ok((StatusCode::NOT_MODIFIED, etag_header(&tag)).into_response());
+    }
+
+    Ok((etag_header(&tag), Json(entry)).into_response())
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/workout_entries/{id}",
+    tag = "Workout Entries",
+    params(
+        ("id" = i64, Path, description = "Entry id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    request_body = NewWorkoutEntry,
+    responses(
+        (status = 200, description = "The updated entry", body = WorkoutEntry),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout entry not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
+pub async fn update_workout_entry(
+    auth: AuthUser,
     Path(id): Path<i64>,
+    headers: HeaderMap,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
     Json(updated_entry): Json<NewWorkoutEntry>,
-) -> Json<WorkoutEntry> {
-    let entry = update_workout_entry_db(&pool, id, &updated_entry)
+) -> Result<(HeaderMap, Json<WorkoutEntry>), (StatusCode, String)> {
+    let current = find_own_entry(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, current.version)?;
+    find_own_workout(&pool, auth.user_id, updated_entry.workout_id).await?;
+
+    let entry = update_workout_entry_db(&pool, id, &updated_entry, expected)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| write_missed(expected, "Workout entry", id))?;
+    publish_entry_update(&live, current.workout_id, &entry);
+
+    Ok((etag_header(&etag(entry.id, entry.version)), Json(entry)))
+}
+
+// PATCH /api/workout_entries/:id
+#[utoipa::path(
+    patch,
+    path = "/api/v1/workout_entries/{id}",
+    tag = "Workout Entries",
+    params(
+        ("id" = i64, Path, description = "Entry id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    request_body = WorkoutEntryPatch,
+    responses(
+        (status = 200, description = "The updated entry", body = WorkoutEntry),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout entry not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
+pub async fn patch_workout_entry(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+    Json(patch): Json<WorkoutEntryPatch>,
+) -> Result<(HeaderMap, Json<WorkoutEntry>), (StatusCode, String)> {
+    let current = find_own_entry(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, current.version)?;
+
+    if let Some(workout_id) = patch.workout_id {
+        find_own_workout(&pool, auth.user_id, workout_id).await?;
+    }
+
+    if let Some(exercise_id) = patch.exercise_id {
+        let exists = get_exercise_by_id(&pool, exercise_id)
+            .await
+            .map_err(internal_error)?
+            .is_some();
+
+        if !exists {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Exercise {} does not exist", exercise_id),
+            ));
+        }
+    }
+
+    if patch.sets.is_some_and(|s| s <= 0) || patch.reps.is_some_and(|r| r <= 0) {
+        return Err((StatusCode::BAD_REQUEST, "sets and reps must be positive".into()));
+    }
+    if patch.weight.flatten().is_some_and(|w| w < 0.0) {
+        return Err((StatusCode::BAD_REQUEST, "weight must not be negative".into()));
+    }
+    if patch.rpe.flatten().is_some_and(|r| !(1.0..=10.0).contains(&r)) {
+        return Err((StatusCode::BAD_REQUEST, "rpe must be between 1 and 10".into()));
+    }
+
+    let entry = patch_workout_entry_db(&pool, id, &patch, expected)
         .await
-        .expect("Failed to update workout entry");
-    Json(entry)
+        .map_err(internal_error)?
+        .ok_or_else(|| write_missed(expected, "Workout entry", id))?;
+    publish_entry_update(&live, current.workout_id, &entry);
+
+    Ok((etag_header(&etag(entry.id, entry.version)), Json(entry)))
 }
 
 // ---------------- WORKOUT ENTRIES ----------------
@@ -201,52 +554,211 @@ pub async fn list_workout_entries(State(pool): State<SqlitePool>) -> Json<Vec<Wo
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/workout_entries",
+    tag = "Workout Entries",
+    request_body = NewWorkoutEntry,
+    responses(
+        (status = 200, description = "status \"success\" with the new entry, or status \"error\" with a message", body = Object),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
 pub async fn create_workout_entry(
+    auth: AuthUser,
     State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
     Json(new_entry): Json<NewWorkoutEntry>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, new_entry.workout_id).await?;
 
     match create_workout_entry_db(&pool, &new_entry).await {
-        Ok(entry) => Json(serde_json::json!({
-            "status": "success",
-            "entry": entry
-        })),
-        Err(e) => Json(serde_json::json!({
+        Ok(entry) => {
+            // Logging the next set ends the rest after the previous one
+            stop_timer(&pool, &live, entry.workout_id).await?;
+            entry_created(&pool, &live, auth.user_id, &entry).await;
+            Ok(Json(serde_json::json!({
+                "status": "success",
+                "entry": entry
+            })))
+        }
+        Err(e) => Ok(Json(serde_json::json!({
             "status": "error",
             "message": e.to_string()
-        })),
+        }))),
+    }
+}
+
+// Tells the devices on the entry's workout; a move also removes it from the old one
+fn publish_entry_update(live: &LiveHub, previous_workout_id: i64, entry: &WorkoutEntry) {
+    if previous_workout_id != entry.workout_id {
+        live.publish(previous_workout_id, LiveEvent::EntryDeleted { id: entry.id });
     }
+    live.publish(entry.workout_id, LiveEvent::EntryUpdated { entry: entry.clone() });
 }
 
 
+#[utoipa::path(
+    put,
+    path = "/api/v1/workouts/{id}",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    request_body = NewWorkout,
+    responses(
+        (status = 200, description = "The updated workout", body = Workout),
+        (status = 404, description = "Workout not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
 pub async fn update_workout(
     auth: AuthUser,
     Path(id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
-    Json(updated): Json<NewWorkout>
-) -> Json<Workout> {
+    Json(mut updated): Json<NewWorkout>
+) -> Result<(HeaderMap, Json<Workout>), (StatusCode, String)> {
+    let current = find_own_workout(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, current.version)?;
+
+    updated.user_id = auth.user_id;
 
-    let workout = update_workout_db(&pool, id, &updated)
+    let workout = update_workout_db(&pool, id, &updated, expected)
         .await
-        .expect("Failed to update workout");
+        .map_err(internal_error)?
+        .ok_or_else(|| write_missed(expected, "Workout", id))?;
 
-    Json(workout)
+    Ok((etag_header(&etag(workout.id, workout.version)), Json(workout)))
 }
 
 
-pub async fn delete_workout_entry(
+// PATCH /api/workouts/:id
+#[utoipa::path(
+    patch,
+    path = "/api/v1/workouts/{id}",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    request_body = WorkoutPatch,
+    responses(
+        (status = 200, description = "The updated workout", body = Workout),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
+pub async fn patch_workout(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
+    Json(patch): Json<WorkoutPatch>,
+) -> Result<(HeaderMap, Json<Workout>), (StatusCode, String)> {
+    let current = find_own_workout(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, current.version)?;
+
+    if let Some(date) = &patch.date
+        && parse_date(date).is_none()
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid date '{}', expected YYYY-MM-DD", date),
+        ));
+    }
+
+    let workout = patch_workout_db(&pool, id, &patch, expected)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| write_missed(expected, "Workout", id))?;
+
+    Ok((etag_header(&etag(workout.id, workout.version)), Json(workout)))
+}
+
+// POST /api/workouts/:id/finish (also ends a running rest timer)
+#[utoipa::path(
+    post,
+    path = "/api/v1/workouts/{id}/finish",
+    tag = "Workouts",
+    params(("id" = i64, Path, description = "Workout id")),
+    responses(
+        (status = 200, description = "The finished workout", body = Workout),
+        (status = 404, description = "Workout not found"),
+        (status = 409, description = "Already finished"),
+    ),
+)]
+pub async fn finish_workout(
+    auth: AuthUser,
     Path(id): Path<i64>,
-) -> Json<String> {
-    match delete_workout_entry_db(&pool, id).await {
-        Ok(_) => Json(format!("Workout entry with id {} deleted", id)),
-        Err(e) => Json(format!("Failed to delete workout entry: {:?}", e)),
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+) -> Result<(HeaderMap, Json<Workout>), (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, id).await?;
+    stop_timer(&pool, &live, id).await?;
+
+    let finished_at = chrono::Utc::now().to_rfc3339();
+    let workout = finish_workout_db(&pool, id, &finished_at)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::CONFLICT, format!("Workout {} is already finished", id)))?;
+
+    tracing::info!("Finished workout {} for user {}", id, auth.user_id);
+    notify(&pool, auth.user_id, WebhookEvent::WorkoutFinished(workout.clone())).await;
+
+    Ok((etag_header(&etag(workout.id, workout.version)), Json(workout)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/workout_entries/{id}",
+    tag = "Workout Entries",
+    params(
+        ("id" = i64, Path, description = "Entry id"),
+        ("If-Match" = Option<String>, Header, description = "ETag the write is conditional on"),
+    ),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Workout entry not found"),
+        (status = 412, description = "If-Match no longer matches"),
+    ),
+)]
+pub async fn delete_workout_entry(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    let entry = find_own_entry(&pool, auth.user_id, id).await?;
+    let expected = if_match(&headers, id, entry.version)?;
+
+    let deleted = delete_workout_entry_db(&pool, id, expected)
+        .await
+        .map_err(internal_error)?;
+
+    if !deleted {
+        return Err(write_missed(expected, "Workout entry", id));
     }
+    live.publish(entry.workout_id, LiveEvent::EntryDeleted { id });
+
+    Ok(Json(format!("Workout entry with id {} deleted", id)))
 }
 
 // ---------------- HEALTH CHECK ----------------
 
-async fn health_check() -> Json<HealthResponse> {
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "Health",
+    responses(
+        (status = 200, description = "The API is up", body = HealthResponse),
+    ),
+    security(()),
+)]
+pub async fn health_check() -> Json<HealthResponse> {
     Json(HealthResponse {
         status: "ok".to_string(),
         message: "GymBuddy API is healthy 💪!".to_string(),
@@ -254,8 +766,17 @@ async fn health_check() -> Json<HealthResponse> {
 }
 
 // GET user by id
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}",
+    tag = "Users",
+    params(("id" = i64, Path, description = "User id")),
+    responses(
+        (status = 200, description = "The user, or null", body = Option<User>),
+    ),
+)]
 pub async fn get_user_by_id(
-    auth: AuthUser,
+    _auth: AuthUser,
     Path(id): Path<i64>,
     State(pool): State<SqlitePool>,
 ) -> Json<Option<User>> {
@@ -263,71 +784,163 @@ pub async fn get_user_by_id(
     Json(user)
 }
 // GET workout by id
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts/{id}",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("If-None-Match" = Option<String>, Header, description = "Answer 304 if the ETag still matches"),
+    ),
+    responses(
+        (status = 200, description = "The workout, with its ETag", body = Workout),
+        (status = 304, description = "Not modified"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
 pub async fn get_workout_by_id_route(
     auth: AuthUser,
     Path(id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
-) -> Json<Option<Workout>> {
-    let workout = get_workout_by_id(&pool, id)
-        .await
-        .unwrap();
+) -> Result<Response, (StatusCode, String)> {
+    let workout = find_own_workout(&pool, auth.user_id, id).await?;
+    let tag = etag(workout.id, workout.version);
 
-    Json(workout)
+    if not_modified(&headers, &tag) {
+        return Ok((StatusCode::NOT_MODIFIED, etag_header(&tag)).into_response());
+    }
+
+    Ok((etag_header(&tag), Json(workout)).into_response())
 }
 
-// To get more detailed info of workout (name, muscle group, etc)
+// To get more detailed info of workout (name, muscle group, etc), for the caller's workouts only
+#[utoipa::path(
+    get,
+    path = "/api/v1/workout_entries",
+    tag = "Workout Entries",
+    params(EntryListQuery),
+    responses(
+        (status = 200, description = "One page of your entries; the next page is in the Link and X-Next-Cursor headers", body = [WorkoutEntryDetailed]),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
 pub async fn list_workout_entries_detailed(
+    auth: AuthUser,
+    OriginalUri(uri): OriginalUri,
+    Query(query): Query<EntryListQuery>,
     State(pool): State<SqlitePool>,
-) -> Json<Vec<WorkoutEntryDetailed>> {
-    match get_detailed_workout_entries(&pool).await {
-        Ok(entries) => Json(entries),
-        Err(e) => {
-            tracing::error!("Error fetching exercises: {:?}", e);
-            Json(vec![])
-        }
-    }
+) -> Result<Response, (StatusCode, String)> {
+    let page = list_workout_entries_page(&pool, auth.user_id, &query)
+        .await
+        .map_err(page_error)?;
+
+    Ok(paged_response(&uri, page))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts/{id}/entries",
+    tag = "Workouts",
+    params(
+        ("id" = i64, Path, description = "Workout id"),
+        ("If-None-Match" = Option<String>, Header, description = "Answer 304 if the ETag still matches"),
+    ),
+    responses(
+        (status = 200, description = "Entries in workout order", body = [WorkoutEntryDetailed]),
+        (status = 304, description = "Not modified"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
 pub async fn get_entries_for_workout(
     auth: AuthUser,
     Path(workout_id): Path<i64>,
+    headers: HeaderMap,
     State(pool): State<SqlitePool>,
-) -> Json<Vec<WorkoutEntryDetailed>> {
+) -> Result<Response, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    // The list changes whenever an entry is added, removed or bumped
+    let versions = get_entry_versions(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+    let tag = collection_etag(&format!("entries-{}", workout_id), &versions);
+
+    if not_modified(&headers, &tag) {
+        return Ok((StatusCode::NOT_MODIFIED, etag_header(&tag)).into_response());
+    }
+
     let entries = get_workout_entries_by_workout_id(&pool, workout_id)
         .await
-        .unwrap_or_default();
+        .map_err(internal_error)?;
 
-    Json(entries)
+    Ok((etag_header(&tag), Json(entries)).into_response())
 }
 
 
 // Summary report route
+#[utoipa::path(
+    get,
+    path = "/api/v1/workouts/{id}/summary",
+    tag = "Workouts",
+    params(("id" = i64, Path, description = "Workout id")),
+    responses(
+        (status = 200, description = "Volume, groups, rounds and density", body = WorkoutSummary),
+        (status = 404, description = "No such workout of yours"),
+    ),
+)]
 pub async fn get_workout_summary_route(
     auth: AuthUser,
     Path(workout_id): Path<i64>,
     State(pool): State<SqlitePool>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<WorkoutSummary>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
     let summary = get_workout_summary(&pool, workout_id)
         .await
-        .expect("Failed to fetch summary");
+        .map_err(internal_error)?;
 
-    Json(summary)
+    Ok(Json(summary))
 }
 
 // Overall progress route
+#[utoipa::path(
+    get,
+    path = "/api/v1/users/{id}/progress",
+    tag = "Users",
+    params(("id" = i64, Path, description = "User id; only your own")),
+    responses(
+        (status = 200, description = "Volume, muscle groups and top exercises per workout, oldest first", body = [WorkoutProgress]),
+        (status = 403, description = "Not your account"),
+    ),
+)]
 pub async fn get_user_progress_route(
     auth: AuthUser,
     Path(user_id): Path<i64>,
     State(pool): State<SqlitePool>,
-) -> Json<serde_json::Value> {
+) -> Result<Json<Vec<WorkoutProgress>>, (StatusCode, String)> {
+    if user_id != auth.user_id {
+        return Err((StatusCode::FORBIDDEN, "You can only see your own progress".into()));
+    }
+
     let progress = get_workout_progress(&pool, user_id)
         .await
-        .expect("Failed to fetch user progress");
+        .map_err(internal_error)?;
 
-    Json(progress)
+    Ok(Json(progress))
 }
 
 // POST /api/register
+#[utoipa::path(
+    post,
+    path = "/api/v1/register",
+    tag = "Auth",
+    request_body = NewUser,
+    responses(
+        (status = 200, description = "status \"success\" or \"error\"", body = Object),
+    ),
+    security(()),
+)]
 pub async fn register_user(
     State(pool): State<SqlitePool>,
     Json(new_user): Json<NewUser>,
@@ -365,6 +978,16 @@ pub async fn register_user(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/login",
+    tag = "Auth",
+    request_body = LoginUser,
+    responses(
+        (status = 200, description = "A token on success, an error message otherwise", body = Object),
+    ),
+    security(()),
+)]
 pub async fn login_user(
     State(pool): State<SqlitePool>,
     Json(login): Json<LoginUser>,
@@ -380,12 +1003,12 @@ pub async fn login_user(
     .await
     .unwrap();
 
-    if let Some((id, password_hash)) = user {
-        if verify_password(&password_hash, &login.password) {
-            tracing::info!("Login successful for {}", login.email);
-            let token = generate_jwt(id);
-            return Json(serde_json::json!({ "token": token }));
-        }
+    if let Some((id, password_hash)) = user
+        && verify_password(&password_hash, &login.password)
+    {
+        tracing::info!("Login successful for {}", login.email);
+        let token = generate_jwt(id);
+        return Json(serde_json::json!({ "token": token }));
     }
 
     tracing::warn!("Invalid login attempt for {}", login.email);
@@ -394,6 +1017,14 @@ pub async fn login_user(
 
 
 // Analytics
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/weekly",
+    tag = "Analytics",
+    responses(
+        (status = 200, description = "This week's volume and hard sets per muscle", body = WeeklyAnalytics),
+    ),
+)]
 pub async fn weekly_analytics(
     auth: AuthUser,
     State(pool): State<SqlitePool>,
@@ -418,69 +1049,175 @@ pub async fn weekly_analytics(
     .await
     .unwrap();
 
-    let muscle: (Option<String>,) = sqlx::query_as(
-        r#"
-        SELECT e.muscle_group
-        FROM workout_entries we
-        JOIN exercises e ON we.exercise_id = e.id
-        JOIN workouts w ON we.workout_id = w.id
-        WHERE w.user_id = ?
-        GROUP BY e.muscle_group
-        ORDER BY SUM(we.sets * we.reps * COALESCE(we.weight,0)) DESC
-        LIMIT 1
-        "#
-    )
-    .bind(auth.user_id)
-    .fetch_one(&pool)
-    .await
-    .unwrap();
+    let muscles = get_muscle_volume_report(&pool, auth.user_id, 1)
+        .await
+        .unwrap()
+        .muscles;
 
     Json(WeeklyAnalytics {
         total_volume: volume.0.unwrap_or(0.0),
-        top_muscle: muscle.0.unwrap_or("N/A".into()),
+        muscles,
     })
 }
 
 
 // ---------------- ROUTER SETUP ----------------
 
-pub fn create_api_router() -> Router<SqlitePool> {
+// Every endpoint of one API version, with paths relative to its prefix. Routes whose
+// response changed shape between versions go through version.adapt.
+fn api_routes(version: ApiVersion) -> Router<SqlitePool> {
     let protected = Router::new()
-        .route("/api/users", get(list_users).post(create_user))
-        .route("/api/users/:id", get(get_user_by_id))
-        .route("/api/users/:id/progress", get(get_user_progress_route))
+        .route("/users", get(list_users).post(create_user))
+        .route("/users/:id", get(get_user_by_id).patch(patch_user))
+        .route("/users/:id/progress", get(get_user_progress_route))
+        .route("/me", delete(delete_account_route))
+        .route("/me/export", get(export_account_route))
+
+        .route("/exercises", get(list_exercises).post(create_exercise))
+        .route("/exercises/:id/next", get(get_next_session_route))
+        .route(
+            "/exercises/:id/progression",
+            get(get_progression_rule_route).put(set_progression_rule_route),
+        )
 
-        .route("/api/exercises", get(list_exercises).post(create_exercise))
+        .route("/templates", get(list_templates).post(create_template))
+        .route("/templates/:id", get(get_template))
+        .route("/templates/:id/start", post(start_template_route))
 
         .route(
-            "/api/workouts",
+            "/workouts",
             get(list_workouts).post(create_workout),
         )
         .route(
-            "/api/workouts/:id",
+            "/workouts/:id",
             get(get_workout_by_id_route)
                 .put(update_workout)
+                .patch(patch_workout)
                 .delete(delete_workout),
         )
-        .route("/api/workouts/:id/entries", get(get_entries_for_workout))
-        .route("/api/workouts/:id/summary", get(get_workout_summary_route))
+        .route(
+            "/workouts/:id/entries",
+            version.adapt::<Vec<WorkoutEntryDetailed>>(get(get_entries_for_workout)),
+        )
+        .route("/workouts/:id/finish", post(finish_workout))
+        .route("/workouts/:id/live", get(live_session))
+        .route("/workouts/:id/summary", get(get_workout_summary_route))
+        .route(
+            "/workouts/:id/order",
+            version.adapt::<Vec<WorkoutEntryDetailed>>(put(reorder_entries_route)),
+        )
+        .route(
+            "/workouts/:id/groups",
+            get(list_entry_groups).post(create_entry_group_route),
+        )
+        .route(
+            "/workouts/:id/groups/:group_id",
+            delete(delete_entry_group_route),
+        )
 
         .route(
-            "/api/workout_entries",
-            get(list_workout_entries_detailed).post(create_workout_entry),
+            "/workout_entries",
+            version
+                .adapt::<Vec<WorkoutEntryDetailed>>(get(list_workout_entries_detailed))
+                .merge(version.adapt::<CreatedEntry>(post(create_workout_entry))),
         )
         .route(
-            "/api/workout_entries/:id",
-            put(update_workout_entry).delete(delete_workout_entry),
+            "/workout_entries/:id",
+            version.adapt::<WorkoutEntry>(
+                get(get_workout_entry_route)
+                    .put(update_workout_entry)
+                    .patch(patch_workout_entry)
+                    .delete(delete_workout_entry),
+            ),
         )
-        .route("/api/analytics/weekly", get(weekly_analytics));
+        .route("/analytics/weekly", get(weekly_analytics))
+        .route("/analytics/fatigue", get(fatigue_analytics))
+        .route("/analytics/muscle_volume", get(muscle_volume_analytics))
+        .route("/analytics/rollups", get(rollup_analytics))
+        .route("/volume_targets", get(list_volume_targets))
+        .route("/volume_targets/:muscle_group", put(set_volume_target_route))
+
+        .route("/reports/progress", get(progress_report))
+        .route("/export/workouts.csv", get(export_workouts_csv))
+        .route(
+            "/import",
+            post(import_route).layer(DefaultBodyLimit::max(MAX_IMPORT_BYTES)),
+        )
+
+        .route("/recovery", get(recovery_route))
+        .route("/recovery/suggestions", get(training_suggestions_route))
+        .route("/recovery/half_lives", get(list_recovery_settings))
+        .route("/recovery/half_lives/:muscle_group", put(set_recovery_setting_route))
+
+        .route(
+            "/sessions/:workout_id/rest",
+            get(get_rest_timer_route)
+                .post(start_rest_timer_route)
+                .delete(stop_rest_timer_route),
+        )
+        .route("/sessions/:workout_id/events", get(session_events))
+        .route("/rest_defaults", get(list_rest_defaults))
+        .route("/rest_defaults/:exercise_id", put(set_rest_default_route))
+
+        .route("/sync/pull", get(sync_pull))
+        .route("/sync/push", post(sync_push))
+
+        .route("/webhooks", get(list_webhooks).post(create_webhook_route))
+        .route("/webhooks/:id", delete(delete_webhook_route))
+        .route("/webhooks/:id/deliveries", get(list_deliveries))
+
+        .route("/schedule", get(list_schedule).post(create_schedule))
+        .route("/schedule/:id", delete(delete_schedule))
+        .route("/calendar/feed", post(create_calendar_feed).delete(revoke_calendar_feed))
+
+        // Admin only (AdminUser extractor)
+        .route("/admin/backup", get(backup_route))
+        .route(
+            "/admin/restore",
+            post(restore_route).layer(DefaultBodyLimit::max(MAX_BACKUP_BYTES)),
+        );
 
     Router::new()
         // Public
-        .route("/api/register", post(register_user))
-        .route("/api/login", post(login_user))
-        .route("/health", get(health_check))
+        .route("/register", post(register_user))
+        .route("/login", post(login_user))
+        .route("/calendar/:token/workouts.ics", get(calendar_feed))
 
         // Protected (AuthUser extractor works here)
         .merge(protected)
 }
+
+pub fn create_api_router() -> Router<SqlitePool> {
+    api_router(LiveHub::default())
+}
+
+// The API around the hub that live sessions and rest timers publish to
+fn api_router(live: LiveHub) -> Router<SqlitePool> {
+    let versioned = |version: ApiVersion| match version.is_deprecated() {
+        true => api_routes(version).layer(middleware::from_fn(deprecated)),
+        false => api_routes(version),
+    };
+
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/api/openapi.json", get(openapi_json))
+        .route("/api/docs", get(api_docs))
+        .route("/api/graphql", post(graphql_route))
+        .route("/api/graphql/schema", get(graphql_schema_route))
+        .nest(ApiVersion::V1.prefix(), versioned(ApiVersion::V1))
+        .nest(ApiVersion::V2.prefix(), versioned(ApiVersion::V2))
+        // Unversioned paths are what the shipped apps call: the v1 API
+        .nest("/api", versioned(ApiVersion::V1))
+        .layer(Extension(live))
+        .layer(Extension(build_schema()))
+}
+
+// The API with the middleware that needs the pool, as served by main
+pub fn create_app(pool: SqlitePool) -> Router {
+    let live = LiveHub::default();
+    resume_rest_timers(pool.clone(), live.clone());
+
+    api_router(live)
+        .layer(middleware::from_fn_with_state(pool.clone(), idempotency))
+        .with_state(pool)
+}
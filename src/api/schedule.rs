@@ -0,0 +1,232 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::api::templates::find_own_template;
+use crate::api::versioning::ApiVersion;
+use crate::calendar::{render_calendar, workout_description, CalendarEvent};
+use crate::db::analytics::{parse_date, today};
+use crate::db::summary::get_workout_summaries;
+use crate::models::analytics::WorkoutSummary;
+use crate::db::schedule::{
+    create_scheduled_session, delete_scheduled_session, get_feed_user, get_scheduled_sessions,
+    get_workouts_since, revoke_feed_token, rotate_feed_token,
+};
+use crate::middleware::auth::AuthUser;
+use crate::models::schedule::{CalendarFeed, NewScheduledSession, ScheduledSession};
+
+// How far back the feed lists completed workouts
+const FEED_HISTORY_DAYS: i64 = 365;
+
+// GET /api/schedule (upcoming sessions)
+#[utoipa::path(
+    get,
+    path = "/api/v1/schedule",
+    tag = "Schedule",
+    responses(
+        (status = 200, description = "Upcoming sessions", body = [ScheduledSession]),
+    ),
+)]
+pub async fn list_schedule(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<ScheduledSession>>, (StatusCode, String)> {
+    let sessions = get_scheduled_sessions(&pool, auth.user_id, &today().to_string())
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(sessions))
+}
+
+// POST /api/schedule
+#[utoipa::path(
+    post,
+    path = "/api/v1/schedule",
+    tag = "Schedule",
+    request_body = NewScheduledSession,
+    responses(
+        (status = 200, description = "The scheduled session", body = ScheduledSession),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Template not found"),
+    ),
+)]
+pub async fn create_schedule(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Json(session): Json<NewScheduledSession>,
+) -> Result<Json<ScheduledSession>, (StatusCode, String)> {
+    if parse_date(&session.date).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid date '{}', expected YYYY-MM-DD", session.date),
+        ));
+    }
+
+    if let Some(template_id) = session.template_id {
+        find_own_template(&pool, auth.user_id, template_id).await?;
+    }
+
+    tracing::info!("Scheduling session on {} for user {}", session.date, auth.user_id);
+
+    let session = create_scheduled_session(&pool, auth.user_id, &session)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(session))
+}
+
+// DELETE /api/schedule/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/schedule/{id}",
+    tag = "Schedule",
+    params(("id" = i64, Path, description = "Scheduled session id")),
+    responses(
+        (status = 200, description = "Removed", body = String),
+        (status = 404, description = "Scheduled session not found"),
+    ),
+)]
+pub async fn delete_schedule(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    let deleted = delete_scheduled_session(&pool, auth.user_id, id)
+        .await
+        .map_err(internal_error)?;
+
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, format!("Scheduled session {} not found", id)));
+    }
+
+    Ok(Json(format!("Scheduled session {} deleted", id)))
+}
+
+// POST /api/calendar/feed (creates the feed, or rotates its token)
+#[utoipa::path(
+    post,
+    path = "/api/v1/calendar/feed",
+    tag = "Schedule",
+    responses(
+        (status = 200, description = "The feed URL with its new token", body = CalendarFeed),
+    ),
+)]
+pub async fn create_calendar_feed(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<CalendarFeed>, (StatusCode, String)> {
+    tracing::info!("Creating calendar feed token for user {}", auth.user_id);
+
+    let token = rotate_feed_token(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(CalendarFeed {
+        path: format!("{}/calendar/{}/workouts.ics", ApiVersion::CURRENT.prefix(), token),
+        token,
+    }))
+}
+
+// DELETE /api/calendar/feed
+#[utoipa::path(
+    delete,
+    path = "/api/v1/calendar/feed",
+    tag = "Schedule",
+    responses(
+        (status = 200, description = "Revoked", body = String),
+        (status = 404, description = "Calendar feed not found"),
+    ),
+)]
+pub async fn revoke_calendar_feed(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    let revoked = revoke_feed_token(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    if !revoked {
+        return Err((StatusCode::NOT_FOUND, "No calendar feed to revoke".to_string()));
+    }
+
+    Ok(Json("Calendar feed revoked".to_string()))
+}
+
+// GET /api/calendar/:token/workouts.ics (public; calendar apps can't send a JWT)
+#[utoipa::path(
+    get,
+    path = "/api/v1/calendar/{token}/workouts.ics",
+    tag = "Schedule",
+    params(("token" = String, Path, description = "Secret feed token")),
+    responses(
+        (status = 200, description = "iCalendar feed", body = String, content_type = "text/calendar"),
+        (status = 404, description = "Calendar feed not found"),
+    ),
+    security(()),
+)]
+pub async fn calendar_feed(
+    Path(token): Path<String>,
+    State(pool): State<SqlitePool>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let user_id = get_feed_user(&pool, &token)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, "Calendar feed not found".to_string()))?;
+
+    let today = today();
+    let history_start = (today - Duration::days(FEED_HISTORY_DAYS)).to_string();
+    let mut events = Vec::new();
+
+    let workouts = get_workouts_since(&pool, user_id, &history_start)
+        .await
+        .map_err(internal_error)?;
+    let ids: Vec<i64> = workouts.iter().map(|w| w.id).collect();
+    let summaries = get_workout_summaries(&pool, &ids)
+        .await
+        .map_err(internal_error)?;
+
+    for workout in workouts {
+        let Some(date) = parse_date(&workout.date) else {
+            continue;
+        };
+        let empty = WorkoutSummary::empty(workout.id);
+        let summary = summaries.get(&workout.id).unwrap_or(&empty);
+
+        events.push(CalendarEvent {
+            uid: format!("workout-{}@gymbuddy", workout.id),
+            date,
+            summary: "Workout".to_string(),
+            description: Some(workout_description(workout.notes.as_deref(), summary)),
+        });
+    }
+
+    for session in get_scheduled_sessions(&pool, user_id, &today.to_string())
+        .await
+        .map_err(internal_error)?
+    {
+        let Some(date) = parse_date(&session.date) else {
+            continue;
+        };
+        let title = session.template_name.as_deref().unwrap_or("Workout");
+
+        events.push(CalendarEvent {
+            uid: format!("scheduled-{}@gymbuddy", session.id),
+            date,
+            summary: format!("Planned: {}", title),
+            description: session.notes.clone(),
+        });
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/calendar; charset=utf-8"),
+            (header::CACHE_CONTROL, "private, max-age=900"),
+        ],
+        render_calendar("GymBuddy workouts", &events, Utc::now()),
+    ))
+}
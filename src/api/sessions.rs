@@ -0,0 +1,314 @@
+use std::convert::Infallible;
+use std::time::Duration;
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension, Json,
+};
+use futures::{stream, Stream, StreamExt};
+use sqlx::SqlitePool;
+use crate::api::live::live_user;
+use crate::api::routes::{find_own_entry, find_own_workout, internal_error};
+use crate::db::connection::get_exercise_by_id;
+use crate::db::rest::{
+    get_rest_default, get_rest_defaults, get_rest_timer, get_running_rest_timers, set_rest_default,
+    start_rest_timer_db, stop_rest_timer_db,
+};
+use crate::live::LiveHub;
+use crate::middleware::auth::AuthUser;
+use crate::models::live::{LiveEvent, LiveQuery};
+use crate::models::rest::{
+    FinishedRest, RestDefault, RestTimer, RestTimerRow, SetRestDefault, StartRestTimer,
+    MAX_REST_SECONDS,
+};
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn validate_rest_seconds(seconds: i64) -> Result<(), (StatusCode, String)> {
+    if !(1..=MAX_REST_SECONDS).contains(&seconds) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Rest must be between 1 and {} seconds", MAX_REST_SECONDS),
+        ));
+    }
+    Ok(())
+}
+
+fn publish_finished(live: &LiveHub, workout_id: i64, finished: Option<FinishedRest>) {
+    if let Some(FinishedRest { entry_id, rest_seconds }) = finished {
+        live.publish(workout_id, LiveEvent::RestTimerStopped { entry_id, rest_seconds });
+    }
+}
+
+// Announces expiry unless the timer was stopped or replaced in the meantime
+fn spawn_expiry(pool: SqlitePool, live: LiveHub, timer: RestTimerRow) {
+    let remaining = (timer.started_at + timer.duration_seconds - now()).max(0);
+
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(remaining as u64)).await;
+
+        match get_rest_timer(&pool, timer.workout_id).await {
+            Ok(Some(current)) if current == timer => {
+                live.publish(
+                    timer.workout_id,
+                    LiveEvent::RestTimerExpired {
+                        entry_id: timer.entry_id,
+                        seconds: timer.duration_seconds,
+                    },
+                );
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Database error: {:?}", e),
+        }
+    });
+}
+
+// Expiry only lives in memory, so timers still running when the server stopped are
+// scheduled again at startup
+pub fn resume_rest_timers(pool: SqlitePool, live: LiveHub) {
+    tokio::spawn(async move {
+        match get_running_rest_timers(&pool, now()).await {
+            Ok(timers) => {
+                for timer in timers {
+                    spawn_expiry(pool.clone(), live.clone(), timer);
+                }
+            }
+            Err(e) => tracing::error!("Database error: {:?}", e),
+        }
+    });
+}
+
+// Starts the rest after one of the workout's entries; shared by the REST and WebSocket APIs
+pub(crate) async fn start_timer(
+    pool: &SqlitePool,
+    live: &LiveHub,
+    user_id: i64,
+    workout_id: i64,
+    entry_id: i64,
+    seconds: Option<i64>,
+) -> Result<RestTimer, (StatusCode, String)> {
+    let entry = find_own_entry(pool, user_id, entry_id).await?;
+    if entry.workout_id != workout_id {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Workout entry {} is not part of workout {}", entry_id, workout_id),
+        ));
+    }
+
+    let seconds = match seconds {
+        Some(seconds) => seconds,
+        None => get_rest_default(pool, user_id, entry.exercise_id)
+            .await
+            .map_err(internal_error)?,
+    };
+    validate_rest_seconds(seconds)?;
+
+    let now = now();
+    let (row, finished) = start_rest_timer_db(pool, workout_id, entry_id, seconds, now)
+        .await
+        .map_err(internal_error)?;
+
+    let timer = RestTimer::from_row(&row, now);
+    publish_finished(live, workout_id, finished);
+    live.publish(workout_id, LiveEvent::RestTimerStarted { timer: timer.clone() });
+    spawn_expiry(pool.clone(), live.clone(), row);
+
+    Ok(timer)
+}
+
+// Ends the running rest, if any, and records how long it really was
+pub(crate) async fn stop_timer(
+    pool: &SqlitePool,
+    live: &LiveHub,
+    workout_id: i64,
+) -> Result<Option<FinishedRest>, (StatusCode, String)> {
+    let finished = stop_rest_timer_db(pool, workout_id, now())
+        .await
+        .map_err(internal_error)?;
+
+    publish_finished(live, workout_id, finished.clone());
+
+    Ok(finished)
+}
+
+// GET /api/sessions/:workout_id/rest
+#[utoipa::path(
+    get,
+    path = "/api/v1/sessions/{workout_id}/rest",
+    tag = "Rest Timers",
+    params(("workout_id" = i64, Path, description = "Workout id")),
+    responses(
+        (status = 200, description = "The running timer, or null", body = Option<RestTimer>),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn get_rest_timer_route(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Option<RestTimer>>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    let timer = get_rest_timer(&pool, workout_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(timer.map(|row| RestTimer::from_row(&row, now()))))
+}
+
+// POST /api/sessions/:workout_id/rest
+#[utoipa::path(
+    post,
+    path = "/api/v1/sessions/{workout_id}/rest",
+    tag = "Rest Timers",
+    params(("workout_id" = i64, Path, description = "Workout id")),
+    request_body = StartRestTimer,
+    responses(
+        (status = 200, description = "The new timer", body = RestTimer),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Workout or entry not found"),
+    ),
+)]
+pub async fn start_rest_timer_route(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+    Json(start): Json<StartRestTimer>,
+) -> Result<Json<RestTimer>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    tracing::info!("Starting rest timer on workout {} for user {}", workout_id, auth.user_id);
+
+    let timer = start_timer(&pool, &live, auth.user_id, workout_id, start.entry_id, start.seconds).await?;
+
+    Ok(Json(timer))
+}
+
+// DELETE /api/sessions/:workout_id/rest
+#[utoipa::path(
+    delete,
+    path = "/api/v1/sessions/{workout_id}/rest",
+    tag = "Rest Timers",
+    params(("workout_id" = i64, Path, description = "Workout id")),
+    responses(
+        (status = 200, description = "The rest recorded on the entry", body = FinishedRest),
+        (status = 404, description = "Timer not found"),
+    ),
+)]
+pub async fn stop_rest_timer_route(
+    auth: AuthUser,
+    Path(workout_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+) -> Result<Json<FinishedRest>, (StatusCode, String)> {
+    find_own_workout(&pool, auth.user_id, workout_id).await?;
+
+    stop_timer(&pool, &live, workout_id)
+        .await?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("No rest timer running on workout {}", workout_id)))
+}
+
+// GET /api/sessions/:workout_id/events (Server-Sent Events)
+#[utoipa::path(
+    get,
+    path = "/api/v1/sessions/{workout_id}/events",
+    tag = "Rest Timers",
+    params(("workout_id" = i64, Path, description = "Workout id"), LiveQuery),
+    responses(
+        (status = 200, description = "Server-Sent Events; each event is a LiveEvent named after its type", body = LiveEvent, content_type = "text/event-stream"),
+        (status = 401, description = "Missing or invalid token"),
+        (status = 404, description = "Workout not found"),
+    ),
+)]
+pub async fn session_events(
+    Path(workout_id): Path<i64>,
+    Query(query): Query<LiveQuery>,
+    headers: HeaderMap,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let user_id = live_user(&headers, &query)
+        .ok_or((StatusCode::UNAUTHORIZED, "Missing or invalid token".to_string()))?;
+
+    find_own_workout(&pool, user_id, workout_id).await?;
+
+    // Subscribe first, so a timer started right after the lookup is not missed
+    let subscription = live.subscribe(workout_id);
+    let running = get_rest_timer(&pool, workout_id)
+        .await
+        .map_err(internal_error)?
+        .map(|row| LiveEvent::RestTimerStarted { timer: RestTimer::from_row(&row, now()) });
+
+    tracing::info!("Streaming session events of workout {} for user {}", workout_id, user_id);
+
+    let events = stream::iter(running).chain(stream::unfold(subscription, |mut subscription| async move {
+        subscription.recv().await.map(|event| (event, subscription))
+    }));
+
+    let events = events.map(|event| {
+        Ok(Event::default()
+            .event(event.name())
+            .json_data(&event)
+            .unwrap_or_default())
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// GET /api/rest_defaults
+#[utoipa::path(
+    get,
+    path = "/api/v1/rest_defaults",
+    tag = "Rest Timers",
+    responses(
+        (status = 200, description = "Your rest lengths per exercise", body = [RestDefault]),
+    ),
+)]
+pub async fn list_rest_defaults(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<RestDefault>>, (StatusCode, String)> {
+    let defaults = get_rest_defaults(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(defaults))
+}
+
+// PUT /api/rest_defaults/:exercise_id
+#[utoipa::path(
+    put,
+    path = "/api/v1/rest_defaults/{exercise_id}",
+    tag = "Rest Timers",
+    params(("exercise_id" = i64, Path, description = "Exercise id")),
+    request_body = SetRestDefault,
+    responses(
+        (status = 200, description = "The saved default", body = RestDefault),
+        (status = 400, description = "Invalid input"),
+        (status = 404, description = "Exercise not found"),
+    ),
+)]
+pub async fn set_rest_default_route(
+    auth: AuthUser,
+    Path(exercise_id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(body): Json<SetRestDefault>,
+) -> Result<Json<RestDefault>, (StatusCode, String)> {
+    validate_rest_seconds(body.seconds)?;
+
+    get_exercise_by_id(&pool, exercise_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or((StatusCode::NOT_FOUND, format!("Exercise {} not found", exercise_id)))?;
+
+    let default = set_rest_default(&pool, auth.user_id, exercise_id, body.seconds)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(default))
+}
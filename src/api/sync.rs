@@ -0,0 +1,83 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::api::webhooks::records_created;
+use crate::db::sync::{pull_changes, push_changes, SyncError};
+use crate::live::LiveHub;
+use crate::middleware::auth::AuthUser;
+use crate::models::sync::{
+    PullQuery, SyncPull, SyncPush, SyncPushResult, DEFAULT_PULL_LIMIT, MAX_PULL_LIMIT,
+    MAX_PUSH_CHANGES,
+};
+
+// GET /api/sync/pull?since=&limit=
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync/pull",
+    tag = "Sync",
+    params(PullQuery),
+    responses(
+        (status = 200, description = "Changes after since", body = SyncPull),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn sync_pull(
+    auth: AuthUser,
+    Query(query): Query<PullQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<SyncPull>, (StatusCode, String)> {
+    let since = query.since.unwrap_or(0);
+    if since < 0 {
+        return Err((StatusCode::BAD_REQUEST, "since must not be negative".to_string()));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_PULL_LIMIT).clamp(1, MAX_PULL_LIMIT);
+
+    tracing::info!("Fetching changes since {} for user {}", since, auth.user_id);
+
+    let pull = pull_changes(&pool, auth.user_id, since, limit)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(pull))
+}
+
+// POST /api/sync/push
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync/push",
+    tag = "Sync",
+    request_body = SyncPush,
+    responses(
+        (status = 200, description = "Applied changes and conflicts", body = SyncPushResult),
+        (status = 400, description = "Invalid change; nothing was applied"),
+    ),
+)]
+pub async fn sync_push(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Extension(live): Extension<LiveHub>,
+    Json(push): Json<SyncPush>,
+) -> Result<Json<SyncPushResult>, (StatusCode, String)> {
+    if push.changes.len() > MAX_PUSH_CHANGES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} changes per push", MAX_PUSH_CHANGES),
+        ));
+    }
+
+    tracing::info!("Applying {} offline changes for user {}", push.changes.len(), auth.user_id);
+
+    let (result, created) = push_changes(&pool, auth.user_id, &push.changes)
+        .await
+        .map_err(|e| match e {
+            SyncError::Invalid(message) => (StatusCode::BAD_REQUEST, message),
+            SyncError::Database(e) => internal_error(e),
+        })?;
+    records_created(&pool, &live, auth.user_id, &created).await;
+
+    Ok(Json(result))
+}
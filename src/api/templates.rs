@@ -0,0 +1,155 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::api::webhooks::notify;
+use crate::db::connection::get_exercise_by_id;
+use crate::db::templates::{
+    create_template_db, get_template_detailed, get_templates_for_user, start_template,
+};
+use crate::middleware::auth::AuthUser;
+use crate::models::template::{
+    NewWorkoutTemplate, StartTemplate, StartedWorkout, WorkoutTemplate, WorkoutTemplateDetailed,
+};
+use crate::models::webhook::WebhookEvent;
+
+// Templates are private, so someone else's template is reported as missing
+pub(crate) async fn find_own_template(
+    pool: &SqlitePool,
+    user_id: i64,
+    id: i64,
+) -> Result<WorkoutTemplateDetailed, (StatusCode, String)> {
+    get_template_detailed(pool, id)
+        .await
+        .map_err(internal_error)?
+        .filter(|t| t.template.user_id == user_id)
+        .ok_or((StatusCode::NOT_FOUND, format!("Template {} not found", id)))
+}
+
+// GET /api/templates
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates",
+    tag = "Templates",
+    responses(
+        (status = 200, description = "Your templates", body = [WorkoutTemplate]),
+    ),
+)]
+pub async fn list_templates(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<WorkoutTemplate>>, (StatusCode, String)> {
+    let templates = get_templates_for_user(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(templates))
+}
+
+// POST /api/templates
+#[utoipa::path(
+    post,
+    path = "/api/v1/templates",
+    tag = "Templates",
+    request_body = NewWorkoutTemplate,
+    responses(
+        (status = 200, description = "The new template", body = WorkoutTemplateDetailed),
+        (status = 400, description = "Invalid input"),
+    ),
+)]
+pub async fn create_template(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Json(new_template): Json<NewWorkoutTemplate>,
+) -> Result<Json<WorkoutTemplateDetailed>, (StatusCode, String)> {
+    if new_template.name.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Template name must not be empty".into()));
+    }
+
+    for planned in &new_template.exercises {
+        if planned.sets <= 0 || planned.reps <= 0 {
+            return Err((StatusCode::BAD_REQUEST, "sets and reps must be positive".into()));
+        }
+
+        let exists = get_exercise_by_id(&pool, planned.exercise_id)
+            .await
+            .map_err(internal_error)?
+            .is_some();
+
+        if !exists {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Exercise {} does not exist", planned.exercise_id),
+            ));
+        }
+    }
+
+    tracing::info!(
+        "Creating template '{}' for user {}",
+        new_template.name,
+        auth.user_id
+    );
+
+    let template = create_template_db(&pool, auth.user_id, &new_template)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(template))
+}
+
+// GET /api/templates/:id
+#[utoipa::path(
+    get,
+    path = "/api/v1/templates/{id}",
+    tag = "Templates",
+    params(("id" = i64, Path, description = "Template id")),
+    responses(
+        (status = 200, description = "The template with its exercises", body = WorkoutTemplateDetailed),
+        (status = 404, description = "Template not found"),
+    ),
+)]
+pub async fn get_template(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<WorkoutTemplateDetailed>, (StatusCode, String)> {
+    let template = find_own_template(&pool, auth.user_id, id).await?;
+    Ok(Json(template))
+}
+
+// POST /api/templates/:id/start
+#[utoipa::path(
+    post,
+    path = "/api/v1/templates/{id}/start",
+    tag = "Templates",
+    params(("id" = i64, Path, description = "Template id")),
+    request_body = StartTemplate,
+    responses(
+        (status = 200, description = "The new workout with recommendations", body = StartedWorkout),
+        (status = 404, description = "Template not found"),
+    ),
+)]
+pub async fn start_template_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+    Json(start): Json<StartTemplate>,
+) -> Result<Json<StartedWorkout>, (StatusCode, String)> {
+    let template = find_own_template(&pool, auth.user_id, id).await?;
+
+    tracing::info!(
+        "Starting workout from template {} for user {}",
+        id,
+        auth.user_id
+    );
+
+    let started = start_template(&pool, auth.user_id, &template, &start)
+        .await
+        .map_err(internal_error)?;
+    notify(&pool, auth.user_id, WebhookEvent::WorkoutCreated(started.workout.clone())).await;
+
+    Ok(Json(started))
+}
@@ -0,0 +1,151 @@
+use axum::{
+    body::{boxed, Body, Full},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::MethodRouter,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::SqlitePool;
+use crate::models::workout_entry::{
+    WorkoutEntry, WorkoutEntryDetailed, WorkoutEntryDetailedV2, WorkoutEntryV2,
+};
+
+// v1 was deprecated on 2026-10-19 and answers 410 Gone from the sunset date on
+pub const V1_DEPRECATED_AT: i64 = 1792368000;
+pub const V1_SUNSET: &str = "Fri, 30 Apr 2027 00:00:00 GMT";
+pub const V1_SUNSET_AT: i64 = 1809043200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiVersion {
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    pub const CURRENT: ApiVersion = ApiVersion::V2;
+
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => "/api/v1",
+            ApiVersion::V2 => "/api/v2",
+        }
+    }
+
+    pub fn is_deprecated(self) -> bool {
+        self != ApiVersion::CURRENT
+    }
+
+    // v2 runs the v1 handler and converts its body to the v2 shape of T
+    pub fn adapt<T: Upgrade>(self, route: MethodRouter<SqlitePool>) -> MethodRouter<SqlitePool> {
+        match self {
+            ApiVersion::V1 => route,
+            ApiVersion::V2 => route.layer(middleware::from_fn(upgrade_response::<T>)),
+        }
+    }
+}
+
+// A v1 response body that changed shape in v2
+pub trait Upgrade: DeserializeOwned + Send + 'static {
+    type V2: Serialize;
+
+    fn upgrade(self) -> Self::V2;
+}
+
+impl Upgrade for WorkoutEntry {
+    type V2 = WorkoutEntryV2;
+
+    fn upgrade(self) -> WorkoutEntryV2 {
+        self.into()
+    }
+}
+
+impl Upgrade for WorkoutEntryDetailed {
+    type V2 = WorkoutEntryDetailedV2;
+
+    fn upgrade(self) -> WorkoutEntryDetailedV2 {
+        self.into()
+    }
+}
+
+impl<T: Upgrade> Upgrade for Vec<T> {
+    type V2 = Vec<T::V2>;
+
+    fn upgrade(self) -> Vec<T::V2> {
+        self.into_iter().map(Upgrade::upgrade).collect()
+    }
+}
+
+// POST /api/workout_entries: {"status": "success", "entry": ...}
+#[derive(Deserialize)]
+pub struct CreatedEntry {
+    status: String,
+    entry: WorkoutEntry,
+}
+
+#[derive(Serialize)]
+pub struct CreatedEntryV2 {
+    status: String,
+    entry: WorkoutEntryV2,
+}
+
+impl Upgrade for CreatedEntry {
+    type V2 = CreatedEntryV2;
+
+    fn upgrade(self) -> CreatedEntryV2 {
+        CreatedEntryV2 { status: self.status, entry: self.entry.into() }
+    }
+}
+
+// Bodies that aren't a T (errors, 304s, a deleted message) pass through unchanged
+async fn upgrade_response<T: Upgrade>(request: Request<Body>, next: Next<Body>) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    if !response.status().is_success() || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!("Could not read response body: {:?}", e);
+            return Response::from_parts(parts, boxed(Full::default()));
+        }
+    };
+
+    let upgraded = serde_json::from_slice::<T>(&bytes)
+        .ok()
+        .and_then(|value| serde_json::to_vec(&value.upgrade()).ok());
+    let body = match upgraded {
+        Some(upgraded) => {
+            parts.headers.remove(header::CONTENT_LENGTH);
+            upgraded.into()
+        }
+        None => bytes,
+    };
+
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+pub fn v1_is_gone(now: i64) -> bool {
+    now >= V1_SUNSET_AT
+}
+
+// Deprecation (RFC 9745) and Sunset (RFC 8594) headers. No successor Link: old clients read
+// the Link header for paging and would trip over a second one.
+pub async fn deprecated(request: Request<Body>, next: Next<Body>) -> Response {
+    let mut response = match v1_is_gone(chrono::Utc::now().timestamp()) {
+        true => (StatusCode::GONE, "API v1 has been retired; use /api/v2").into_response(),
+        false => next.run(request).await,
+    };
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", HeaderValue::from_str(&format!("@{}", V1_DEPRECATED_AT)).unwrap());
+    headers.insert("sunset", HeaderValue::from_static(V1_SUNSET));
+
+    response
+}
@@ -0,0 +1,248 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{StatusCode, Uri},
+    Json,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::SqlitePool;
+use crate::api::routes::internal_error;
+use crate::db::analytics::{estimated_one_rep_max, get_best_one_rep_max};
+use crate::db::connection::{get_workout_by_id, get_workout_entry, is_admin};
+use crate::db::webhooks::{
+    create_webhook, delete_webhook, enqueue_deliveries, get_deliveries, get_webhook, get_webhooks,
+};
+use crate::live::LiveHub;
+use crate::middleware::auth::AuthUser;
+use crate::models::live::LiveEvent;
+use crate::models::webhook::{
+    CreatedWebhook, DeliveryLogQuery, NewWebhook, PersonalRecordAchieved, Webhook, WebhookDelivery,
+    WebhookEvent, WebhookPayload, DEFAULT_LOG_LIMIT, MAX_LOG_LIMIT, WEBHOOK_EVENTS,
+};
+use crate::models::workout::CreatedRecords;
+use crate::models::workout_entry::WorkoutEntry;
+use crate::webhooks::{allow_loopback, check_target};
+
+const SECRET_LENGTH: usize = 32;
+
+// Queues the event for the webhooks listening to it. The write that caused it has already
+// happened, so a failure here is logged rather than failing the request.
+pub(crate) async fn notify(pool: &SqlitePool, user_id: i64, event: WebhookEvent) {
+    let now = chrono::Utc::now();
+    let payload = WebhookPayload {
+        event: &event,
+        user_id,
+        occurred_at: now.to_rfc3339(),
+    };
+    let payload = match serde_json::to_string(&payload) {
+        Ok(payload) => payload,
+        Err(e) => return tracing::error!("Could not serialize {} webhook: {:?}", event.name(), e),
+    };
+
+    if let Err(e) = enqueue_deliveries(pool, user_id, event.name(), &payload, now.timestamp()).await {
+        tracing::error!("Database error: {:?}", e);
+    }
+}
+
+// entry.created, and pr.achieved when the set beats the best estimated 1RM logged before it
+pub(crate) async fn notify_entry_created(pool: &SqlitePool, user_id: i64, entry: &WorkoutEntry) {
+    notify(pool, user_id, WebhookEvent::EntryCreated(entry.clone())).await;
+
+    let weight = entry.weight.unwrap_or(0.0) as f64;
+    if weight <= 0.0 {
+        return;
+    }
+
+    let previous_best = match get_best_one_rep_max(pool, user_id, entry.exercise_id, entry.id).await {
+        Ok(Some(best)) => best,
+        Ok(None) => return, // the first set of an exercise sets no record
+        Err(e) => return tracing::error!("Database error: {:?}", e),
+    };
+
+    let e1rm = estimated_one_rep_max(weight, entry.reps);
+    if e1rm > previous_best {
+        let record = PersonalRecordAchieved {
+            entry_id: entry.id,
+            workout_id: entry.workout_id,
+            exercise_id: entry.exercise_id,
+            weight,
+            reps: entry.reps,
+            estimated_one_rep_max: e1rm,
+            previous_best,
+        };
+        notify(pool, user_id, WebhookEvent::PersonalRecord(record)).await;
+    }
+}
+
+// Everything that follows a logged set, however it was written: devices following the
+// workout see it, and webhooks hear entry.created (and pr.achieved)
+pub(crate) async fn entry_created(pool: &SqlitePool, live: &LiveHub, user_id: i64, entry: &WorkoutEntry) {
+    live.publish(entry.workout_id, LiveEvent::EntryCreated { entry: entry.clone() });
+    notify_entry_created(pool, user_id, entry).await;
+}
+
+// The events of the rows an offline sync push or an import created, oldest first
+pub(crate) async fn records_created(pool: &SqlitePool, live: &LiveHub, user_id: i64, created: &CreatedRecords) {
+    for id in &created.workouts {
+        match get_workout_by_id(pool, *id).await {
+            Ok(Some(workout)) => notify(pool, user_id, WebhookEvent::WorkoutCreated(workout)).await,
+            Ok(None) => {} // deleted again in the meantime
+            Err(e) => tracing::error!("Database error: {:?}", e),
+        }
+    }
+
+    for id in &created.entries {
+        match get_workout_entry(pool, *id).await {
+            Ok(Some(entry)) => entry_created(pool, live, user_id, &entry).await,
+            Ok(None) => {}
+            Err(e) => tracing::error!("Database error: {:?}", e),
+        }
+    }
+}
+
+fn validate_webhook(webhook: &NewWebhook) -> Result<(), (StatusCode, String)> {
+    let bad_request = |message: String| Err((StatusCode::BAD_REQUEST, message));
+
+    let valid_url = webhook.url.parse::<Uri>().is_ok_and(|uri| {
+        matches!(uri.scheme_str(), Some("http" | "https")) && uri.host().is_some()
+    });
+    if !valid_url {
+        return bad_request(format!("Invalid url '{}', expected http(s)://...", webhook.url));
+    }
+
+    if webhook.events.is_empty() {
+        return bad_request("Subscribe to at least one event".to_string());
+    }
+    if let Some(unknown) = webhook.events.iter().find(|e| !WEBHOOK_EVENTS.contains(&e.as_str())) {
+        return bad_request(format!(
+            "Unknown event '{}', expected one of {}",
+            unknown,
+            WEBHOOK_EVENTS.join(", ")
+        ));
+    }
+
+    Ok(())
+}
+
+// GET /api/webhooks
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks",
+    tag = "Webhooks",
+    responses(
+        (status = 200, description = "Your webhooks", body = [Webhook]),
+    ),
+)]
+pub async fn list_webhooks(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<Webhook>>, (StatusCode, String)> {
+    let webhooks = get_webhooks(&pool, auth.user_id)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(webhooks.into_iter().map(Webhook::from).collect()))
+}
+
+// POST /api/webhooks
+#[utoipa::path(
+    post,
+    path = "/api/v1/webhooks",
+    tag = "Webhooks",
+    request_body = NewWebhook,
+    responses(
+        (status = 200, description = "The webhook with its signing secret, shown only this once", body = CreatedWebhook),
+        (status = 400, description = "Invalid input, or a url on a private network"),
+        (status = 403, description = "all_users needs an admin"),
+    ),
+)]
+pub async fn create_webhook_route(
+    auth: AuthUser,
+    State(pool): State<SqlitePool>,
+    Json(mut webhook): Json<NewWebhook>,
+) -> Result<Json<CreatedWebhook>, (StatusCode, String)> {
+    validate_webhook(&webhook)?;
+    let uri: Uri = webhook.url.parse().map_err(|_| (StatusCode::BAD_REQUEST, "Invalid url".to_string()))?;
+    check_target(&uri, allow_loopback())
+        .await
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    webhook.events.sort();
+    webhook.events.dedup();
+
+    if webhook.all_users && !is_admin(&pool, auth.user_id).await.map_err(internal_error)? {
+        return Err((StatusCode::FORBIDDEN, "Only admins can watch all users".to_string()));
+    }
+
+    let secret: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(SECRET_LENGTH)
+        .map(char::from)
+        .collect();
+    let secret = format!("whsec_{}", secret);
+
+    tracing::info!("Registering webhook to {} for user {}", webhook.url, auth.user_id);
+
+    let now = chrono::Utc::now().timestamp();
+    let row = create_webhook(&pool, auth.user_id, &webhook.url, &secret, &webhook.events, webhook.all_users, now)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(CreatedWebhook { webhook: row.into(), secret }))
+}
+
+// DELETE /api/webhooks/:id
+#[utoipa::path(
+    delete,
+    path = "/api/v1/webhooks/{id}",
+    tag = "Webhooks",
+    params(("id" = i64, Path, description = "Webhook id")),
+    responses(
+        (status = 200, description = "Deleted", body = String),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn delete_webhook_route(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<String>, (StatusCode, String)> {
+    let deleted = delete_webhook(&pool, auth.user_id, id)
+        .await
+        .map_err(internal_error)?;
+
+    if !deleted {
+        return Err((StatusCode::NOT_FOUND, format!("Webhook {} not found", id)));
+    }
+
+    Ok(Json(format!("Webhook {} deleted", id)))
+}
+
+// GET /api/webhooks/:id/deliveries (newest first)
+#[utoipa::path(
+    get,
+    path = "/api/v1/webhooks/{id}/deliveries",
+    tag = "Webhooks",
+    params(("id" = i64, Path, description = "Webhook id"), DeliveryLogQuery),
+    responses(
+        (status = 200, description = "Deliveries, newest first", body = [WebhookDelivery]),
+        (status = 404, description = "Webhook not found"),
+    ),
+)]
+pub async fn list_deliveries(
+    auth: AuthUser,
+    Path(id): Path<i64>,
+    Query(query): Query<DeliveryLogQuery>,
+    State(pool): State<SqlitePool>,
+) -> Result<Json<Vec<WebhookDelivery>>, (StatusCode, String)> {
+    get_webhook(&pool, id)
+        .await
+        .map_err(internal_error)?
+        .filter(|w| w.user_id == auth.user_id)
+        .ok_or((StatusCode::NOT_FOUND, format!("Webhook {} not found", id)))?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LOG_LIMIT).clamp(1, MAX_LOG_LIMIT);
+    let deliveries = get_deliveries(&pool, id, limit)
+        .await
+        .map_err(internal_error)?;
+
+    Ok(Json(deliveries))
+}
@@ -1,16 +1,14 @@
 use rand::rngs::OsRng;
 use argon2::{Argon2, PasswordHasher, PasswordVerifier};
-use argon2::password_hash::{SaltString, PasswordHash, PasswordHasher as _};
+use argon2::password_hash::{SaltString, PasswordHash};
 
 pub fn hash_password(password: &str) -> String {
     let salt = SaltString::generate(&mut OsRng);
     let argon2 = Argon2::default();
 
-    let hash = argon2.hash_password(password.as_bytes(), &salt)
+    argon2.hash_password(password.as_bytes(), &salt)
         .unwrap()
-        .to_string();
-
-    hash
+        .to_string()
 }
 
 pub fn verify_password(hashed: &str, password: &str) -> bool {
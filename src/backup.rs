@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use crate::models::backup::{
+    BackupArchive, BackupHeader, BackupLine, BACKUP_FORMAT, BACKUP_SCHEMA_VERSION,
+};
+
+fn check_header(header: &BackupHeader) -> Result<(), String> {
+    if header.format != BACKUP_FORMAT {
+        return Err(format!("Not a {} archive", BACKUP_FORMAT));
+    }
+    if header.schema_version != BACKUP_SCHEMA_VERSION {
+        return Err(format!(
+            "Archive has schema version {}, this server expects {}",
+            header.schema_version, BACKUP_SCHEMA_VERSION
+        ));
+    }
+    Ok(())
+}
+
+// Accepts both the JSON archive and NDJSON (header line, then one row per line)
+pub fn parse_backup(body: &str) -> Result<BackupArchive, String> {
+    let body = body.trim_start_matches('\u{feff}');
+
+    if let Ok(archive) = serde_json::from_str::<BackupArchive>(body) {
+        check_header(&archive.header)?;
+        return Ok(archive);
+    }
+
+    let mut lines = body
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+
+    let (_, first) = lines.next().ok_or("The archive is empty")?;
+    let header: BackupHeader = serde_json::from_str(first)
+        .map_err(|e| format!("Line 1 is not a backup header: {}", e))?;
+    check_header(&header)?;
+
+    let mut tables: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    for (index, line) in lines {
+        let line: BackupLine = serde_json::from_str(line)
+            .map_err(|e| format!("Line {}: {}", index + 1, e))?;
+        tables.entry(line.table).or_default().push(line.row);
+    }
+
+    Ok(BackupArchive { header, tables })
+}
+
+pub fn to_ndjson(archive: &BackupArchive, table_order: &[&str]) -> String {
+    let mut out = serde_json::to_string(&archive.header).expect("header serializes");
+    out.push('\n');
+
+    for table in table_order {
+        for row in archive.tables.get(*table).into_iter().flatten() {
+            let line = serde_json::json!({ "table": table, "row": row });
+            out.push_str(&line.to_string());
+            out.push('\n');
+        }
+    }
+
+    out
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use crate::models::analytics::WorkoutSummary;
+
+// RFC 5545 limits content lines to 75 octets
+const MAX_LINE_OCTETS: usize = 75;
+
+// One all-day event
+#[derive(Debug)]
+pub struct CalendarEvent {
+    pub uid: String,
+    pub date: NaiveDate,
+    pub summary: String,
+    pub description: Option<String>,
+}
+
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Long lines continue on the next line after CRLF and a space, never splitting a UTF-8 character
+fn push_line(out: &mut String, line: &str) {
+    let mut rest = line;
+    let mut limit = MAX_LINE_OCTETS;
+
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        out.push_str(&rest[..split]);
+        out.push_str("\r\n ");
+        rest = &rest[split..];
+        limit = MAX_LINE_OCTETS - 1;
+    }
+
+    out.push_str(rest);
+    out.push_str("\r\n");
+}
+
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{:.0}", value) } else { format!("{:.1}", value) }
+}
+
+// Event text for a completed workout, from get_workout_summary
+pub fn workout_description(notes: Option<&str>, summary: &WorkoutSummary) -> String {
+    let mut lines = vec![format!(
+        "{} exercises, {} sets, {} reps, {} kg volume",
+        summary.exercises.len(),
+        summary.total_sets,
+        summary.total_reps,
+        number(summary.total_volume),
+    )];
+
+    for exercise in &summary.exercises {
+        let (name, sets, reps) = (&exercise.name, exercise.sets, exercise.reps);
+        let line = match exercise.weight {
+            weight if weight > 0.0 => format!("{}: {} x {} @ {} kg", name, sets, reps, number(weight)),
+            _ => format!("{}: {} x {}", name, sets, reps),
+        };
+        lines.push(line);
+    }
+
+    if let Some(notes) = notes.filter(|n| !n.trim().is_empty()) {
+        lines.push(String::new());
+        lines.push(notes.to_string());
+    }
+
+    lines.join("\n")
+}
+
+pub fn render_calendar(name: &str, events: &[CalendarEvent], stamp: DateTime<Utc>) -> String {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut out = String::new();
+
+    push_line(&mut out, "BEGIN:VCALENDAR");
+    push_line(&mut out, "VERSION:2.0");
+    push_line(&mut out, "PRODID:-//GymBuddy//Workouts//EN");
+    push_line(&mut out, "CALSCALE:GREGORIAN");
+    push_line(&mut out, &format!("X-WR-CALNAME:{}", escape_text(name)));
+
+    for event in events {
+        push_line(&mut out, "BEGIN:VEVENT");
+        push_line(&mut out, &format!("UID:{}", event.uid));
+        push_line(&mut out, &format!("DTSTAMP:{}", stamp));
+        push_line(&mut out, &format!("DTSTART;VALUE=DATE:{}", event.date.format("%Y%m%d")));
+        push_line(
+            &mut out,
+            &format!("DTEND;VALUE=DATE:{}", (event.date + Duration::days(1)).format("%Y%m%d")),
+        );
+        push_line(&mut out, &format!("SUMMARY:{}", escape_text(&event.summary)));
+        if let Some(description) = &event.description {
+            push_line(&mut out, &format!("DESCRIPTION:{}", escape_text(description)));
+        }
+        push_line(&mut out, "TRANSP:TRANSPARENT");
+        push_line(&mut out, "END:VEVENT");
+    }
+
+    push_line(&mut out, "END:VCALENDAR");
+    out
+}
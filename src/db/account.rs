@@ -0,0 +1,208 @@
+use std::collections::{BTreeSet, HashMap};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use crate::db::analytics::get_volume_targets;
+use crate::db::recovery::get_recovery_settings;
+use crate::db::rest::get_rest_defaults;
+use crate::db::schedule::get_scheduled_sessions;
+use crate::db::templates::{get_template_detailed, get_templates_for_user};
+use crate::db::webhooks::get_webhooks;
+use crate::models::{
+    account::{
+        AccountExport, AccountProfile, ExportedProgressionRule, ExportedWorkout,
+        ACCOUNT_EXPORT_VERSION,
+    },
+    entry_group::EntryGroup,
+    exercise::Exercise,
+    webhook::Webhook,
+    workout::Workout,
+    workout_entry::WorkoutEntry,
+};
+
+pub async fn get_account_profile(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Option<AccountProfile>, sqlx::Error> {
+    let profile = sqlx::query_as::<_, AccountProfile>(
+        "SELECT id, username, email FROM users WHERE id = ?"
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(profile)
+}
+
+pub async fn get_password_hash(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Option<String>, sqlx::Error> {
+    let hash: Option<(String,)> = sqlx::query_as("SELECT password_hash FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(hash.map(|(hash,)| hash))
+}
+
+pub async fn export_account(
+    pool: &SqlitePool,
+    user: AccountProfile,
+) -> Result<AccountExport, sqlx::Error> {
+    let user_id = user.id;
+
+    let workouts = sqlx::query_as::<_, Workout>(
+        "SELECT id, user_id, date, notes, version, finished_at FROM workouts WHERE user_id = ? ORDER BY date, id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let entries = sqlx::query_as::<_, WorkoutEntry>(
+        "
+        SELECT we.id, we.workout_id, we.exercise_id, we.sets, we.reps, we.weight, we.rpe,
+            we.position, we.group_id, we.version, we.rest_seconds
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        WHERE w.user_id = ?
+        ORDER BY we.workout_id, we.position, we.id
+        "
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let groups = sqlx::query_as::<_, EntryGroup>(
+        "
+        SELECT g.id, g.workout_id, g.kind, g.rounds, g.duration_seconds
+        FROM entry_groups g
+        JOIN workouts w ON g.workout_id = w.id
+        WHERE w.user_id = ?
+        ORDER BY g.id
+        "
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut exercise_ids: BTreeSet<i64> = entries.iter().map(|e| e.exercise_id).collect();
+
+    let mut entries_by_workout: HashMap<i64, Vec<WorkoutEntry>> = HashMap::new();
+    for entry in entries {
+        entries_by_workout.entry(entry.workout_id).or_default().push(entry);
+    }
+    let mut groups_by_workout: HashMap<i64, Vec<EntryGroup>> = HashMap::new();
+    for group in groups {
+        groups_by_workout.entry(group.workout_id).or_default().push(group);
+    }
+
+    let workouts = workouts
+        .into_iter()
+        .map(|w| ExportedWorkout {
+            entries: entries_by_workout.remove(&w.id).unwrap_or_default(),
+            groups: groups_by_workout.remove(&w.id).unwrap_or_default(),
+            id: w.id,
+            date: w.date,
+            notes: w.notes,
+        })
+        .collect();
+
+    let mut templates = Vec::new();
+    for template in get_templates_for_user(pool, user_id).await? {
+        if let Some(detailed) = get_template_detailed(pool, template.id).await? {
+            exercise_ids.extend(detailed.exercises.iter().map(|e| e.exercise_id));
+            templates.push(detailed);
+        }
+    }
+
+    let rules: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT exercise_id, rule FROM progression_rules WHERE user_id = ? ORDER BY exercise_id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let progression_rules = rules
+        .into_iter()
+        .filter_map(|(exercise_id, rule)| {
+            serde_json::from_str(&rule)
+                .ok()
+                .map(|rule| ExportedProgressionRule { exercise_id, rule })
+        })
+        .collect();
+
+    let rest_defaults = get_rest_defaults(pool, user_id).await?;
+    exercise_ids.extend(rest_defaults.iter().map(|d| d.exercise_id));
+
+    let mut exercises = Vec::new();
+    for id in exercise_ids {
+        if let Some(exercise) = sqlx::query_as::<_, Exercise>(
+            "SELECT id, name, muscle_group, description FROM exercises WHERE id = ?"
+        )
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        {
+            exercises.push(exercise);
+        }
+    }
+
+    Ok(AccountExport {
+        version: ACCOUNT_EXPORT_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        user,
+        workouts,
+        exercises,
+        templates,
+        progression_rules,
+        volume_targets: get_volume_targets(pool, user_id).await?,
+        recovery_settings: get_recovery_settings(pool, user_id).await?,
+        scheduled_sessions: get_scheduled_sessions(pool, user_id, "").await?,
+        rest_defaults,
+        webhooks: get_webhooks(pool, user_id).await?.into_iter().map(Webhook::from).collect(),
+    })
+}
+
+// Removes the user and every row that belongs to them in one transaction.
+// Shared exercises are kept. Returns false when the user doesn't exist.
+pub async fn delete_account(pool: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // Children before parents, so this works with foreign keys on or off
+    let statements = [
+        "DELETE FROM rest_timers WHERE workout_id IN (SELECT id FROM workouts WHERE user_id = ?)",
+        "DELETE FROM workout_entries WHERE workout_id IN (SELECT id FROM workouts WHERE user_id = ?)",
+        "DELETE FROM entry_groups WHERE workout_id IN (SELECT id FROM workouts WHERE user_id = ?)",
+        "DELETE FROM workouts WHERE user_id = ?",
+        "DELETE FROM workout_template_exercises WHERE template_id IN (SELECT id FROM workout_templates WHERE user_id = ?)",
+        "DELETE FROM workout_templates WHERE user_id = ?",
+        "DELETE FROM progression_rules WHERE user_id = ?",
+        "DELETE FROM muscle_volume_targets WHERE user_id = ?",
+        "DELETE FROM muscle_recovery_settings WHERE user_id = ?",
+        "DELETE FROM scheduled_sessions WHERE user_id = ?",
+        "DELETE FROM calendar_feeds WHERE user_id = ?",
+        "DELETE FROM exercise_rest_defaults WHERE user_id = ?",
+        "DELETE FROM idempotency_keys WHERE user_id = ?",
+        "DELETE FROM webhook_deliveries WHERE webhook_id IN (SELECT id FROM webhooks WHERE user_id = ?)",
+        "DELETE FROM webhooks WHERE user_id = ?",
+        // Last, since deleting the workouts above leaves tombstones
+        "DELETE FROM sync_tombstones WHERE user_id = ?",
+        "DELETE FROM sync_state WHERE user_id = ?",
+    ];
+
+    for statement in statements {
+        sqlx::query(statement)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let result = sqlx::query("DELETE FROM users WHERE id = ?")
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
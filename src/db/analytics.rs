@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use sqlx::{Row, SqlitePool};
+use crate::db::connection::entry_volume;
+use crate::models::analytics::{
+    DeloadSuggestion, FatigueReport, FatigueWarning, FatigueWarningKind, MuscleVolumeReport,
+    MuscleVolumeStatus, MuscleWeekSets, NewVolumeTarget, VolumeStatus, VolumeTarget,
+};
+
+pub const DEFAULT_STALL_SESSIONS: usize = 3;
+pub const DEFAULT_ACWR_THRESHOLD: f64 = 1.5;
+
+// Volume cut recommended for a deload week
+const DELOAD_VOLUME_REDUCTION: u32 = 40;
+
+// Stalled or regressing exercises needed before a deload is suggested on their own
+const STALLS_BEFORE_DELOAD: usize = 2;
+
+pub const DEFAULT_TREND_WEEKS: u32 = 4;
+pub const MAX_TREND_WEEKS: u32 = 52;
+
+// Landmarks used for muscle groups the user hasn't configured
+const DEFAULT_MEV: i32 = 10;
+const DEFAULT_MAV: i32 = 16;
+const DEFAULT_MRV: i32 = 22;
+
+// Sets logged below this RPE are treated as warm-ups; sets without RPE count as hard
+pub const HARD_SET_MIN_RPE: f64 = 7.0;
+
+// Estimated one-rep max (Epley), used to compare sessions with different rep counts
+pub fn estimated_one_rep_max(weight: f64, reps: i32) -> f64 {
+    weight * (1.0 + reps as f64 / 30.0)
+}
+
+pub fn today() -> NaiveDate {
+    Utc::now().date_naive()
+}
+
+// Workout dates are stored as text; anything that isn't YYYY-MM-DD is ignored by date maths
+pub fn parse_date(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok()
+}
+
+// Monday of the week containing `date`
+pub fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(date.weekday().num_days_from_monday() as i64)
+}
+
+pub async fn get_fatigue_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    stall_sessions: usize,
+    acwr_threshold: f64,
+) -> Result<FatigueReport, sqlx::Error> {
+    let today = today();
+    let mut warnings = Vec::new();
+
+    // Acute:chronic workload ratio, over the last 7 and 28 days including today
+    let chronic_start = today - Duration::days(28);
+    let acute_start = today - Duration::days(7);
+
+    let rows = sqlx::query(
+        "
+        SELECT w.date, we.sets, we.reps, we.weight
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        WHERE w.user_id = ? AND w.date >= ?
+        "
+    )
+    .bind(user_id)
+    .bind(chronic_start.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let mut acute_volume = 0.0;
+    let mut chronic_total = 0.0;
+
+    for row in rows {
+        let Some(date) = parse_date(&row.get::<String, _>("date")) else {
+            continue;
+        };
+        if date > today || date <= chronic_start {
+            continue;
+        }
+
+        let volume = entry_volume(
+            row.get("sets"),
+            row.get("reps"),
+            row.get::<Option<f64>, _>("weight").unwrap_or(0.0),
+        );
+
+        chronic_total += volume;
+        if date > acute_start {
+            acute_volume += volume;
+        }
+    }
+
+    let chronic_volume = chronic_total / 4.0;
+    let acwr = (chronic_volume > 0.0).then(|| acute_volume / chronic_volume);
+
+    if let Some(ratio) = acwr
+        && ratio > acwr_threshold
+    {
+        warnings.push(FatigueWarning {
+            kind: FatigueWarningKind::WorkloadSpike,
+            exercise_id: None,
+            exercise_name: None,
+            message: format!(
+                "Last 7 days' volume is {:.2}x your 28-day weekly average (threshold {:.2})",
+                ratio, acwr_threshold
+            ),
+        });
+    }
+
+    // Stalls and regressions, judged on the top set of each session
+    let rows = sqlx::query(
+        "
+        SELECT exercise_id, name, date, reps, weight
+        FROM (
+            SELECT
+                we.exercise_id,
+                e.name,
+                w.date,
+                we.reps,
+                we.weight,
+                ROW_NUMBER() OVER (
+                    PARTITION BY we.workout_id, we.exercise_id
+                    ORDER BY COALESCE(we.weight, 0) DESC, we.reps DESC, we.id DESC
+                ) AS rank
+            FROM workout_entries we
+            JOIN workouts w ON we.workout_id = w.id
+            JOIN exercises e ON we.exercise_id = e.id
+            WHERE w.user_id = ? AND we.weight > 0
+        )
+        WHERE rank = 1
+        ORDER BY exercise_id, date DESC
+        "
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut sessions: HashMap<i64, (String, Vec<f64>)> = HashMap::new();
+    for row in rows {
+        let (_, e1rms) = sessions
+            .entry(row.get("exercise_id"))
+            .or_insert_with(|| (row.get("name"), Vec::new()));
+        e1rms.push(estimated_one_rep_max(row.get("weight"), row.get("reps")));
+    }
+
+    let mut exercise_ids: Vec<_> = sessions.keys().copied().collect();
+    exercise_ids.sort_unstable();
+
+    let mut stalled = 0;
+    for exercise_id in exercise_ids {
+        let (name, e1rms) = &sessions[&exercise_id];
+        let Some(kind) = detect_stall(e1rms, stall_sessions) else {
+            continue;
+        };
+
+        stalled += 1;
+        let message = match kind {
+            FatigueWarningKind::Regression => format!(
+                "{} has gone down for {} sessions in a row",
+                name, stall_sessions
+            ),
+            _ => format!(
+                "{} hasn't improved over the last {} sessions",
+                name, stall_sessions
+            ),
+        };
+
+        warnings.push(FatigueWarning {
+            kind,
+            exercise_id: Some(exercise_id),
+            exercise_name: Some(name.clone()),
+            message,
+        });
+    }
+
+    let spiked = acwr.is_some_and(|ratio| ratio > acwr_threshold);
+    let suggested_deload = (spiked || stalled >= STALLS_BEFORE_DELOAD).then(|| {
+        let start = week_start(today) + Duration::days(7);
+        let reason = if spiked {
+            "Workload rose faster than your body has adapted to".to_string()
+        } else {
+            format!("{} exercises have stalled or regressed", stalled)
+        };
+
+        DeloadSuggestion {
+            start_date: start.to_string(),
+            end_date: (start + Duration::days(6)).to_string(),
+            volume_reduction_percent: DELOAD_VOLUME_REDUCTION,
+            reason,
+        }
+    });
+
+    Ok(FatigueReport {
+        acute_volume,
+        chronic_volume,
+        acwr,
+        acwr_threshold,
+        warnings,
+        suggested_deload,
+    })
+}
+
+// `e1rms` is newest first. The last `window` sessions stall when none beats the session
+// before them, and regress when each one is lower than the one before.
+pub fn detect_stall(e1rms: &[f64], window: usize) -> Option<FatigueWarningKind> {
+    if window == 0 || e1rms.len() <= window {
+        return None;
+    }
+
+    let baseline = e1rms[window];
+    let recent = &e1rms[..window];
+
+    if recent.iter().any(|&e| e > baseline) {
+        return None;
+    }
+
+    let regressing = e1rms[..=window].windows(2).all(|pair| pair[0] < pair[1]);
+    Some(if regressing {
+        FatigueWarningKind::Regression
+    } else {
+        FatigueWarningKind::Stall
+    })
+}
+
+pub async fn get_volume_targets(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<VolumeTarget>, sqlx::Error> {
+    let targets = sqlx::query_as::<_, VolumeTarget>(
+        "
+        SELECT muscle_group, mev, mav, mrv
+        FROM muscle_volume_targets
+        WHERE user_id = ?
+        ORDER BY muscle_group
+        "
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(targets)
+}
+
+pub async fn set_volume_target(
+    pool: &SqlitePool,
+    user_id: i64,
+    muscle_group: &str,
+    target: &NewVolumeTarget,
+) -> Result<VolumeTarget, sqlx::Error> {
+    sqlx::query(
+        "
+        INSERT INTO muscle_volume_targets (user_id, muscle_group, mev, mav, mrv)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(user_id, muscle_group)
+        DO UPDATE SET mev = excluded.mev, mav = excluded.mav, mrv = excluded.mrv
+        "
+    )
+    .bind(user_id)
+    .bind(muscle_group)
+    .bind(target.mev)
+    .bind(target.mav)
+    .bind(target.mrv)
+    .execute(pool)
+    .await?;
+
+    Ok(VolumeTarget {
+        muscle_group: muscle_group.to_string(),
+        mev: target.mev,
+        mav: target.mav,
+        mrv: target.mrv,
+    })
+}
+
+pub fn volume_status(sets: i64, target: &VolumeTarget) -> VolumeStatus {
+    if sets < target.mev as i64 {
+        VolumeStatus::Under
+    } else if sets > target.mrv as i64 {
+        VolumeStatus::Over
+    } else {
+        VolumeStatus::InRange
+    }
+}
+
+// Hard sets per muscle for this week and the `weeks - 1` weeks before it
+pub async fn get_muscle_volume_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    weeks: u32,
+) -> Result<MuscleVolumeReport, sqlx::Error> {
+    let this_week = week_start(today());
+    let first_week = this_week - Duration::weeks(weeks as i64 - 1);
+
+    let rows = sqlx::query(
+        "
+        SELECT w.date, e.muscle_group, we.sets, we.rpe
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN exercises e ON we.exercise_id = e.id
+        WHERE w.user_id = ? AND w.date >= ?
+        "
+    )
+    .bind(user_id)
+    .bind(first_week.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    // Muscle groups are free text, so they are matched case-insensitively
+    let mut sets_by_muscle: HashMap<String, (String, Vec<i64>)> = HashMap::new();
+
+    for row in rows {
+        let Some(date) = parse_date(&row.get::<String, _>("date")) else {
+            continue;
+        };
+        if date > this_week + Duration::days(6) {
+            continue;
+        }
+        if row.get::<Option<f64>, _>("rpe").is_some_and(|rpe| rpe < HARD_SET_MIN_RPE) {
+            continue;
+        }
+
+        let muscle_group: String = row.get("muscle_group");
+        let week = ((week_start(date) - first_week).num_days() / 7) as usize;
+
+        let (_, weekly) = sets_by_muscle
+            .entry(muscle_group.to_lowercase())
+            .or_insert_with(|| (muscle_group, vec![0; weeks as usize]));
+        weekly[week] += row.get::<i32, _>("sets") as i64;
+    }
+
+    let mut targets: HashMap<String, VolumeTarget> = get_volume_targets(pool, user_id)
+        .await?
+        .into_iter()
+        .map(|t| (t.muscle_group.to_lowercase(), t))
+        .collect();
+
+    // Configured muscles are always reported, even without sets
+    for (key, target) in &targets {
+        sets_by_muscle
+            .entry(key.clone())
+            .or_insert_with(|| (target.muscle_group.clone(), vec![0; weeks as usize]));
+    }
+
+    let mut muscles: Vec<MuscleVolumeStatus> = sets_by_muscle
+        .into_iter()
+        .map(|(key, (muscle_group, weekly))| {
+            let target = targets.remove(&key).unwrap_or_else(|| VolumeTarget {
+                muscle_group: muscle_group.clone(),
+                mev: DEFAULT_MEV,
+                mav: DEFAULT_MAV,
+                mrv: DEFAULT_MRV,
+            });
+            let sets = *weekly.last().unwrap_or(&0);
+
+            let trend = weekly
+                .iter()
+                .enumerate()
+                .map(|(i, &sets)| MuscleWeekSets {
+                    week_start: (first_week + Duration::weeks(i as i64)).to_string(),
+                    sets,
+                })
+                .collect();
+
+            MuscleVolumeStatus {
+                status: volume_status(sets, &target),
+                muscle_group,
+                sets,
+                target,
+                trend,
+            }
+        })
+        .collect();
+
+    muscles.sort_by(|a, b| b.sets.cmp(&a.sets).then_with(|| a.muscle_group.cmp(&b.muscle_group)));
+
+    Ok(MuscleVolumeReport {
+        week_start: this_week.to_string(),
+        weeks,
+        muscles,
+    })
+}
+
+// Best estimated 1RM the user logged for the exercise before the entry (earlier workouts, or
+// earlier in the same one), None before the first set. Later or imported history doesn't count.
+pub async fn get_best_one_rep_max(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+    entry_id: i64,
+) -> Result<Option<f64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "
+        SELECT MAX(we.weight * (1.0 + we.reps / 30.0))
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN (
+            SELECT cw.date, ce.id
+            FROM workout_entries ce
+            JOIN workouts cw ON ce.workout_id = cw.id
+            WHERE ce.id = ?
+        ) current
+        WHERE w.user_id = ? AND we.exercise_id = ?
+          AND (w.date < current.date OR (w.date = current.date AND we.id < current.id))
+          AND we.weight > 0 AND we.reps > 0
+        "
+    )
+    .bind(entry_id)
+    .bind(user_id)
+    .bind(exercise_id)
+    .fetch_one(pool)
+    .await
+}
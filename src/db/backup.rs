@@ -0,0 +1,341 @@
+use std::collections::{HashMap, HashSet};
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::{Column, Row, Sqlite, SqlitePool, Transaction, TypeInfo, ValueRef};
+use crate::models::backup::{
+    BackupArchive, BackupHeader, BackupRow, RestoreReport, RestoredTable, BACKUP_FORMAT,
+    BACKUP_SCHEMA_VERSION,
+};
+
+pub struct BackupTable {
+    pub name: &'static str,
+    pub has_id: bool, // INTEGER PRIMARY KEY `id`, remapped on restore
+    pub references: &'static [(&'static str, &'static str)], // (column, referenced table)
+}
+
+// Short-lived caches, sync bookkeeping, running timers, the webhook queue (restoring it
+// would send old events again) and the analytics rollups (rebuilt from the restored entries
+// as they're inserted), which a restore has no use for
+pub const SKIPPED_TABLES: &[&str] = &[
+    "idempotency_keys",
+    "sync_state",
+    "sync_tombstones",
+    "rest_timers",
+    "webhook_deliveries",
+    "analytics_daily",
+    "analytics_weekly",
+];
+
+// Every other table created by init_db, parents before children
+pub const BACKUP_TABLES: &[BackupTable] = &[
+    BackupTable { name: "users", has_id: true, references: &[] },
+    BackupTable { name: "exercises", has_id: true, references: &[] },
+    BackupTable { name: "workouts", has_id: true, references: &[("user_id", "users")] },
+    BackupTable { name: "entry_groups", has_id: true, references: &[("workout_id", "workouts")] },
+    BackupTable {
+        name: "workout_entries",
+        has_id: true,
+        references: &[
+            ("workout_id", "workouts"),
+            ("exercise_id", "exercises"),
+            ("group_id", "entry_groups"),
+        ],
+    },
+    BackupTable {
+        name: "progression_rules",
+        has_id: false,
+        references: &[("user_id", "users"), ("exercise_id", "exercises")],
+    },
+    BackupTable { name: "workout_templates", has_id: true, references: &[("user_id", "users")] },
+    BackupTable {
+        name: "workout_template_exercises",
+        has_id: true,
+        references: &[("template_id", "workout_templates"), ("exercise_id", "exercises")],
+    },
+    BackupTable { name: "muscle_volume_targets", has_id: false, references: &[("user_id", "users")] },
+    BackupTable { name: "muscle_recovery_settings", has_id: false, references: &[("user_id", "users")] },
+    BackupTable {
+        name: "scheduled_sessions",
+        has_id: true,
+        references: &[("user_id", "users"), ("template_id", "workout_templates")],
+    },
+    BackupTable { name: "calendar_feeds", has_id: false, references: &[("user_id", "users")] },
+    BackupTable {
+        name: "exercise_rest_defaults",
+        has_id: false,
+        references: &[("user_id", "users"), ("exercise_id", "exercises")],
+    },
+    BackupTable { name: "webhooks", has_id: true, references: &[("user_id", "users")] },
+];
+
+pub enum RestoreError {
+    Invalid(String),
+    Conflict(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RestoreError {
+    fn from(e: sqlx::Error) -> Self {
+        RestoreError::Database(e)
+    }
+}
+
+// Storage class decides the JSON type, so the dump doesn't need to know the schema
+fn row_to_json(row: &sqlx::sqlite::SqliteRow) -> Result<BackupRow, sqlx::Error> {
+    let mut object = BackupRow::new();
+
+    for (i, column) in row.columns().iter().enumerate() {
+        let raw = row.try_get_raw(i)?;
+        let value = if raw.is_null() {
+            Value::Null
+        } else {
+            match raw.type_info().name() {
+                "INTEGER" => Value::from(row.try_get::<i64, _>(i)?),
+                "REAL" => Value::from(row.try_get::<f64, _>(i)?),
+                _ => Value::from(row.try_get::<String, _>(i)?),
+            }
+        };
+        object.insert(column.name().to_string(), value);
+    }
+
+    Ok(object)
+}
+
+pub async fn create_backup(pool: &SqlitePool) -> Result<BackupArchive, sqlx::Error> {
+    // One read transaction, so the archive is a consistent snapshot
+    let mut tx = pool.begin().await?;
+    let mut tables = std::collections::BTreeMap::new();
+
+    for table in BACKUP_TABLES {
+        let order = if table.has_id { "id" } else { "rowid" };
+        let rows = sqlx::query(&format!("SELECT * FROM {} ORDER BY {}", table.name, order))
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let rows = rows.iter().map(row_to_json).collect::<Result<Vec<_>, _>>()?;
+        tables.insert(table.name.to_string(), rows);
+    }
+
+    tx.commit().await?;
+
+    Ok(BackupArchive {
+        header: BackupHeader {
+            format: BACKUP_FORMAT.to_string(),
+            schema_version: BACKUP_SCHEMA_VERSION,
+            created_at: Utc::now().to_rfc3339(),
+        },
+        tables,
+    })
+}
+
+async fn table_columns(
+    tx: &mut Transaction<'_, Sqlite>,
+    table: &str,
+) -> Result<HashSet<String>, sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info(?)")
+        .bind(table)
+        .fetch_all(&mut **tx)
+        .await?;
+
+    Ok(columns.into_iter().map(|(name,)| name).collect())
+}
+
+fn bind_value<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    value: &Value,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match value {
+        Value::Bool(b) => query.bind(*b as i64),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        _ => query.bind(None::<i64>),
+    }
+}
+
+// Loads the archive in one transaction. Rows keep their id when it is free and get a new
+// one otherwise; foreign keys follow the mapping. Exercises with a name that already exists
+// are reused instead of duplicated.
+pub async fn restore_backup(
+    pool: &SqlitePool,
+    archive: &BackupArchive,
+    replace: bool,
+) -> Result<RestoreReport, RestoreError> {
+    if let Some(unknown) = archive
+        .tables
+        .keys()
+        .find(|name| !BACKUP_TABLES.iter().any(|t| t.name == name.as_str()))
+    {
+        return Err(RestoreError::Invalid(format!("Unknown table '{}'", unknown)));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    if replace {
+        // Queued deliveries, timers, replayable responses and tombstones point at ids the restore
+        // hands out again, so they go too. The rollups follow the deletes through their triggers.
+        for table in SKIPPED_TABLES.iter().filter(|t| !t.starts_with("analytics_")) {
+            sqlx::query(&format!("DELETE FROM {}", table))
+                .execute(&mut *tx)
+                .await?;
+        }
+        for table in BACKUP_TABLES.iter().rev() {
+            sqlx::query(&format!("DELETE FROM {}", table.name))
+                .execute(&mut *tx)
+                .await?;
+        }
+    } else if let Some(rows) = archive.tables.get("users") {
+        // Logins are by email, so merging a second account with the same address would leave
+        // login to pick one of them
+        let mut taken = Vec::new();
+        for row in rows {
+            if let Some(email) = row.get("email").and_then(Value::as_str) {
+                let existing: Option<(i64,)> =
+                    sqlx::query_as("SELECT id FROM users WHERE email = ?")
+                        .bind(email)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                if existing.is_some() {
+                    taken.push(email.to_string());
+                }
+            }
+        }
+        if !taken.is_empty() {
+            return Err(RestoreError::Conflict(format!(
+                "Emails already exist: {}",
+                taken.join(", ")
+            )));
+        }
+    }
+
+    let mut existing_exercises: HashMap<String, i64> = HashMap::new();
+    let exercises: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM exercises ORDER BY id")
+        .fetch_all(&mut *tx)
+        .await?;
+    for (id, name) in exercises {
+        existing_exercises.entry(name.to_lowercase()).or_insert(id);
+    }
+
+    let mut id_maps: HashMap<&str, HashMap<i64, i64>> = HashMap::new();
+    let mut report = Vec::new();
+
+    for table in BACKUP_TABLES {
+        let rows = archive.tables.get(table.name).map(Vec::as_slice).unwrap_or_default();
+        let columns = table_columns(&mut tx, table.name).await?;
+        let mut restored = RestoredTable {
+            table: table.name.to_string(),
+            restored: 0,
+            reused: 0,
+            remapped: 0,
+        };
+
+        for (index, row) in rows.iter().enumerate() {
+            let invalid = |message: String| {
+                RestoreError::Invalid(format!("{} row {}: {}", table.name, index + 1, message))
+            };
+
+            if let Some(column) = row.keys().find(|c| !columns.contains(c.as_str())) {
+                return Err(invalid(format!("unknown column '{}'", column)));
+            }
+            if row.values().any(|v| v.is_array() || v.is_object()) {
+                return Err(invalid("values must be scalars".to_string()));
+            }
+
+            let mut row = row.clone();
+
+            for (column, referenced) in table.references {
+                let Some(old) = row.get(*column).and_then(Value::as_i64) else {
+                    continue;
+                };
+                let new = id_maps
+                    .get(referenced)
+                    .and_then(|map| map.get(&old))
+                    .ok_or_else(|| {
+                        invalid(format!("{} {} is not in the archive", column, old))
+                    })?;
+                row.insert(column.to_string(), Value::from(*new));
+            }
+
+            let old_id = if table.has_id {
+                Some(
+                    row.get("id")
+                        .and_then(Value::as_i64)
+                        .ok_or_else(|| invalid("missing integer id".to_string()))?,
+                )
+            } else {
+                None
+            };
+
+            if table.name == "exercises" && !replace {
+                let name = row.get("name").and_then(Value::as_str).unwrap_or_default();
+                if let Some(existing) = existing_exercises.get(&name.to_lowercase()) {
+                    id_maps.entry(table.name).or_default().insert(old_id.unwrap_or_default(), *existing);
+                    restored.reused += 1;
+                    continue;
+                }
+            }
+
+            if let Some(id) = old_id {
+                let taken: Option<(i64,)> =
+                    sqlx::query_as(&format!("SELECT id FROM {} WHERE id = ?", table.name))
+                        .bind(id)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                if taken.is_some() {
+                    row.remove("id");
+                    restored.remapped += 1;
+                }
+            }
+
+            // Sync ids are unique too; a copy of an existing row gets a fresh one
+            if let Some(client_id) = row.get("client_id").and_then(Value::as_str) {
+                let taken: Option<(i64,)> =
+                    sqlx::query_as(&format!("SELECT id FROM {} WHERE client_id = ?", table.name))
+                        .bind(client_id)
+                        .fetch_optional(&mut *tx)
+                        .await?;
+                if taken.is_some() {
+                    row.remove("client_id");
+                }
+            }
+
+            let names: Vec<&String> = row.keys().collect();
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table.name,
+                names.iter().map(|n| n.as_str()).collect::<Vec<_>>().join(", "),
+                vec!["?"; names.len()].join(", "),
+            );
+
+            let mut query = sqlx::query(&sql);
+            for value in row.values() {
+                query = bind_value(query, value);
+            }
+
+            let result = query.execute(&mut *tx).await.map_err(|e| match e {
+                sqlx::Error::Database(db) => invalid(db.message().to_string()),
+                e => RestoreError::Database(e),
+            })?;
+
+            if let Some(old) = old_id {
+                id_maps
+                    .entry(table.name)
+                    .or_default()
+                    .insert(old, result.last_insert_rowid());
+            }
+            restored.restored += 1;
+        }
+
+        report.push(restored);
+    }
+
+    tx.commit().await?;
+
+    Ok(RestoreReport {
+        schema_version: archive.header.schema_version,
+        replaced: replace,
+        tables: report,
+    })
+}
@@ -1,11 +1,12 @@
 use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
-use sqlx::Row; 
-use crate::auth::{hash_password, verify_password};
+use crate::auth::hash_password;
+use crate::db::rollups::{rebuild_rollups, rollup_table, rollup_upsert, RollupSource, ROLLUP_PERIODS};
 use crate::models::{
-    user::{NewUser, User},
-    workout::{Workout, NewWorkout},
+    user::{NewUser, User, UserPatch},
+    workout::{Workout, NewWorkout, WorkoutPatch},
     exercise::{Exercise, NewExercise},
-    workout_entry::{WorkoutEntry, NewWorkoutEntry, WorkoutEntryDetailed},
+    workout_entry::{WorkoutEntry, NewWorkoutEntry, WorkoutEntryDetailed, WorkoutEntryPatch},
+    analytics::RollupPeriod,
 };
 
 
@@ -61,6 +62,22 @@ pub async fn init_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    // Supersets, giant sets and circuits inside a workout
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS entry_groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            workout_id INTEGER NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('superset', 'giant_set', 'circuit')),
+            rounds INTEGER NOT NULL DEFAULT 1 CHECK (rounds > 0),
+            duration_seconds INTEGER CHECK (duration_seconds > 0),
+            FOREIGN KEY(workout_id) REFERENCES workouts(id) ON DELETE CASCADE
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
     // Create workout_entries table (linking workouts ↔ exercises)
     sqlx::query(
         "
@@ -71,6 +88,9 @@ pub async fn init_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     sets INTEGER NOT NULL CHECK (sets > 0),
     reps INTEGER NOT NULL CHECK (reps > 0),
     weight REAL CHECK (weight >= 0),
+    rpe REAL CHECK (rpe BETWEEN 1 AND 10),
+    position INTEGER NOT NULL DEFAULT 0,
+    group_id INTEGER REFERENCES entry_groups(id) ON DELETE SET NULL,
     FOREIGN KEY(workout_id) REFERENCES workouts(id) ON DELETE CASCADE,
     FOREIGN KEY(exercise_id) REFERENCES exercises(id)
     );
@@ -79,17 +99,565 @@ pub async fn init_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     .execute(pool)
     .await?;
 
+    // Columns added after the tables first shipped
+    add_column_if_missing(pool, "users", "is_admin", "INTEGER NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(pool, "workouts", "version", "INTEGER NOT NULL DEFAULT 1").await?;
+    add_column_if_missing(pool, "workouts", "finished_at", "TEXT").await?;
+    add_column_if_missing(pool, "workout_entries", "version", "INTEGER NOT NULL DEFAULT 1").await?;
+    add_column_if_missing(pool, "workout_entries", "rpe", "REAL CHECK (rpe BETWEEN 1 AND 10)").await?;
+    add_column_if_missing(pool, "workout_entries", "position", "INTEGER NOT NULL DEFAULT 0").await?;
+    add_column_if_missing(pool, "workout_entries", "rest_seconds", "INTEGER CHECK (rest_seconds >= 0)").await?;
+    add_column_if_missing(
+        pool,
+        "workout_entries",
+        "group_id",
+        "INTEGER REFERENCES entry_groups(id) ON DELETE SET NULL",
+    )
+    .await?;
+
+    // History and analytics read a user's workouts by date, then their entries
+    for index in [
+        "CREATE INDEX IF NOT EXISTS idx_workouts_user_date ON workouts(user_id, date)",
+        "CREATE INDEX IF NOT EXISTS idx_workout_entries_workout ON workout_entries(workout_id)",
+        "CREATE INDEX IF NOT EXISTS idx_entry_groups_workout ON entry_groups(workout_id)",
+    ] {
+        sqlx::query(index).execute(pool).await?;
+    }
+
+    // Per-user progression rule for an exercise, stored as JSON
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS progression_rules (
+            user_id INTEGER NOT NULL,
+            exercise_id INTEGER NOT NULL,
+            rule TEXT NOT NULL,
+            PRIMARY KEY(user_id, exercise_id),
+            FOREIGN KEY(user_id) REFERENCES users(id),
+            FOREIGN KEY(exercise_id) REFERENCES exercises(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Workout templates and their planned exercises
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS workout_templates (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            notes TEXT,
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS workout_template_exercises (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            template_id INTEGER NOT NULL,
+            exercise_id INTEGER NOT NULL,
+            position INTEGER NOT NULL,
+            sets INTEGER NOT NULL CHECK (sets > 0),
+            reps INTEGER NOT NULL CHECK (reps > 0),
+            FOREIGN KEY(template_id) REFERENCES workout_templates(id) ON DELETE CASCADE,
+            FOREIGN KEY(exercise_id) REFERENCES exercises(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Weekly hard-set targets per muscle group
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS muscle_volume_targets (
+            user_id INTEGER NOT NULL,
+            muscle_group TEXT NOT NULL COLLATE NOCASE,
+            mev INTEGER NOT NULL CHECK (mev >= 0),
+            mav INTEGER NOT NULL,
+            mrv INTEGER NOT NULL,
+            PRIMARY KEY(user_id, muscle_group),
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Per-user recovery half-lives per muscle group
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS muscle_recovery_settings (
+            user_id INTEGER NOT NULL,
+            muscle_group TEXT NOT NULL COLLATE NOCASE,
+            half_life_hours REAL NOT NULL CHECK (half_life_hours > 0),
+            PRIMARY KEY(user_id, muscle_group),
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Planned sessions, optionally from a template
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS scheduled_sessions (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            date TEXT NOT NULL,
+            template_id INTEGER REFERENCES workout_templates(id) ON DELETE SET NULL,
+            notes TEXT,
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Secret token per user for the iCalendar feed
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS calendar_feeds (
+            user_id INTEGER PRIMARY KEY,
+            token TEXT NOT NULL UNIQUE,
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Per-user default rest after sets of an exercise
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS exercise_rest_defaults (
+            user_id INTEGER NOT NULL,
+            exercise_id INTEGER NOT NULL,
+            seconds INTEGER NOT NULL CHECK (seconds > 0),
+            PRIMARY KEY (user_id, exercise_id),
+            FOREIGN KEY(user_id) REFERENCES users(id),
+            FOREIGN KEY(exercise_id) REFERENCES exercises(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // The running rest timer of a workout, if any. Owned by the server so every device agrees.
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS rest_timers (
+            workout_id INTEGER PRIMARY KEY,
+            entry_id INTEGER NOT NULL,
+            started_at INTEGER NOT NULL,
+            duration_seconds INTEGER NOT NULL CHECK (duration_seconds > 0),
+            FOREIGN KEY(workout_id) REFERENCES workouts(id) ON DELETE CASCADE,
+            FOREIGN KEY(entry_id) REFERENCES workout_entries(id) ON DELETE CASCADE
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Responses to keyed POSTs, replayed on retry. user_id 0 is for requests without a JWT.
+    // Short-lived, so left out of backups.
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS idempotency_keys (
+            user_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            fingerprint TEXT NOT NULL,
+            status INTEGER,
+            content_type TEXT,
+            body BLOB,
+            created_at INTEGER NOT NULL,
+            PRIMARY KEY (user_id, key)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // URLs to notify about events; `events` is a comma separated list.
+    // `all_users` hooks (admins only) hear about every user's events.
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS webhooks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            url TEXT NOT NULL,
+            secret TEXT NOT NULL,
+            events TEXT NOT NULL,
+            all_users INTEGER NOT NULL DEFAULT 0,
+            created_at INTEGER NOT NULL,
+            FOREIGN KEY(user_id) REFERENCES users(id)
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    // Delivery queue and log in one: pending rows are retried until delivered or out of attempts
+    sqlx::query(
+        "
+        CREATE TABLE IF NOT EXISTS webhook_deliveries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            webhook_id INTEGER NOT NULL,
+            event TEXT NOT NULL,
+            payload TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'delivered', 'failed')),
+            attempts INTEGER NOT NULL DEFAULT 0,
+            next_attempt_at INTEGER,
+            response_status INTEGER,
+            last_error TEXT,
+            created_at INTEGER NOT NULL,
+            delivered_at INTEGER,
+            FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+        );
+        ",
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query(
+        "CREATE INDEX IF NOT EXISTS idx_webhook_deliveries_due ON webhook_deliveries(status, next_attempt_at)"
+    )
+    .execute(pool)
+    .await?;
+
+    init_sync(pool).await?;
+    init_rollups(pool).await?;
+
     Ok(())
 }
 
-pub async fn get_all_users(pool: &SqlitePool) -> Result<Vec<User>, sqlx::Error> {
-    let users = sqlx::query_as::<_, User>(
-        "SELECT id, username, email FROM users"
+// Random version 4 UUID, for rows created without a client id
+const NEW_UUID_SQL: &str = "
+    lower(hex(randomblob(4))) || '-' || lower(hex(randomblob(2))) || '-4' ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || substr('89ab', 1 + abs(random()) % 4, 1) ||
+    substr(lower(hex(randomblob(2))), 2) || '-' || lower(hex(randomblob(6)))
+";
+
+// Change tracking for offline sync. Every insert, version bump and delete of a workout or entry
+// takes the next number from the owner's counter; deletes leave a tombstone behind. Triggers keep
+// this right for every write path, including imports and restores.
+async fn init_sync(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for table in ["workouts", "workout_entries"] {
+        add_column_if_missing(pool, table, "client_id", "TEXT").await?;
+        add_column_if_missing(pool, table, "change_seq", "INTEGER NOT NULL DEFAULT 0").await?;
+
+        sqlx::query(&format!(
+            "UPDATE {} SET client_id = {} WHERE client_id IS NULL",
+            table, NEW_UUID_SQL
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    let statements = [
+        "CREATE TABLE IF NOT EXISTS sync_state (
+            user_id INTEGER PRIMARY KEY,
+            seq INTEGER NOT NULL
+        )"
+        .to_string(),
+        "CREATE TABLE IF NOT EXISTS sync_tombstones (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            kind TEXT NOT NULL CHECK (kind IN ('workout', 'entry')),
+            client_id TEXT NOT NULL,
+            change_seq INTEGER NOT NULL
+        )"
+        .to_string(),
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_workouts_client_id ON workouts(client_id)".to_string(),
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_workout_entries_client_id ON workout_entries(client_id)".to_string(),
+        "CREATE INDEX IF NOT EXISTS idx_workouts_user_seq ON workouts(user_id, change_seq)".to_string(),
+        "CREATE INDEX IF NOT EXISTS idx_workout_entries_seq ON workout_entries(workout_id, change_seq)".to_string(),
+        "CREATE INDEX IF NOT EXISTS idx_sync_tombstones_user_seq ON sync_tombstones(user_id, change_seq)".to_string(),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS workouts_sync_insert AFTER INSERT ON workouts
+            BEGIN
+                INSERT OR IGNORE INTO sync_state (user_id, seq) VALUES (NEW.user_id, 0);
+                UPDATE sync_state SET seq = seq + 1 WHERE user_id = NEW.user_id;
+                UPDATE workouts
+                SET change_seq = (SELECT seq FROM sync_state WHERE user_id = NEW.user_id),
+                    client_id = COALESCE(NEW.client_id, {})
+                WHERE id = NEW.id;
+            END",
+            NEW_UUID_SQL
+        ),
+        "CREATE TRIGGER IF NOT EXISTS workouts_sync_update AFTER UPDATE OF version ON workouts
+        WHEN NEW.version <> OLD.version
+        BEGIN
+            INSERT OR IGNORE INTO sync_state (user_id, seq) VALUES (NEW.user_id, 0);
+            UPDATE sync_state SET seq = seq + 1 WHERE user_id = NEW.user_id;
+            UPDATE workouts
+            SET change_seq = (SELECT seq FROM sync_state WHERE user_id = NEW.user_id)
+            WHERE id = NEW.id;
+        END"
+        .to_string(),
+        "CREATE TRIGGER IF NOT EXISTS workouts_sync_delete AFTER DELETE ON workouts
+        BEGIN
+            INSERT OR IGNORE INTO sync_state (user_id, seq) VALUES (OLD.user_id, 0);
+            UPDATE sync_state SET seq = seq + 1 WHERE user_id = OLD.user_id;
+            INSERT INTO sync_tombstones (user_id, kind, client_id, change_seq)
+            SELECT OLD.user_id, 'workout', OLD.client_id, seq FROM sync_state WHERE user_id = OLD.user_id;
+        END"
+        .to_string(),
+        format!(
+            "CREATE TRIGGER IF NOT EXISTS workout_entries_sync_insert AFTER INSERT ON workout_entries
+            BEGIN
+                INSERT OR IGNORE INTO sync_state (user_id, seq)
+                SELECT user_id, 0 FROM workouts WHERE id = NEW.workout_id;
+                UPDATE sync_state SET seq = seq + 1
+                WHERE user_id = (SELECT user_id FROM workouts WHERE id = NEW.workout_id);
+                UPDATE workout_entries
+                SET change_seq = (
+                        SELECT s.seq FROM sync_state s JOIN workouts w ON w.user_id = s.user_id
+                        WHERE w.id = NEW.workout_id
+                    ),
+                    client_id = COALESCE(NEW.client_id, {})
+                WHERE id = NEW.id;
+            END",
+            NEW_UUID_SQL
+        ),
+        "CREATE TRIGGER IF NOT EXISTS workout_entries_sync_update AFTER UPDATE OF version ON workout_entries
+        WHEN NEW.version <> OLD.version
+        BEGIN
+            INSERT OR IGNORE INTO sync_state (user_id, seq)
+            SELECT user_id, 0 FROM workouts WHERE id = NEW.workout_id;
+            UPDATE sync_state SET seq = seq + 1
+            WHERE user_id = (SELECT user_id FROM workouts WHERE id = NEW.workout_id);
+            UPDATE workout_entries
+            SET change_seq = (
+                    SELECT s.seq FROM sync_state s JOIN workouts w ON w.user_id = s.user_id
+                    WHERE w.id = NEW.workout_id
+                )
+            WHERE id = NEW.id;
+        END"
+        .to_string(),
+        // Entries removed together with their workout are covered by the workout's tombstone
+        "CREATE TRIGGER IF NOT EXISTS workout_entries_sync_delete AFTER DELETE ON workout_entries
+        WHEN EXISTS (SELECT 1 FROM workouts WHERE id = OLD.workout_id)
+        BEGIN
+            UPDATE sync_state SET seq = seq + 1
+            WHERE user_id = (SELECT user_id FROM workouts WHERE id = OLD.workout_id);
+            INSERT INTO sync_tombstones (user_id, kind, client_id, change_seq)
+            SELECT s.user_id, 'entry', OLD.client_id, s.seq
+            FROM sync_state s JOIN workouts w ON w.user_id = s.user_id
+            WHERE w.id = OLD.workout_id;
+        END"
+        .to_string(),
+    ];
+
+    for statement in statements {
+        sqlx::query(&statement).execute(pool).await?;
+    }
+
+    Ok(())
+}
+
+// An entry's workout_entries row as a rollup source, before (OLD) or after (NEW) the change
+fn rollup_entry(row: &str) -> String {
+    format!(
+        "(SELECT {row}.workout_id AS workout_id, {row}.exercise_id AS exercise_id, {row}.group_id AS group_id,
+            {row}.sets AS sets, {row}.reps AS reps, {row}.weight AS weight)",
+        row = row
     )
-    .fetch_all(pool)
+}
+
+// Incremental upkeep of the analytics rollups (see db::rollups). Every change to an entry,
+// its workout, its group or its exercise is applied as a delta: the old contribution taken
+// off, the new one added. A cascaded delete runs once its parent row is gone, so workouts
+// and groups settle their entries in BEFORE DELETE triggers and the entries' own triggers
+// find nothing left to undo. Rows that drop to no entries are removed.
+async fn init_rollups(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let (daily, _) = rollup_table(RollupPeriod::Day);
+    let existed: Option<(String,)> =
+        sqlx::query_as("SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(daily)
+            .fetch_optional(pool)
+            .await?;
+
+    for period in ROLLUP_PERIODS {
+        let (table, column) = rollup_table(period);
+        sqlx::query(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (
+                user_id INTEGER NOT NULL,
+                {column} TEXT NOT NULL,
+                exercise_id INTEGER NOT NULL,
+                muscle_group TEXT NOT NULL,
+                entries INTEGER NOT NULL,
+                sets INTEGER NOT NULL,
+                reps INTEGER NOT NULL,
+                volume REAL NOT NULL,
+                PRIMARY KEY (user_id, {column}, exercise_id)
+            )"
+        ))
+        .execute(pool)
+        .await?;
+        sqlx::query(&format!(
+            "CREATE INDEX IF NOT EXISTS idx_{table}_empty ON {table}(entries) WHERE entries = 0"
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    // One entry, before or after the change
+    fn entry(entries: &str) -> RollupSource<'_> {
+        RollupSource { entries, ..RollupSource::TABLE }
+    }
+    // All entries of the workout, owned by its old or new user and date
+    fn workout(old: bool) -> RollupSource<'static> {
+        let (user_id, date, filter) = if old {
+            ("OLD.user_id", "OLD.date", "we.workout_id = OLD.id")
+        } else {
+            ("NEW.user_id", "NEW.date", "we.workout_id = NEW.id")
+        };
+        RollupSource { entries: "workout_entries", user_id, date, joins: "", filter }
+    }
+    // The entries of a group
+    fn members(filter: &str) -> RollupSource<'_> {
+        RollupSource { filter, ..RollupSource::TABLE }
+    }
+
+    let (new_entry, old_entry) = (rollup_entry("NEW"), rollup_entry("OLD"));
+    let rounds = "COALESCE(g.rounds, 1)";
+
+    // (trigger, event, [(source, rounds, sign)])
+    let triggers = [
+        (
+            "rollups_entry_insert",
+            "AFTER INSERT ON workout_entries",
+            vec![(entry(&new_entry), rounds, "")],
+        ),
+        (
+            "rollups_entry_update",
+            "AFTER UPDATE OF workout_id, exercise_id, group_id, sets, reps, weight ON workout_entries",
+            vec![(entry(&old_entry), rounds, "-"), (entry(&new_entry), rounds, "")],
+        ),
+        (
+            "rollups_entry_delete",
+            "AFTER DELETE ON workout_entries",
+            vec![(entry(&old_entry), rounds, "-")],
+        ),
+        (
+            "rollups_workout_update",
+            "AFTER UPDATE OF user_id, date ON workouts
+            WHEN NEW.user_id IS NOT OLD.user_id OR NEW.date IS NOT OLD.date",
+            vec![
+                (workout(true), rounds, "-"),
+                (workout(false), rounds, ""),
+            ],
+        ),
+        (
+            "rollups_workout_delete",
+            "BEFORE DELETE ON workouts",
+            vec![(workout(true), rounds, "-")],
+        ),
+        (
+            "rollups_group_update",
+            "AFTER UPDATE OF rounds ON entry_groups WHEN NEW.rounds <> OLD.rounds",
+            vec![
+                (members("we.group_id = OLD.id"), "OLD.rounds", "-"),
+                (members("we.group_id = NEW.id"), "NEW.rounds", ""),
+            ],
+        ),
+        // Members go back to counting once; unlinking them afterwards then changes nothing
+        (
+            "rollups_group_delete",
+            "BEFORE DELETE ON entry_groups",
+            vec![
+                (members("we.group_id = OLD.id"), "OLD.rounds", "-"),
+                (members("we.group_id = OLD.id"), "1", ""),
+            ],
+        ),
+    ];
+
+    for (name, event, deltas) in triggers {
+        let mut body = Vec::new();
+        for period in ROLLUP_PERIODS {
+            for (source, rounds, sign) in &deltas {
+                body.push(rollup_upsert(period, source, rounds, sign));
+            }
+            body.push(format!("DELETE FROM {} WHERE entries = 0", rollup_table(period).0));
+        }
+        sqlx::query(&format!(
+            "CREATE TRIGGER IF NOT EXISTS {} {} BEGIN {}; END",
+            name,
+            event,
+            body.join(";\n")
+        ))
+        .execute(pool)
+        .await?;
+    }
+
+    let renames: Vec<String> = ROLLUP_PERIODS
+        .iter()
+        .map(|period| {
+            format!(
+                "UPDATE {} SET muscle_group = NEW.muscle_group WHERE exercise_id = NEW.id",
+                rollup_table(*period).0
+            )
+        })
+        .collect();
+    sqlx::query(&format!(
+        "CREATE TRIGGER IF NOT EXISTS rollups_exercise_update AFTER UPDATE OF muscle_group ON exercises
+        BEGIN {}; END",
+        renames.join(";\n")
+    ))
+    .execute(pool)
+    .await?;
+
+    // Entries normally keep their exercise from being deleted, but not where foreign keys
+    // aren't enforced; the exercise's rollups go with it either way
+    let removals: Vec<String> = ROLLUP_PERIODS
+        .iter()
+        .map(|period| format!("DELETE FROM {} WHERE exercise_id = OLD.id", rollup_table(*period).0))
+        .collect();
+    sqlx::query(&format!(
+        "CREATE TRIGGER IF NOT EXISTS rollups_exercise_delete AFTER DELETE ON exercises
+        BEGIN {}; END",
+        removals.join(";\n")
+    ))
+    .execute(pool)
+    .await?;
+
+    // First start with rollups: backfill from the existing history
+    if existed.is_none() {
+        rebuild_rollups(pool).await?;
+    }
+
+    Ok(())
+}
+
+// SQLite has no ADD COLUMN IF NOT EXISTS, so check the schema first
+async fn add_column_if_missing(
+    pool: &SqlitePool,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), sqlx::Error> {
+    let existing: Option<(String,)> = sqlx::query_as(
+        "SELECT name FROM pragma_table_info(?) WHERE name = ?"
+    )
+    .bind(table)
+    .bind(column)
+    .fetch_optional(pool)
     .await?;
 
-    Ok(users)
+    if existing.is_none() {
+        sqlx::query(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))
+            .execute(pool)
+            .await?;
+    }
+
+    Ok(())
 }
 
 pub async fn create_user_db(pool: &SqlitePool, new_user: &NewUser) -> Result<User, sqlx::Error> {
@@ -138,24 +706,70 @@ pub async fn update_user_db(pool: &SqlitePool, id: i64, updated_user: &NewUser)
 }
 
 
-pub async fn delete_user_db(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM users WHERE id = ?")
-        .bind(id)
-        .execute(pool)
-        .await?;
-    Ok(())
+pub enum PatchUserError {
+    Taken,
+    Database(sqlx::Error),
 }
 
-// WORKOUTS CRUD 
-pub async fn get_all_workouts(pool: &SqlitePool) -> Result<Vec<Workout>, sqlx::Error> {
-    let workouts = sqlx::query_as::<_, Workout>(
-        "SELECT id, user_id, date, notes FROM workouts"
+impl From<sqlx::Error> for PatchUserError {
+    fn from(e: sqlx::Error) -> Self {
+        PatchUserError::Database(e)
+    }
+}
+
+// Only the given fields change; a new password is hashed. Returns None when the user doesn't exist.
+// A username or email another account already has is refused: logins are by email.
+pub async fn patch_user_db(
+    pool: &SqlitePool,
+    id: i64,
+    patch: &UserPatch,
+) -> Result<Option<User>, PatchUserError> {
+    let password_hash = patch.password.as_deref().map(hash_password);
+    let mut tx = pool.begin().await?;
+
+    let taken: Option<(i64,)> =
+        sqlx::query_as("SELECT id FROM users WHERE (email = ? OR username = ?) AND id != ?")
+            .bind(&patch.email)
+            .bind(&patch.username)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    if taken.is_some() {
+        return Err(PatchUserError::Taken);
+    }
+
+    sqlx::query(
+        "
+        UPDATE users
+        SET username = COALESCE(?, username),
+            email = COALESCE(?, email),
+            password_hash = COALESCE(?, password_hash)
+        WHERE id = ?
+        "
     )
-    .fetch_all(pool)
+    .bind(&patch.username)
+    .bind(&patch.email)
+    .bind(password_hash)
+    .bind(id)
+    .execute(&mut *tx)
     .await?;
-    Ok(workouts)
+
+    let user = sqlx::query_as::<_, User>("SELECT id, username, email FROM users WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(user)
+}
+
+// Also removes everything the user owns, see delete_account
+pub async fn delete_user_db(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+    crate::db::account::delete_account(pool, id).await?;
+    Ok(())
 }
 
+// WORKOUTS CRUD 
 pub async fn create_workout_db(pool: &SqlitePool, workout: &NewWorkout) -> Result<Workout, sqlx::Error> {
     let result = sqlx::query(
         "INSERT INTO workouts (user_id, date, notes) VALUES (?, ?, ?)"
@@ -169,7 +783,7 @@ pub async fn create_workout_db(pool: &SqlitePool, workout: &NewWorkout) -> Resul
     let last_id = result.last_insert_rowid();
 
     let new_workout = sqlx::query_as::<_, Workout>(
-        "SELECT id, user_id, date, notes FROM workouts WHERE id = ?"
+        "SELECT id, user_id, date, notes, version, finished_at FROM workouts WHERE id = ?"
     )
     .bind(last_id)
     .fetch_one(pool)
@@ -179,24 +793,23 @@ pub async fn create_workout_db(pool: &SqlitePool, workout: &NewWorkout) -> Resul
 }
 
 // Delete a workout by ID
-pub async fn delete_workout_db(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM workouts WHERE id = ?")
+// Returns false when nothing was deleted (missing, or not at `expected_version`)
+pub async fn delete_workout_db(
+    pool: &SqlitePool,
+    id: i64,
+    expected_version: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM workouts WHERE id = ? AND (? IS NULL OR version = ?)")
         .bind(id)
+        .bind(expected_version)
+        .bind(expected_version)
         .execute(pool)
         .await?;
-    Ok(())
+    Ok(result.rows_affected() > 0)
 }
 
 // ---------------- EXERCISES ----------------
 
-pub async fn get_all_exercises(pool: &SqlitePool) -> Result<Vec<Exercise>, sqlx::Error> {
-    let exercises =
-        sqlx::query_as::<_, Exercise>("SELECT id, name, muscle_group, description FROM exercises")
-            .fetch_all(pool)
-            .await?;
-    Ok(exercises)
-}
-
 pub async fn get_exercise_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Exercise>, sqlx::Error> {
     let exercise = sqlx::query_as::<_, Exercise>(
         "SELECT id, name, muscle_group, description FROM exercises WHERE id = ?",
@@ -240,16 +853,24 @@ pub async fn delete_exercise_db(pool: &SqlitePool, id: i64) -> Result<(), sqlx::
     Ok(())
 }
 
+// With `expected_version` the update only happens if nobody changed the entry in between.
+// An entry moved to another workout leaves its group and goes last there.
+// Returns None when no row was updated.
 pub async fn update_workout_entry_db(
     pool: &SqlitePool,
     id: i64,
     updated_entry: &NewWorkoutEntry,
-) -> Result<WorkoutEntry, sqlx::Error> {
-    sqlx::query(
+    expected_version: Option<i64>,
+) -> Result<Option<WorkoutEntry>, sqlx::Error> {
+    let result = sqlx::query(
         "
         UPDATE workout_entries 
-        SET workout_id = ?, exercise_id = ?, sets = ?, reps = ?, weight = ?
-        WHERE id = ?
+        SET workout_id = ?, exercise_id = ?, sets = ?, reps = ?, weight = ?, rpe = ?,
+            group_id = CASE WHEN workout_id = ? THEN group_id ELSE NULL END,
+            position = CASE WHEN workout_id = ? THEN position
+                ELSE (SELECT COALESCE(MAX(position), 0) + 1 FROM workout_entries WHERE workout_id = ?) END,
+            version = version + 1
+        WHERE id = ? AND (? IS NULL OR version = ?)
         ",
     )
     .bind(updated_entry.workout_id)
@@ -257,29 +878,85 @@ pub async fn update_workout_entry_db(
     .bind(updated_entry.sets)
     .bind(updated_entry.reps)
     .bind(updated_entry.weight)
+    .bind(updated_entry.rpe)
+    .bind(updated_entry.workout_id)
+    .bind(updated_entry.workout_id)
+    .bind(updated_entry.workout_id)
     .bind(id)
+    .bind(expected_version)
+    .bind(expected_version)
     .execute(pool)
     .await?;
 
-    // Fetch the updated record
-    let updated = sqlx::query_as::<_, WorkoutEntry>(
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    get_workout_entry(pool, id).await
+}
+
+pub async fn get_workout_entry(pool: &SqlitePool, id: i64) -> Result<Option<WorkoutEntry>, sqlx::Error> {
+    let entry = sqlx::query_as::<_, WorkoutEntry>(
+        "SELECT id, workout_id, exercise_id, sets, reps, weight, rpe, position, group_id, version, rest_seconds FROM workout_entries WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+    Ok(entry)
+}
+
+// Only the given fields change; moving the entry to another workout takes it out of its group
+// and puts it last there
+pub async fn patch_workout_entry_db(
+    pool: &SqlitePool,
+    id: i64,
+    patch: &WorkoutEntryPatch,
+    expected_version: Option<i64>,
+) -> Result<Option<WorkoutEntry>, sqlx::Error> {
+    let result = sqlx::query(
         "
-        SELECT id, workout_id, exercise_id, sets, reps, weight
-        FROM workout_entries
-        WHERE id = ?
+        UPDATE workout_entries
+        SET workout_id = COALESCE(?, workout_id),
+            exercise_id = COALESCE(?, exercise_id),
+            sets = COALESCE(?, sets),
+            reps = COALESCE(?, reps),
+            weight = CASE WHEN ? THEN ? ELSE weight END,
+            rpe = CASE WHEN ? THEN ? ELSE rpe END,
+            group_id = CASE WHEN COALESCE(?, workout_id) = workout_id THEN group_id ELSE NULL END,
+            position = CASE WHEN COALESCE(?, workout_id) = workout_id THEN position
+                ELSE (SELECT COALESCE(MAX(position), 0) + 1 FROM workout_entries WHERE workout_id = ?) END,
+            version = version + 1
+        WHERE id = ? AND (? IS NULL OR version = ?)
         ",
     )
+    .bind(patch.workout_id)
+    .bind(patch.exercise_id)
+    .bind(patch.sets)
+    .bind(patch.reps)
+    .bind(patch.weight.is_some())
+    .bind(patch.weight.flatten())
+    .bind(patch.rpe.is_some())
+    .bind(patch.rpe.flatten())
+    .bind(patch.workout_id)
+    .bind(patch.workout_id)
+    .bind(patch.workout_id)
     .bind(id)
-    .fetch_one(pool)
+    .bind(expected_version)
+    .bind(expected_version)
+    .execute(pool)
     .await?;
 
-    Ok(updated)
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    get_workout_entry(pool, id).await
 }
 
 // Get a single workout by ID
 pub async fn get_workout_by_id(pool: &SqlitePool, id: i64) -> Result<Option<Workout>, sqlx::Error> {
     let workout = sqlx::query_as::<_, Workout>(
-        "SELECT id, user_id, date, notes FROM workouts WHERE id = ?"
+        "SELECT id, user_id, date, notes, version, finished_at FROM workouts WHERE id = ?"
     )
     .bind(id)
     .fetch_optional(pool)
@@ -298,12 +975,20 @@ pub async fn get_user(pool: &SqlitePool, id: i64) -> Result<Option<User>, sqlx::
     Ok(user)
 }
 
+pub async fn is_admin(pool: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let is_admin: Option<bool> = sqlx::query_scalar("SELECT is_admin FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?;
+    Ok(is_admin.unwrap_or(false))
+}
+
 // WORKOUT ENTRIES CRUD
 
 // Get all workout entries
 pub async fn get_all_workout_entries(pool: &SqlitePool) -> Result<Vec<WorkoutEntry>, sqlx::Error> {
     let entries = sqlx::query_as::<_, WorkoutEntry>(
-        "SELECT id, workout_id, exercise_id, sets, reps, weight FROM workout_entries"
+        "SELECT id, workout_id, exercise_id, sets, reps, weight, rpe, position, group_id, version, rest_seconds FROM workout_entries"
     )
     .fetch_all(pool)
     .await?;
@@ -316,21 +1001,24 @@ pub async fn create_workout_entry_db(
     new_entry: &NewWorkoutEntry,
 ) -> Result<WorkoutEntry, sqlx::Error> {
     let result = sqlx::query(
-        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight)
-         VALUES (?, ?, ?, ?, ?)"
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, rpe, position)
+         VALUES (?, ?, ?, ?, ?, ?,
+            (SELECT COALESCE(MAX(position), 0) + 1 FROM workout_entries WHERE workout_id = ?))"
     )
     .bind(new_entry.workout_id)
     .bind(new_entry.exercise_id)
     .bind(new_entry.sets)
     .bind(new_entry.reps)
     .bind(new_entry.weight)
+    .bind(new_entry.rpe)
+    .bind(new_entry.workout_id)
     .execute(pool)
     .await?;
 
     let last_id = result.last_insert_rowid();
 
     let entry = sqlx::query_as::<_, WorkoutEntry>(
-        "SELECT id, workout_id, exercise_id, sets, reps, weight FROM workout_entries WHERE id = ?"
+        "SELECT id, workout_id, exercise_id, sets, reps, weight, rpe, position, group_id, version, rest_seconds FROM workout_entries WHERE id = ?"
     )
     .bind(last_id)
     .fetch_one(pool)
@@ -339,64 +1027,116 @@ pub async fn create_workout_entry_db(
     Ok(entry)
 }
 
+// With `expected_version` the update only happens if nobody changed the workout in between.
+// Returns None when no row was updated.
 pub async fn update_workout_db(
     pool: &SqlitePool,
     id: i64,
     updated_workout: &NewWorkout,
-) -> Result<Workout, sqlx::Error> {
-    sqlx::query(
-        "UPDATE workouts SET user_id = ?, date = ?, notes = ? WHERE id = ?"
+    expected_version: Option<i64>,
+) -> Result<Option<Workout>, sqlx::Error> {
+    let result = sqlx::query(
+        "
+        UPDATE workouts SET user_id = ?, date = ?, notes = ?, version = version + 1
+        WHERE id = ? AND (? IS NULL OR version = ?)
+        "
     )
     .bind(updated_workout.user_id)
     .bind(&updated_workout.date)
     .bind(&updated_workout.notes)
     .bind(id)
+    .bind(expected_version)
+    .bind(expected_version)
     .execute(pool)
     .await?;
 
-    let workout = sqlx::query_as::<_, Workout>(
-        "SELECT id, user_id, date, notes FROM workouts WHERE id = ?"
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    get_workout_by_id(pool, id).await
+}
+
+// Only the given fields change. Returns None when no row was updated.
+pub async fn patch_workout_db(
+    pool: &SqlitePool,
+    id: i64,
+    patch: &WorkoutPatch,
+    expected_version: Option<i64>,
+) -> Result<Option<Workout>, sqlx::Error> {
+    let result = sqlx::query(
+        "
+        UPDATE workouts
+        SET date = COALESCE(?, date),
+            notes = CASE WHEN ? THEN ? ELSE notes END,
+            version = version + 1
+        WHERE id = ? AND (? IS NULL OR version = ?)
+        "
     )
+    .bind(&patch.date)
+    .bind(patch.notes.is_some())
+    .bind(patch.notes.clone().flatten())
     .bind(id)
-    .fetch_one(pool)
+    .bind(expected_version)
+    .bind(expected_version)
+    .execute(pool)
     .await?;
 
-    Ok(workout)
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    get_workout_by_id(pool, id).await
 }
 
-// Delete a workout entry
-pub async fn delete_workout_entry_db(pool: &SqlitePool, id: i64) -> Result<(), sqlx::Error> {
-    sqlx::query("DELETE FROM workout_entries WHERE id = ?")
-        .bind(id)
-        .execute(pool)
-        .await?;
-    Ok(())
+// Marks the workout as done. Returns None when it was finished already.
+pub async fn finish_workout_db(
+    pool: &SqlitePool,
+    id: i64,
+    finished_at: &str,
+) -> Result<Option<Workout>, sqlx::Error> {
+    let result = sqlx::query(
+        "UPDATE workouts SET finished_at = ?, version = version + 1 WHERE id = ? AND finished_at IS NULL"
+    )
+    .bind(finished_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+
+    get_workout_by_id(pool, id).await
 }
 
-// Get all entries with exercise info
-pub async fn get_detailed_workout_entries(
+// Delete a workout entry
+// Returns false when nothing was deleted (missing, or not at `expected_version`)
+pub async fn delete_workout_entry_db(
     pool: &SqlitePool,
-) -> Result<Vec<WorkoutEntryDetailed>, sqlx::Error> {
-    let entries = sqlx::query_as::<_, WorkoutEntryDetailed>(
-        "
-        SELECT 
-            we.id,
-            we.workout_id,
-            we.exercise_id,
-            e.name AS exercise_name,
-            e.muscle_group AS muscle_group,
-            we.sets,
-            we.reps,
-            we.weight
-        FROM workout_entries we
-        JOIN exercises e ON we.exercise_id = e.id
-        ORDER BY we.workout_id;
-        "
+    id: i64,
+    expected_version: Option<i64>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM workout_entries WHERE id = ? AND (? IS NULL OR version = ?)"
     )
-    .fetch_all(pool)
+    .bind(id)
+    .bind(expected_version)
+    .bind(expected_version)
+    .execute(pool)
     .await?;
+    Ok(result.rows_affected() > 0)
+}
 
-    Ok(entries)
+// Cheap change detector for a workout's entries: (id, version) of each, in id order
+pub async fn get_entry_versions(
+    pool: &SqlitePool,
+    workout_id: i64,
+) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT id, version FROM workout_entries WHERE workout_id = ? ORDER BY id")
+        .bind(workout_id)
+        .fetch_all(pool)
+        .await
 }
 
 pub async fn get_workout_entries_by_workout_id(
@@ -413,11 +1153,14 @@ pub async fn get_workout_entries_by_workout_id(
             e.muscle_group AS muscle_group,
             we.sets,
             we.reps,
-            we.weight
+            we.weight,
+            we.rpe,
+            we.position,
+            we.group_id
         FROM workout_entries we
         JOIN exercises e ON we.exercise_id = e.id
         WHERE we.workout_id = ?
-        ORDER BY we.id;
+        ORDER BY we.position, we.id;
         "
     )
     .bind(workout_id)
@@ -427,165 +1170,7 @@ pub async fn get_workout_entries_by_workout_id(
     Ok(entries)
 }
 
-// Calculates per-muscle-group volume and top exercises
-pub async fn get_workout_progress(
-    pool: &SqlitePool,
-    user_id: i64,
-) -> Result<serde_json::Value, sqlx::Error> {
-
-    let workouts = sqlx::query(
-        "SELECT id, date FROM workouts WHERE user_id = ?"
-    )
-    .bind(user_id)
-    .fetch_all(pool)
-    .await?;
-
-    let mut progress = Vec::new();
-
-    for workout in workouts {
-        let entries = sqlx::query(
-            "
-            SELECT e.name, e.muscle_group, we.sets, we.reps, we.weight
-            FROM workout_entries we
-            JOIN exercises e ON we.exercise_id = e.id
-            WHERE we.workout_id = ?
-            "
-        )
-        .bind(workout.get::<i64, _>("id"))
-        .fetch_all(pool)
-        .await?;
-
-        let mut total_sets = 0;
-        let mut total_reps = 0;
-        let mut total_volume = 0.0;
-        let mut muscle_groups = std::collections::HashMap::new();
-        let mut top_exercises = Vec::new();
-
-        for row in entries {
-            let sets: i32 = row.get("sets");
-            let reps: i32 = row.get("reps");
-            let weight: f64 = row.get::<Option<f64>, _>("weight").unwrap_or(0.0);
-            let name: String = row.get("name");
-            let mg: String = row.get("muscle_group");
-
-            let volume = sets as f64 * reps as f64 * weight;
-
-            total_sets += sets;
-            total_reps += reps;
-            total_volume += volume;
-
-            *muscle_groups.entry(mg.clone()).or_insert(0.0) += volume;
-
-            top_exercises.push(serde_json::json!({
-                "name": name,
-                "volume": volume
-            }));
-        }
-
-        top_exercises.sort_by(|a, b| {
-            b["volume"].as_f64()
-                .unwrap()
-                .partial_cmp(&a["volume"].as_f64().unwrap())
-                .unwrap()
-        });
-
-        progress.push(serde_json::json!({
-            "workout_id": workout.get::<i64, _>("id"),
-            "date": workout.get::<String, _>("date"),
-            "total_sets": total_sets,
-            "total_reps": total_reps,
-            "total_volume": total_volume,
-            "muscle_groups": muscle_groups,
-            "top_exercises": top_exercises
-        }));
-    }
-
-    Ok(serde_json::json!(progress))
-}
-// For summary, total volume, most muscle group trained
-pub async fn get_workout_summary(
-    pool: &SqlitePool,
-    workout_id: i64,
-) -> Result<serde_json::Value, sqlx::Error> {
-
-    let entries = sqlx::query(
-        "
-        SELECT e.name, we.sets, we.reps, we.weight
-        FROM workout_entries we
-        JOIN exercises e ON we.exercise_id = e.id
-        WHERE we.workout_id = ?
-        "
-    )
-    .bind(workout_id)
-    .fetch_all(pool)
-    .await?;
-
-    let mut total_sets = 0;
-    let mut total_reps = 0;
-    let mut total_volume = 0.0;
-
-    let exercises: Vec<_> = entries
-        .into_iter()
-        .map(|row| {
-            let sets: i32 = row.get("sets");
-            let reps: i32 = row.get("reps");
-            let weight: f64 = row.get::<Option<f64>, _>("weight").unwrap_or(0.0);
-            let name: String = row.get("name");
-
-            let volume = sets as f64 * reps as f64 * weight;
-
-            total_sets += sets;
-            total_reps += reps;
-            total_volume += volume;
-
-            serde_json::json!({
-                "name": name,
-                "sets": sets,
-                "reps": reps,
-                "weight": weight,
-                "volume": volume
-            })
-        })
-        .collect();
-
-    Ok(serde_json::json!({
-        "workout_id": workout_id,
-        "total_sets": total_sets,
-        "total_reps": total_reps,
-        "total_volume": total_volume,
-        "exercises": exercises
-    }))
+// Training volume of one entry (sets x reps x weight); bodyweight entries count as 0
+pub fn entry_volume(sets: i32, reps: i32, weight: f64) -> f64 {
+    sets as f64 * reps as f64 * weight
 }
-
-// Overall progress
-pub async fn get_user_progress(
-    pool: &SqlitePool,
-    user_id: i64,
-) -> Result<Vec<serde_json::Value>, sqlx::Error> {
-
-    let workouts = sqlx::query(
-        "SELECT id, date FROM workouts WHERE user_id = ? ORDER BY date ASC"
-    )
-    .bind(user_id)
-    .fetch_all(pool)
-    .await?;
-
-    let mut progress = Vec::new();
-
-    for workout in workouts {
-        let w_id = workout.get::<i64, _>("id");
-        let date = workout.get::<String, _>("date");
-
-        let summary = get_workout_summary(pool, w_id).await?;
-
-        progress.push(serde_json::json!({
-            "workout_id": w_id,
-            "date": date,
-            "total_sets": summary["total_sets"],
-            "total_reps": summary["total_reps"],
-            "total_volume": summary["total_volume"]
-        }));
-    }
-
-    Ok(progress)
-}
\ No newline at end of file
@@ -0,0 +1,153 @@
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use crate::models::entry_group::{EntryGroup, NewEntryGroup};
+
+pub async fn get_groups_for_workout(
+    pool: &SqlitePool,
+    workout_id: i64,
+) -> Result<Vec<EntryGroup>, sqlx::Error> {
+    let groups = sqlx::query_as::<_, EntryGroup>(
+        "
+        SELECT id, workout_id, kind, rounds, duration_seconds
+        FROM entry_groups
+        WHERE workout_id = ?
+        ORDER BY id
+        "
+    )
+    .bind(workout_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(groups)
+}
+
+// Entry ids of a workout in their current order
+async fn ordered_entry_ids(
+    tx: &mut Transaction<'_, Sqlite>,
+    workout_id: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let ids: Vec<(i64,)> = sqlx::query_as(
+        "SELECT id FROM workout_entries WHERE workout_id = ? ORDER BY position, id"
+    )
+    .bind(workout_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    Ok(ids.into_iter().map(|(id,)| id).collect())
+}
+
+// Positions are rewritten as 1..n in the given order
+async fn write_positions(
+    tx: &mut Transaction<'_, Sqlite>,
+    workout_id: i64,
+    entry_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    for (index, id) in entry_ids.iter().enumerate() {
+        sqlx::query("UPDATE workout_entries SET version = version + (position <> ?), position = ? WHERE id = ? AND workout_id = ?")
+            .bind(index as i32 + 1)
+            .bind(index as i32 + 1)
+            .bind(id)
+            .bind(workout_id)
+            .execute(&mut **tx)
+            .await?;
+    }
+    Ok(())
+}
+
+// Groups the entries and moves them next to each other, where the first of them was.
+// Callers check that the entries belong to the workout and are not grouped yet.
+pub async fn create_entry_group(
+    pool: &SqlitePool,
+    workout_id: i64,
+    new_group: &NewEntryGroup,
+) -> Result<EntryGroup, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "INSERT INTO entry_groups (workout_id, kind, rounds, duration_seconds) VALUES (?, ?, ?, ?)"
+    )
+    .bind(workout_id)
+    .bind(new_group.kind)
+    .bind(new_group.rounds.unwrap_or(1))
+    .bind(new_group.duration_seconds)
+    .execute(&mut *tx)
+    .await?;
+
+    let group_id = result.last_insert_rowid();
+
+    for entry_id in &new_group.entry_ids {
+        sqlx::query("UPDATE workout_entries SET group_id = ?, version = version + 1 WHERE id = ? AND workout_id = ?")
+            .bind(group_id)
+            .bind(entry_id)
+            .bind(workout_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    let current = ordered_entry_ids(&mut tx, workout_id).await?;
+    let anchor = current
+        .iter()
+        .position(|id| new_group.entry_ids.contains(id))
+        .unwrap_or(current.len());
+
+    let mut order: Vec<i64> = current[..anchor]
+        .iter()
+        .copied()
+        .filter(|id| !new_group.entry_ids.contains(id))
+        .collect();
+    order.extend(&new_group.entry_ids);
+    order.extend(
+        current[anchor..]
+            .iter()
+            .filter(|id| !new_group.entry_ids.contains(id)),
+    );
+
+    write_positions(&mut tx, workout_id, &order).await?;
+
+    let group = sqlx::query_as::<_, EntryGroup>(
+        "SELECT id, workout_id, kind, rounds, duration_seconds FROM entry_groups WHERE id = ?"
+    )
+    .bind(group_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(group)
+}
+
+// Ungroups the entries and removes the group; returns false when it doesn't exist
+pub async fn delete_entry_group(
+    pool: &SqlitePool,
+    workout_id: i64,
+    group_id: i64,
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE workout_entries SET group_id = NULL, version = version + 1 WHERE group_id = ? AND workout_id = ?")
+        .bind(group_id)
+        .bind(workout_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM entry_groups WHERE id = ? AND workout_id = ?")
+        .bind(group_id)
+        .bind(workout_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// `entry_ids` must hold every entry of the workout exactly once
+pub async fn reorder_entries(
+    pool: &SqlitePool,
+    workout_id: i64,
+    entry_ids: &[i64],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    write_positions(&mut tx, workout_id, entry_ids).await?;
+    tx.commit().await?;
+    Ok(())
+}
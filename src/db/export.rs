@@ -0,0 +1,38 @@
+use futures::stream::BoxStream;
+use sqlx::SqlitePool;
+use crate::models::export::ExportRow;
+
+// Rows are streamed from SQLite one by one instead of collected into a Vec
+pub fn stream_workout_history<'a>(
+    pool: &'a SqlitePool,
+    user_id: i64,
+    from: Option<&'a str>,
+    to: Option<&'a str>,
+) -> BoxStream<'a, Result<ExportRow, sqlx::Error>> {
+    sqlx::query_as::<_, ExportRow>(
+        "
+        SELECT
+            w.id AS workout_id,
+            w.date,
+            w.notes,
+            e.name AS exercise,
+            e.muscle_group,
+            we.sets,
+            we.reps,
+            we.weight
+        FROM workouts w
+        LEFT JOIN workout_entries we ON we.workout_id = w.id
+        LEFT JOIN exercises e ON we.exercise_id = e.id
+        WHERE w.user_id = ?
+          AND (? IS NULL OR substr(w.date, 1, 10) >= ?)
+          AND (? IS NULL OR substr(w.date, 1, 10) <= ?)
+        ORDER BY w.date, w.id, we.position, we.id
+        "
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .fetch(pool)
+}
@@ -0,0 +1,82 @@
+use sqlx::SqlitePool;
+use crate::models::idempotency::IdempotencyRecord;
+
+// Forgets keys created before `cutoff` (unix seconds)
+pub async fn purge_expired_keys(pool: &SqlitePool, cutoff: i64) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < ?")
+        .bind(cutoff)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Reserves the key for this request. Returns the existing record when the key was already taken.
+pub async fn claim_key(
+    pool: &SqlitePool,
+    user_id: i64,
+    key: &str,
+    fingerprint: &str,
+    now: i64,
+) -> Result<Option<IdempotencyRecord>, sqlx::Error> {
+    let result = sqlx::query(
+        "
+        INSERT INTO idempotency_keys (user_id, key, fingerprint, created_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT (user_id, key) DO NOTHING
+        "
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(fingerprint)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() > 0 {
+        return Ok(None);
+    }
+
+    let record = sqlx::query_as::<_, IdempotencyRecord>(
+        "SELECT fingerprint, status, content_type, body FROM idempotency_keys WHERE user_id = ? AND key = ?"
+    )
+    .bind(user_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(record)
+}
+
+pub async fn complete_key(
+    pool: &SqlitePool,
+    user_id: i64,
+    key: &str,
+    status: u16,
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE idempotency_keys SET status = ?, content_type = ?, body = ? WHERE user_id = ? AND key = ?"
+    )
+    .bind(status as i64)
+    .bind(content_type)
+    .bind(body)
+    .bind(user_id)
+    .bind(key)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Frees the key after a failed attempt so the client can retry with it
+pub async fn release_key(pool: &SqlitePool, user_id: i64, key: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM idempotency_keys WHERE user_id = ? AND key = ?")
+        .bind(user_id)
+        .bind(key)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use sqlx::SqlitePool;
+use crate::import::{normalize_exercise_name, ParsedImport};
+use crate::models::import::{
+    ExerciseMapping, ImportReport, ImportSource, ImportedSet, MappingStatus,
+};
+use crate::models::workout::CreatedRecords;
+
+// The other apps don't export muscle groups, so new exercises land here until edited
+const IMPORTED_MUSCLE_GROUP: &str = "Other";
+
+struct MergedEntry {
+    exercise: String,
+    weight: Option<f64>,
+    sets: i32,
+    reps: i32,
+    rpe: Option<f64>,
+}
+
+// Consecutive sets of one exercise with equal weight and reps become a single entry
+fn merge_sets(sets: &[&ImportedSet]) -> Vec<MergedEntry> {
+    let mut entries: Vec<MergedEntry> = Vec::new();
+
+    for set in sets {
+        let rpe = set.rpe.filter(|r| (1.0..=10.0).contains(r));
+
+        match entries.last_mut() {
+            Some(entry)
+                if entry.exercise == set.exercise
+                    && entry.weight == set.weight
+                    && entry.reps == set.reps =>
+            {
+                entry.sets += 1;
+                entry.rpe = match (entry.rpe, rpe) {
+                    (Some(a), Some(b)) => Some(a.max(b)),
+                    (a, b) => a.or(b),
+                };
+            }
+            _ => entries.push(MergedEntry {
+                exercise: set.exercise.clone(),
+                weight: set.weight,
+                sets: 1,
+                reps: set.reps,
+                rpe,
+            }),
+        }
+    }
+
+    entries
+}
+
+// Writes everything in one transaction; a dry run rolls it back, only reports and creates nothing
+pub async fn import_history(
+    pool: &SqlitePool,
+    user_id: i64,
+    source: ImportSource,
+    parsed: &ParsedImport,
+    dry_run: bool,
+) -> Result<(ImportReport, CreatedRecords), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let existing: Vec<(i64, String)> = sqlx::query_as("SELECT id, name FROM exercises ORDER BY id")
+        .fetch_all(&mut *tx)
+        .await?;
+
+    // Map every source exercise name to an exercise id
+    let mut exercise_ids: HashMap<String, i64> = HashMap::new();
+    let mut mappings = Vec::new();
+
+    for set in &parsed.sets {
+        if exercise_ids.contains_key(&set.exercise) {
+            continue;
+        }
+
+        let normalized = normalize_exercise_name(&set.exercise);
+        let matched = existing
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(set.exercise.trim()))
+            .or_else(|| existing.iter().find(|(_, name)| normalize_exercise_name(name) == normalized));
+
+        let (id, name, status) = match matched {
+            Some((id, name)) => (*id, name.clone(), MappingStatus::Matched),
+            None => {
+                let description = format!("Imported from {:?}", source);
+                let id = sqlx::query(
+                    "INSERT INTO exercises (name, muscle_group, description) VALUES (?, ?, ?)"
+                )
+                .bind(set.exercise.trim())
+                .bind(IMPORTED_MUSCLE_GROUP)
+                .bind(description)
+                .execute(&mut *tx)
+                .await?
+                .last_insert_rowid();
+
+                (id, set.exercise.trim().to_string(), MappingStatus::Created)
+            }
+        };
+
+        exercise_ids.insert(set.exercise.clone(), id);
+        mappings.push(ExerciseMapping {
+            source_name: set.exercise.clone(),
+            exercise_id: (!dry_run || status == MappingStatus::Matched).then_some(id),
+            exercise_name: name,
+            status,
+        });
+    }
+
+    // Group sets into workouts, keeping file order
+    let mut workout_order: Vec<&str> = Vec::new();
+    let mut workouts: HashMap<&str, Vec<&ImportedSet>> = HashMap::new();
+
+    for set in &parsed.sets {
+        workouts
+            .entry(set.workout_key.as_str())
+            .or_insert_with(|| {
+                workout_order.push(set.workout_key.as_str());
+                Vec::new()
+            })
+            .push(set);
+    }
+
+    let mut created = CreatedRecords::default();
+
+    for key in &workout_order {
+        let sets = &workouts[key];
+        let first = sets[0];
+
+        let notes = match (&first.workout_name, &first.workout_notes) {
+            (Some(name), Some(notes)) => Some(format!("{}\n{}", name, notes)),
+            (name, notes) => name.clone().or_else(|| notes.clone()),
+        };
+
+        let workout_id = sqlx::query("INSERT INTO workouts (user_id, date, notes) VALUES (?, ?, ?)")
+            .bind(user_id)
+            .bind(&first.date)
+            .bind(notes)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+        created.workouts.push(workout_id);
+
+        for (position, entry) in merge_sets(sets).into_iter().enumerate() {
+            let entry_id = sqlx::query(
+                "
+                INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, rpe, position)
+                VALUES (?, ?, ?, ?, ?, ?, ?)
+                "
+            )
+            .bind(workout_id)
+            .bind(exercise_ids[&entry.exercise])
+            .bind(entry.sets)
+            .bind(entry.reps)
+            .bind(entry.weight)
+            .bind(entry.rpe)
+            .bind(position as i32 + 1)
+            .execute(&mut *tx)
+            .await?
+            .last_insert_rowid();
+            created.entries.push(entry_id);
+        }
+    }
+
+    let report = ImportReport {
+        source,
+        dry_run,
+        workouts: workout_order.len(),
+        entries: created.entries.len(),
+        sets: parsed.sets.len(),
+        skipped_rows: parsed.skipped_rows,
+        exercises: mappings,
+    };
+
+    if dry_run {
+        tx.rollback().await?;
+        return Ok((report, CreatedRecords::default()));
+    }
+    tx.commit().await?;
+
+    Ok((report, created))
+}
@@ -0,0 +1,72 @@
+use sqlx::SqlitePool;
+use crate::models::{exercise::Exercise, workout::Workout, workout_entry::WorkoutEntry};
+
+// Batch lookups for the GraphQL data loaders. Ids are bound as one JSON array and
+// expanded with json_each, so a batch is a single query whatever its size.
+
+pub(crate) fn json_ids(ids: &[i64]) -> String {
+    serde_json::to_string(ids).unwrap_or_else(|_| "[]".to_string())
+}
+
+// Newest first; `from` and `to` are inclusive YYYY-MM-DD bounds
+pub async fn get_recent_workouts(
+    pool: &SqlitePool,
+    user_id: i64,
+    from: Option<&str>,
+    to: Option<&str>,
+    limit: i64,
+) -> Result<Vec<Workout>, sqlx::Error> {
+    sqlx::query_as::<_, Workout>(
+        "
+        SELECT id, user_id, date, notes, version, finished_at
+        FROM workouts
+        WHERE user_id = ?
+          AND (? IS NULL OR substr(date, 1, 10) >= ?)
+          AND (? IS NULL OR substr(date, 1, 10) <= ?)
+        ORDER BY date DESC, id DESC
+        LIMIT ?
+        "
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+// Entries of all the given workouts, in workout order
+pub async fn get_entries_for_workouts(
+    pool: &SqlitePool,
+    workout_ids: &[i64],
+) -> Result<Vec<WorkoutEntry>, sqlx::Error> {
+    sqlx::query_as::<_, WorkoutEntry>(
+        "
+        SELECT id, workout_id, exercise_id, sets, reps, weight, rpe, position, group_id, version, rest_seconds
+        FROM workout_entries
+        WHERE workout_id IN (SELECT value FROM json_each(?))
+        ORDER BY workout_id, position, id
+        "
+    )
+    .bind(json_ids(workout_ids))
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_exercises_by_ids(
+    pool: &SqlitePool,
+    ids: &[i64],
+) -> Result<Vec<Exercise>, sqlx::Error> {
+    sqlx::query_as::<_, Exercise>(
+        "
+        SELECT id, name, muscle_group, description
+        FROM exercises
+        WHERE id IN (SELECT value FROM json_each(?))
+        "
+    )
+    .bind(json_ids(ids))
+    .fetch_all(pool)
+    .await
+}
@@ -1 +1,20 @@
 pub mod connection; // points to connection.rs in db 
+pub mod progression;
+pub mod templates;
+pub mod analytics;
+pub mod recovery;
+pub mod entry_groups;
+pub mod export;
+pub mod import;
+pub mod account;
+pub mod report;
+pub mod backup;
+pub mod schedule;
+pub mod pagination;
+pub mod idempotency;
+pub mod sync;
+pub mod rest;
+pub mod webhooks;
+pub mod loaders;
+pub mod summary;
+pub mod rollups;
@@ -0,0 +1,362 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{sqlite::SqliteRow, FromRow, Row, SqlitePool, TypeInfo, ValueRef};
+use crate::db::analytics::parse_date;
+use crate::models::{
+    exercise::Exercise,
+    pagination::{
+        EntryListQuery, ExerciseListQuery, Page, UserListQuery, WorkoutListQuery,
+        DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE,
+    },
+    user::User,
+    workout::Workout,
+    workout_entry::WorkoutEntryDetailed,
+};
+
+// A column a list can be sorted by; expressions must never be NULL for keyset paging to work
+pub struct SortField {
+    pub name: &'static str,
+    pub expr: &'static str,
+}
+
+pub struct Sort {
+    pub field: &'static SortField,
+    pub desc: bool,
+}
+
+// `name` sorts ascending, `-name` descending
+pub fn parse_sort(
+    param: Option<&str>,
+    fields: &'static [SortField],
+    default: &str,
+) -> Result<Sort, PageError> {
+    let param = param.unwrap_or(default);
+    let (name, desc) = match param.strip_prefix('-') {
+        Some(name) => (name, true),
+        None => (param, false),
+    };
+
+    let field = fields.iter().find(|f| f.name == name).ok_or_else(|| {
+        let names: Vec<_> = fields.iter().map(|f| f.name).collect();
+        PageError::Invalid(format!(
+            "Unknown sort '{}', expected one of: {}",
+            name,
+            names.join(", ")
+        ))
+    })?;
+
+    Ok(Sort { field, desc })
+}
+
+pub fn page_size(limit: Option<u32>) -> Result<u32, PageError> {
+    match limit.unwrap_or(DEFAULT_PAGE_SIZE) {
+        0 => Err(PageError::Invalid("limit must be positive".to_string())),
+        limit => Ok(limit.min(MAX_PAGE_SIZE)),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum SqlValue {
+    Int(i64),
+    Text(String),
+}
+
+// Position after the last row of a page. Opaque to clients: JSON, hex encoded.
+#[derive(Serialize, Deserialize, Debug)]
+struct Cursor {
+    sort: String,
+    value: Value,
+    id: i64,
+}
+
+fn encode_cursor(cursor: &Cursor) -> String {
+    let json = serde_json::to_string(cursor).expect("cursor serializes");
+    json.bytes().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn decode_cursor(text: &str) -> Option<Cursor> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    let bytes = (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+// WHERE conditions with their bind values, in order
+#[derive(Default)]
+pub struct Filters {
+    conditions: Vec<String>,
+    binds: Vec<SqlValue>,
+}
+
+impl Filters {
+    pub fn push(&mut self, condition: &str, binds: impl IntoIterator<Item = SqlValue>) {
+        self.conditions.push(condition.to_string());
+        self.binds.extend(binds);
+    }
+
+    // Case-insensitive substring match; % and _ in the text are taken literally
+    pub fn contains(&mut self, expr: &str, text: &str) {
+        let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        self.push(
+            &format!("{} LIKE ? ESCAPE '\\'", expr),
+            [SqlValue::Text(format!("%{}%", escaped))],
+        );
+    }
+}
+
+fn sort_value(row: &SqliteRow) -> Result<Value, sqlx::Error> {
+    let raw = row.try_get_raw("sort_key")?;
+    Ok(match raw.type_info().name() {
+        "INTEGER" => Value::from(row.try_get::<i64, _>("sort_key")?),
+        _ => Value::from(row.try_get::<String, _>("sort_key")?),
+    })
+}
+
+pub enum PageError {
+    Invalid(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for PageError {
+    fn from(e: sqlx::Error) -> Self {
+        PageError::Database(e)
+    }
+}
+
+// What a list selects: `SELECT {columns} FROM {from}`, with `id_expr` as the unique tiebreaker
+pub struct ListSource {
+    pub columns: &'static str,
+    pub from: &'static str,
+    pub id_expr: &'static str,
+}
+
+// Keyset pagination: rows are ordered by the sort expression and then the id, and the
+// cursor resumes strictly after the last row, so inserts don't shift later pages.
+pub async fn fetch_page<T>(
+    pool: &SqlitePool,
+    source: &ListSource,
+    mut filters: Filters,
+    sort: &Sort,
+    cursor: Option<&str>,
+    limit: u32,
+) -> Result<Page<T>, PageError>
+where
+    T: for<'r> FromRow<'r, SqliteRow>,
+{
+    let sort_name = format!("{}{}", if sort.desc { "-" } else { "" }, sort.field.name);
+    let expr = sort.field.expr;
+    let op = if sort.desc { "<" } else { ">" };
+    let dir = if sort.desc { "DESC" } else { "ASC" };
+
+    if let Some(cursor) = cursor {
+        let invalid = || PageError::Invalid("Invalid cursor for this sort".to_string());
+        let cursor = decode_cursor(cursor)
+            .filter(|c| c.sort == sort_name)
+            .ok_or_else(invalid)?;
+        let value = match cursor.value {
+            Value::Number(n) => SqlValue::Int(n.as_i64().ok_or_else(invalid)?),
+            Value::String(s) => SqlValue::Text(s),
+            _ => return Err(invalid()),
+        };
+        filters.push(
+            &format!("({e} {op} ? OR ({e} = ? AND {id} {op} ?))", e = expr, op = op, id = source.id_expr),
+            [value.clone(), value, SqlValue::Int(cursor.id)],
+        );
+    }
+
+    let where_clause = if filters.conditions.is_empty() {
+        String::new()
+    } else {
+        format!("WHERE {}", filters.conditions.join(" AND "))
+    };
+
+    // One extra row tells whether there is a next page
+    let sql = format!(
+        "SELECT {columns}, {expr} AS sort_key, {id} AS page_id FROM {from} {where_clause} \
+         ORDER BY {expr} {dir}, {id} {dir} LIMIT {fetch}",
+        columns = source.columns,
+        expr = expr,
+        id = source.id_expr,
+        from = source.from,
+        where_clause = where_clause,
+        dir = dir,
+        fetch = limit as i64 + 1,
+    );
+
+    let mut query = sqlx::query(&sql);
+    for bind in filters.binds {
+        query = match bind {
+            SqlValue::Int(i) => query.bind(i),
+            SqlValue::Text(s) => query.bind(s),
+        };
+    }
+
+    let mut rows = query.fetch_all(pool).await?;
+    let has_more = rows.len() > limit as usize;
+    rows.truncate(limit as usize);
+
+    let next_cursor = match rows.last() {
+        Some(last) if has_more => Some(encode_cursor(&Cursor {
+            sort: sort_name,
+            value: sort_value(last)?,
+            id: last.try_get("page_id")?,
+        })),
+        _ => None,
+    };
+
+    let items = rows.iter().map(T::from_row).collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Page { items, next_cursor })
+}
+
+// Inclusive date range on a YYYY-MM-DD text column
+fn date_range(
+    filters: &mut Filters,
+    expr: &str,
+    from: Option<&String>,
+    to: Option<&String>,
+) -> Result<(), PageError> {
+    for (date, op) in [(from, ">="), (to, "<=")] {
+        let Some(date) = date else {
+            continue;
+        };
+        let date = parse_date(date).ok_or_else(|| {
+            PageError::Invalid(format!("Invalid date '{}', expected YYYY-MM-DD", date))
+        })?;
+        filters.push(
+            &format!("substr({}, 1, 10) {} ?", expr, op),
+            [SqlValue::Text(date.to_string())],
+        );
+    }
+    Ok(())
+}
+
+const WORKOUT_SORTS: &[SortField] = &[
+    SortField { name: "date", expr: "w.date" },
+    SortField { name: "id", expr: "w.id" },
+];
+
+const WORKOUTS: ListSource = ListSource {
+    columns: "w.id, w.user_id, w.date, w.notes, w.version, w.finished_at",
+    from: "workouts w",
+    id_expr: "w.id",
+};
+
+pub async fn list_workouts_page(
+    pool: &SqlitePool,
+    user_id: i64,
+    query: &WorkoutListQuery,
+) -> Result<Page<Workout>, PageError> {
+    let sort = parse_sort(query.sort.as_deref(), WORKOUT_SORTS, "-date")?;
+    let limit = page_size(query.limit)?;
+
+    let mut filters = Filters::default();
+    filters.push("w.user_id = ?", [SqlValue::Int(user_id)]);
+    date_range(&mut filters, "w.date", query.from.as_ref(), query.to.as_ref())?;
+    if let Some(q) = &query.q {
+        filters.contains("w.notes", q);
+    }
+
+    fetch_page(pool, &WORKOUTS, filters, &sort, query.cursor.as_deref(), limit).await
+}
+
+const ENTRY_SORTS: &[SortField] = &[
+    SortField { name: "id", expr: "we.id" },
+    SortField { name: "date", expr: "w.date" },
+];
+
+const ENTRIES: ListSource = ListSource {
+    columns: "we.id, we.workout_id, we.exercise_id, e.name AS exercise_name, \
+              e.muscle_group AS muscle_group, we.sets, we.reps, we.weight, we.rpe, \
+              we.position, we.group_id",
+    from: "workout_entries we JOIN workouts w ON we.workout_id = w.id \
+           JOIN exercises e ON we.exercise_id = e.id",
+    id_expr: "we.id",
+};
+
+pub async fn list_workout_entries_page(
+    pool: &SqlitePool,
+    user_id: i64,
+    query: &EntryListQuery,
+) -> Result<Page<WorkoutEntryDetailed>, PageError> {
+    let sort = parse_sort(query.sort.as_deref(), ENTRY_SORTS, "id")?;
+    let limit = page_size(query.limit)?;
+
+    let mut filters = Filters::default();
+    filters.push("w.user_id = ?", [SqlValue::Int(user_id)]);
+    date_range(&mut filters, "w.date", query.from.as_ref(), query.to.as_ref())?;
+    if let Some(workout_id) = query.workout_id {
+        filters.push("we.workout_id = ?", [SqlValue::Int(workout_id)]);
+    }
+    if let Some(exercise_id) = query.exercise_id {
+        filters.push("we.exercise_id = ?", [SqlValue::Int(exercise_id)]);
+    }
+    if let Some(muscle_group) = &query.muscle_group {
+        filters.push("e.muscle_group = ? COLLATE NOCASE", [SqlValue::Text(muscle_group.clone())]);
+    }
+    if let Some(q) = &query.q {
+        filters.contains("w.notes", q);
+    }
+
+    fetch_page(pool, &ENTRIES, filters, &sort, query.cursor.as_deref(), limit).await
+}
+
+const EXERCISE_SORTS: &[SortField] = &[
+    SortField { name: "id", expr: "e.id" },
+    SortField { name: "name", expr: "e.name" },
+    SortField { name: "muscle_group", expr: "e.muscle_group" },
+];
+
+const EXERCISES: ListSource = ListSource {
+    columns: "e.id, e.name, e.muscle_group, e.description",
+    from: "exercises e",
+    id_expr: "e.id",
+};
+
+pub async fn list_exercises_page(
+    pool: &SqlitePool,
+    query: &ExerciseListQuery,
+) -> Result<Page<Exercise>, PageError> {
+    let sort = parse_sort(query.sort.as_deref(), EXERCISE_SORTS, "id")?;
+    let limit = page_size(query.limit)?;
+
+    let mut filters = Filters::default();
+    if let Some(muscle_group) = &query.muscle_group {
+        filters.push("e.muscle_group = ? COLLATE NOCASE", [SqlValue::Text(muscle_group.clone())]);
+    }
+    if let Some(q) = &query.q {
+        filters.contains("e.name", q);
+    }
+
+    fetch_page(pool, &EXERCISES, filters, &sort, query.cursor.as_deref(), limit).await
+}
+
+const USER_SORTS: &[SortField] = &[
+    SortField { name: "id", expr: "u.id" },
+    SortField { name: "username", expr: "u.username" },
+];
+
+const USERS: ListSource = ListSource {
+    columns: "u.id, u.username, u.email",
+    from: "users u",
+    id_expr: "u.id",
+};
+
+pub async fn list_users_page(
+    pool: &SqlitePool,
+    query: &UserListQuery,
+) -> Result<Page<User>, PageError> {
+    let sort = parse_sort(query.sort.as_deref(), USER_SORTS, "id")?;
+    let limit = page_size(query.limit)?;
+
+    let mut filters = Filters::default();
+    if let Some(q) = &query.q {
+        filters.contains("u.username", q);
+    }
+
+    fetch_page(pool, &USERS, filters, &sort, query.cursor.as_deref(), limit).await
+}
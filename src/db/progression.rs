@@ -0,0 +1,230 @@
+use sqlx::SqlitePool;
+use crate::models::{
+    exercise::Exercise,
+    progression::{NextSessionRecommendation, ProgressionRule, SessionSnapshot},
+};
+
+// How many past sessions are looked at when recommending the next one
+const HISTORY_SESSIONS: i64 = 5;
+
+// Sets and reps used when there is no history and no template to go by
+const DEFAULT_SETS: i32 = 3;
+const DEFAULT_REPS: i32 = 8;
+
+// Roughly how much load one point of RPE is worth
+const LOAD_PER_RPE: f32 = 0.03;
+
+pub async fn get_progression_rule(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+) -> Result<ProgressionRule, sqlx::Error> {
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT rule FROM progression_rules WHERE user_id = ? AND exercise_id = ?"
+    )
+    .bind(user_id)
+    .bind(exercise_id)
+    .fetch_optional(pool)
+    .await?;
+
+    let rule = row
+        .and_then(|(rule,)| serde_json::from_str(&rule).ok())
+        .unwrap_or_default();
+
+    Ok(rule)
+}
+
+pub async fn set_progression_rule(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+    rule: &ProgressionRule,
+) -> Result<(), sqlx::Error> {
+    let encoded = serde_json::to_string(rule).expect("ProgressionRule always serializes");
+
+    sqlx::query(
+        "
+        INSERT INTO progression_rules (user_id, exercise_id, rule) VALUES (?, ?, ?)
+        ON CONFLICT(user_id, exercise_id) DO UPDATE SET rule = excluded.rule
+        "
+    )
+    .bind(user_id)
+    .bind(exercise_id)
+    .bind(encoded)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Top set (heaviest, then most reps) of each recent workout, newest first
+pub async fn get_exercise_history(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+    limit: i64,
+) -> Result<Vec<SessionSnapshot>, sqlx::Error> {
+    let history = sqlx::query_as::<_, SessionSnapshot>(
+        "
+        SELECT workout_id, date, sets, reps, weight, rpe
+        FROM (
+            SELECT
+                we.workout_id,
+                w.date,
+                we.sets,
+                we.reps,
+                we.weight,
+                we.rpe,
+                ROW_NUMBER() OVER (
+                    PARTITION BY we.workout_id
+                    ORDER BY COALESCE(we.weight, 0) DESC, we.reps DESC, we.id DESC
+                ) AS rank
+            FROM workout_entries we
+            JOIN workouts w ON we.workout_id = w.id
+            WHERE w.user_id = ? AND we.exercise_id = ?
+        )
+        WHERE rank = 1
+        ORDER BY date DESC, workout_id DESC
+        LIMIT ?
+        "
+    )
+    .bind(user_id)
+    .bind(exercise_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(history)
+}
+
+// Builds the recommendation for one exercise; `planned` is (sets, reps) from a template
+pub async fn get_next_session(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise: &Exercise,
+    planned: Option<(i32, i32)>,
+) -> Result<NextSessionRecommendation, sqlx::Error> {
+    let rule = get_progression_rule(pool, user_id, exercise.id).await?;
+    let history = get_exercise_history(pool, user_id, exercise.id, HISTORY_SESSIONS).await?;
+
+    let (sets, reps, weight, reason) = recommend_next(&rule, &history, planned);
+
+    Ok(NextSessionRecommendation {
+        exercise_id: exercise.id,
+        exercise_name: exercise.name.clone(),
+        rule,
+        sets,
+        reps,
+        weight,
+        reason,
+        last_session: history.into_iter().next(),
+    })
+}
+
+// Pure progression logic. `history` is newest first.
+pub fn recommend_next(
+    rule: &ProgressionRule,
+    history: &[SessionSnapshot],
+    planned: Option<(i32, i32)>,
+) -> (i32, i32, Option<f32>, String) {
+    let (planned_sets, planned_reps) = planned.unwrap_or((DEFAULT_SETS, DEFAULT_REPS));
+
+    let Some(last) = history.first() else {
+        let reps = match *rule {
+            ProgressionRule::DoubleProgression { rep_min, .. } => rep_min,
+            _ => planned_reps,
+        };
+        return (
+            planned_sets,
+            reps,
+            None,
+            "No history for this exercise yet; pick a weight you can move with good form".into(),
+        );
+    };
+
+    let sets = planned.map(|(s, _)| s).unwrap_or(last.sets);
+
+    match *rule {
+        ProgressionRule::DoubleProgression { rep_min, rep_max, weight_increment } => {
+            match last.weight {
+                Some(weight) if last.reps >= rep_max => (
+                    sets,
+                    rep_min,
+                    Some(weight + weight_increment),
+                    format!(
+                        "Reached {} reps at {}; add {} and restart at {} reps",
+                        last.reps, weight, weight_increment, rep_min
+                    ),
+                ),
+                Some(weight) => {
+                    let reps = (last.reps + 1).clamp(rep_min, rep_max);
+                    (
+                        sets,
+                        reps,
+                        Some(weight),
+                        format!("Keep {} and aim for {} reps", weight, reps),
+                    )
+                }
+                None => (
+                    sets,
+                    last.reps + 1,
+                    None,
+                    "Bodyweight exercise; add a rep".into(),
+                ),
+            }
+        }
+        ProgressionRule::FixedIncrement { weight_increment } => {
+            let Some(weight) = last.weight else {
+                return (sets, last.reps + 1, None, "Bodyweight exercise; add a rep".into());
+            };
+
+            // Missing reps at the same weight as the session before counts as a stall
+            let stalled = history.get(1).is_some_and(|previous| {
+                previous.weight == last.weight && last.reps < previous.reps
+            });
+
+            if stalled {
+                (
+                    sets,
+                    last.reps,
+                    Some(weight),
+                    format!("Reps dropped at {}; repeat the weight before adding more", weight),
+                )
+            } else {
+                (
+                    sets,
+                    last.reps,
+                    Some(weight + weight_increment),
+                    format!("Add {} to last session's {}", weight_increment, weight),
+                )
+            }
+        }
+        ProgressionRule::RpeBased { target_rpe, weight_increment } => {
+            let Some(weight) = last.weight else {
+                return (sets, last.reps, None, "Bodyweight exercise; adjust reps to the target RPE".into());
+            };
+            let Some(rpe) = last.rpe else {
+                return (
+                    sets,
+                    last.reps,
+                    Some(weight),
+                    "No RPE logged last session; repeat the weight and log RPE".into(),
+                );
+            };
+
+            let adjusted = weight * (1.0 + LOAD_PER_RPE * (target_rpe - rpe));
+            let next = round_to(adjusted, weight_increment).max(0.0);
+
+            (
+                sets,
+                last.reps,
+                Some(next),
+                format!("Last top set was RPE {}; adjust load towards RPE {}", rpe, target_rpe),
+            )
+        }
+    }
+}
+
+fn round_to(value: f32, step: f32) -> f32 {
+    (value / step).round() * step
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use sqlx::{Row, SqlitePool};
+use crate::db::analytics::{parse_date, HARD_SET_MIN_RPE};
+use crate::db::templates::{get_template_detailed, get_templates_for_user};
+use crate::models::recovery::{
+    MuscleRecovery, MuscleSuggestion, NewRecoverySetting, RecoveryReport, RecoverySetting,
+    TemplateSuggestion, TrainingSuggestions,
+};
+
+pub const DEFAULT_HALF_LIFE_HOURS: f64 = 36.0;
+
+// Remaining hard sets at which a muscle counts as fully fatigued (0% recovered)
+const FULL_FATIGUE_SETS: f64 = 10.0;
+
+// Sessions older than this no longer affect recovery
+const DECAY_WINDOW_DAYS: i64 = 14;
+
+// History used to learn how often each muscle is usually trained
+const FREQUENCY_WINDOW_DAYS: i64 = 90;
+const DEFAULT_INTERVAL_DAYS: f64 = 7.0;
+
+// Below this a muscle is reported as still recovering
+const RECOVERED_PERCENT: f64 = 70.0;
+
+// Workouts only have a date, so sessions are assumed to happen at midday
+fn session_time(date: NaiveDate) -> NaiveDateTime {
+    date.and_time(NaiveTime::from_hms_opt(12, 0, 0).unwrap())
+}
+
+// Hard sets still "felt" after `hours`, halving every `half_life_hours`
+pub fn remaining_fatigue(sets: f64, hours: f64, half_life_hours: f64) -> f64 {
+    sets * 0.5_f64.powf(hours.max(0.0) / half_life_hours)
+}
+
+pub fn recovery_percent(fatigue: f64) -> f64 {
+    let percent = 100.0 * (1.0 - (fatigue / FULL_FATIGUE_SETS).min(1.0));
+    (percent * 10.0).round() / 10.0
+}
+
+pub async fn get_recovery_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<RecoverySetting>, sqlx::Error> {
+    let settings = sqlx::query_as::<_, RecoverySetting>(
+        "
+        SELECT muscle_group, half_life_hours
+        FROM muscle_recovery_settings
+        WHERE user_id = ?
+        ORDER BY muscle_group
+        "
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(settings)
+}
+
+pub async fn set_recovery_setting(
+    pool: &SqlitePool,
+    user_id: i64,
+    muscle_group: &str,
+    setting: &NewRecoverySetting,
+) -> Result<RecoverySetting, sqlx::Error> {
+    sqlx::query(
+        "
+        INSERT INTO muscle_recovery_settings (user_id, muscle_group, half_life_hours)
+        VALUES (?, ?, ?)
+        ON CONFLICT(user_id, muscle_group) DO UPDATE SET half_life_hours = excluded.half_life_hours
+        "
+    )
+    .bind(user_id)
+    .bind(muscle_group)
+    .bind(setting.half_life_hours)
+    .execute(pool)
+    .await?;
+
+    Ok(RecoverySetting {
+        muscle_group: muscle_group.to_string(),
+        half_life_hours: setting.half_life_hours,
+    })
+}
+
+// Everything the recovery model knows about one muscle group
+struct MuscleHistory {
+    muscle_group: String,
+    last_trained: Option<NaiveDate>,
+    sessions: Vec<(NaiveDate, i64)>, // (date, hard sets) inside the frequency window
+}
+
+impl MuscleHistory {
+    fn typical_interval_days(&self) -> f64 {
+        let mut dates: Vec<_> = self.sessions.iter().map(|(date, _)| *date).collect();
+        dates.sort_unstable();
+        dates.dedup();
+
+        if dates.len() < 2 {
+            return DEFAULT_INTERVAL_DAYS;
+        }
+
+        let span = (dates[dates.len() - 1] - dates[0]).num_days() as f64;
+        span / (dates.len() - 1) as f64
+    }
+}
+
+// Muscle groups the user has ever trained, keyed case-insensitively
+async fn get_muscle_histories(
+    pool: &SqlitePool,
+    user_id: i64,
+    today: NaiveDate,
+) -> Result<HashMap<String, MuscleHistory>, sqlx::Error> {
+    let mut histories: HashMap<String, MuscleHistory> = HashMap::new();
+
+    let rows = sqlx::query(
+        "
+        SELECT e.muscle_group, MAX(w.date) AS last_trained
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN exercises e ON we.exercise_id = e.id
+        WHERE w.user_id = ? AND w.date <= ?
+        GROUP BY e.muscle_group
+        "
+    )
+    .bind(user_id)
+    .bind(today.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let muscle_group: String = row.get("muscle_group");
+        let last_trained = parse_date(&row.get::<String, _>("last_trained"));
+
+        let history = histories
+            .entry(muscle_group.to_lowercase())
+            .or_insert_with(|| MuscleHistory {
+                muscle_group,
+                last_trained: None,
+                sessions: Vec::new(),
+            });
+        history.last_trained = history.last_trained.max(last_trained);
+    }
+
+    let rows = sqlx::query(
+        "
+        SELECT w.date, e.muscle_group, we.sets, we.rpe
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN exercises e ON we.exercise_id = e.id
+        WHERE w.user_id = ? AND w.date >= ? AND w.date <= ?
+        "
+    )
+    .bind(user_id)
+    .bind((today - Duration::days(FREQUENCY_WINDOW_DAYS)).to_string())
+    .bind(today.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    for row in rows {
+        let Some(date) = parse_date(&row.get::<String, _>("date")) else {
+            continue;
+        };
+        if row.get::<Option<f64>, _>("rpe").is_some_and(|rpe| rpe < HARD_SET_MIN_RPE) {
+            continue;
+        }
+
+        let muscle_group: String = row.get("muscle_group");
+        if let Some(history) = histories.get_mut(&muscle_group.to_lowercase()) {
+            history.sessions.push((date, row.get::<i32, _>("sets") as i64));
+        }
+    }
+
+    Ok(histories)
+}
+
+fn muscle_recovery(
+    history: &MuscleHistory,
+    half_life_hours: f64,
+    now: NaiveDateTime,
+) -> MuscleRecovery {
+    let window_start = now.date() - Duration::days(DECAY_WINDOW_DAYS);
+
+    let mut fatigue = 0.0;
+    let mut recent_sets = 0;
+
+    for &(date, sets) in &history.sessions {
+        if date < window_start {
+            continue;
+        }
+        let hours = (now - session_time(date)).num_minutes() as f64 / 60.0;
+        fatigue += remaining_fatigue(sets as f64, hours, half_life_hours);
+        recent_sets += sets;
+    }
+
+    MuscleRecovery {
+        muscle_group: history.muscle_group.clone(),
+        recovery_percent: recovery_percent(fatigue),
+        fatigue: (fatigue * 100.0).round() / 100.0,
+        half_life_hours,
+        last_trained: history.last_trained.map(|d| d.to_string()),
+        days_since_last: history.last_trained.map(|d| (now.date() - d).num_days()),
+        recent_sets,
+    }
+}
+
+async fn compute_recovery(
+    pool: &SqlitePool,
+    user_id: i64,
+    now: NaiveDateTime,
+) -> Result<Vec<(MuscleRecovery, f64)>, sqlx::Error> {
+    let histories = get_muscle_histories(pool, user_id, now.date()).await?;
+
+    let half_lives: HashMap<String, f64> = get_recovery_settings(pool, user_id)
+        .await?
+        .into_iter()
+        .map(|s| (s.muscle_group.to_lowercase(), s.half_life_hours))
+        .collect();
+
+    let mut muscles: Vec<_> = histories
+        .iter()
+        .map(|(key, history)| {
+            let half_life = half_lives.get(key).copied().unwrap_or(DEFAULT_HALF_LIFE_HOURS);
+            (muscle_recovery(history, half_life, now), history.typical_interval_days())
+        })
+        .collect();
+
+    muscles.sort_by(|a, b| a.0.muscle_group.cmp(&b.0.muscle_group));
+    Ok(muscles)
+}
+
+pub async fn get_recovery_report(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<RecoveryReport, sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    let muscles = compute_recovery(pool, user_id, now)
+        .await?
+        .into_iter()
+        .map(|(recovery, _)| recovery)
+        .collect();
+
+    Ok(RecoveryReport {
+        as_of: now.date().to_string(),
+        muscles,
+    })
+}
+
+// Ranks muscles (and the user's templates) by how recovered and how overdue they are
+pub async fn get_training_suggestions(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<TrainingSuggestions, sqlx::Error> {
+    let now = Utc::now().naive_utc();
+
+    let mut muscle_groups: Vec<MuscleSuggestion> = compute_recovery(pool, user_id, now)
+        .await?
+        .into_iter()
+        .map(|(recovery, interval)| {
+            // How overdue the muscle is relative to the usual rhythm, capped at twice as late
+            let dueness = recovery
+                .days_since_last
+                .map(|days| (days as f64 / interval.max(1.0)).min(2.0))
+                .unwrap_or(1.0);
+            let score = recovery.recovery_percent / 100.0 * dueness;
+
+            let reason = if recovery.recovery_percent < RECOVERED_PERCENT {
+                format!("Still recovering ({}%)", recovery.recovery_percent)
+            } else if dueness >= 1.0 {
+                format!("Recovered and due; usually trained every {:.1} days", interval)
+            } else {
+                format!(
+                    "Recovered, but trained {} day(s) ago",
+                    recovery.days_since_last.unwrap_or(0)
+                )
+            };
+
+            MuscleSuggestion {
+                muscle_group: recovery.muscle_group,
+                recovery_percent: recovery.recovery_percent,
+                days_since_last: recovery.days_since_last,
+                typical_interval_days: (interval * 10.0).round() / 10.0,
+                score: (score * 1000.0).round() / 1000.0,
+                reason,
+            }
+        })
+        .collect();
+
+    muscle_groups.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let by_muscle: HashMap<String, &MuscleSuggestion> = muscle_groups
+        .iter()
+        .map(|m| (m.muscle_group.to_lowercase(), m))
+        .collect();
+
+    let mut templates = Vec::new();
+
+    for template in get_templates_for_user(pool, user_id).await? {
+        let Some(detailed) = get_template_detailed(pool, template.id).await? else {
+            continue;
+        };
+
+        let mut groups: Vec<String> = detailed
+            .exercises
+            .iter()
+            .map(|e| e.muscle_group.clone())
+            .collect();
+        groups.sort_unstable_by_key(|g| g.to_lowercase());
+        groups.dedup_by_key(|g| g.to_lowercase());
+
+        if groups.is_empty() {
+            continue;
+        }
+
+        // Muscles that were never trained are fully recovered and due
+        let (recovery_sum, score_sum) = groups.iter().fold((0.0, 0.0), |(r, s), group| {
+            match by_muscle.get(&group.to_lowercase()) {
+                Some(m) => (r + m.recovery_percent, s + m.score),
+                None => (r + 100.0, s + 1.0),
+            }
+        });
+        let count = groups.len() as f64;
+
+        templates.push(TemplateSuggestion {
+            template_id: template.id,
+            name: template.name,
+            muscle_groups: groups,
+            recovery_percent: (recovery_sum / count * 10.0).round() / 10.0,
+            score: (score_sum / count * 1000.0).round() / 1000.0,
+        });
+    }
+
+    templates.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    Ok(TrainingSuggestions {
+        as_of: now.date().to_string(),
+        muscle_groups,
+        templates,
+    })
+}
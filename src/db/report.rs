@@ -0,0 +1,157 @@
+use std::collections::{BTreeMap, HashMap};
+use chrono::NaiveDate;
+use sqlx::{Row, SqlitePool};
+use crate::db::analytics::{estimated_one_rep_max, parse_date, week_start};
+use crate::db::summary::{get_user_progress, get_workout_progress};
+use crate::models::report::{MuscleShare, PersonalRecord, ProgressReport, WeekVolume};
+
+fn in_range(date: &str, from: NaiveDate, to: NaiveDate) -> bool {
+    parse_date(date).is_some_and(|d| d >= from && d <= to)
+}
+
+pub async fn build_progress_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    username: String,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<ProgressReport, sqlx::Error> {
+    let mut workouts = 0;
+    let mut total_sets = 0;
+    let mut total_reps = 0;
+    let mut total_volume = 0.0;
+    let mut weeks: BTreeMap<NaiveDate, (i64, f64)> = BTreeMap::new();
+
+    // Totals per workout come from the same summaries as /api/users/:id/progress
+    for workout in get_user_progress(pool, user_id).await? {
+        if !in_range(&workout.date, from, to) {
+            continue;
+        }
+
+        let volume = workout.total_volume;
+        workouts += 1;
+        total_sets += workout.total_sets;
+        total_reps += workout.total_reps;
+        total_volume += volume;
+
+        if let Some(date) = parse_date(&workout.date) {
+            let week = weeks.entry(week_start(date)).or_insert((0, 0.0));
+            week.0 += 1;
+            week.1 += volume;
+        }
+    }
+
+    // Empty weeks are kept so the chart shows gaps in training
+    let mut weekly_volume = Vec::new();
+    let mut week = week_start(from);
+    while week <= to {
+        let (workouts, volume) = weeks.get(&week).copied().unwrap_or((0, 0.0));
+        weekly_volume.push(WeekVolume {
+            week_start: week.to_string(),
+            workouts,
+            volume,
+        });
+        week += chrono::Duration::weeks(1);
+    }
+
+    let mut muscles: HashMap<String, f64> = HashMap::new();
+    for workout in get_workout_progress(pool, user_id).await? {
+        if !in_range(&workout.totals.date, from, to) {
+            continue;
+        }
+        for (muscle, volume) in workout.muscle_groups {
+            *muscles.entry(muscle).or_insert(0.0) += volume;
+        }
+    }
+
+    let muscle_total: f64 = muscles.values().sum();
+    let mut muscle_distribution: Vec<MuscleShare> = muscles
+        .into_iter()
+        .map(|(muscle_group, volume)| MuscleShare {
+            percent: if muscle_total > 0.0 { volume / muscle_total * 100.0 } else { 0.0 },
+            muscle_group,
+            volume,
+        })
+        .collect();
+    muscle_distribution.sort_by(|a, b| {
+        b.volume.total_cmp(&a.volume).then_with(|| a.muscle_group.cmp(&b.muscle_group))
+    });
+
+    Ok(ProgressReport {
+        username,
+        from: from.to_string(),
+        to: to.to_string(),
+        workouts,
+        total_sets,
+        total_reps,
+        total_volume,
+        weekly_volume,
+        muscle_distribution,
+        personal_records: get_personal_records(pool, user_id, from, to).await?,
+    })
+}
+
+async fn get_personal_records(
+    pool: &SqlitePool,
+    user_id: i64,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<PersonalRecord>, sqlx::Error> {
+    let rows = sqlx::query(
+        "
+        SELECT e.name, w.date, we.reps, we.weight
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN exercises e ON we.exercise_id = e.id
+        WHERE w.user_id = ? AND we.weight > 0 AND we.reps > 0
+          AND substr(w.date, 1, 10) <= ?
+        ORDER BY w.date, we.id
+        "
+    )
+    .bind(user_id)
+    .bind(to.to_string())
+    .fetch_all(pool)
+    .await?;
+
+    let mut before: HashMap<String, f64> = HashMap::new();
+    let mut best: HashMap<String, PersonalRecord> = HashMap::new();
+
+    for row in rows {
+        let name: String = row.get("name");
+        let date: String = row.get("date");
+        let reps: i32 = row.get("reps");
+        let weight: f64 = row.get("weight");
+        let e1rm = estimated_one_rep_max(weight, reps);
+
+        if !in_range(&date, from, to) {
+            let previous = before.entry(name).or_insert(0.0);
+            *previous = previous.max(e1rm);
+            continue;
+        }
+
+        if best.get(&name).is_none_or(|record| e1rm > record.estimated_one_rep_max) {
+            best.insert(name.clone(), PersonalRecord {
+                exercise: name,
+                date: date.get(..10).unwrap_or(&date).to_string(),
+                weight,
+                reps,
+                estimated_one_rep_max: e1rm,
+                previous_best: None,
+            });
+        }
+    }
+
+    let mut records: Vec<PersonalRecord> = best
+        .into_values()
+        .filter_map(|mut record| {
+            record.previous_best = before.get(&record.exercise).copied();
+            match record.previous_best {
+                Some(previous) if previous >= record.estimated_one_rep_max => None,
+                _ => Some(record),
+            }
+        })
+        .collect();
+    records.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.exercise.cmp(&b.exercise)));
+
+    Ok(records)
+}
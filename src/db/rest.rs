@@ -0,0 +1,152 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use crate::models::rest::{FinishedRest, RestDefault, RestTimerRow, DEFAULT_REST_SECONDS};
+
+pub async fn get_rest_defaults(pool: &SqlitePool, user_id: i64) -> Result<Vec<RestDefault>, sqlx::Error> {
+    sqlx::query_as::<_, RestDefault>(
+        "SELECT exercise_id, seconds FROM exercise_rest_defaults WHERE user_id = ? ORDER BY exercise_id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+// The user's default for the exercise, or DEFAULT_REST_SECONDS
+pub async fn get_rest_default(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+) -> Result<i64, sqlx::Error> {
+    let seconds: Option<i64> = sqlx::query_scalar(
+        "SELECT seconds FROM exercise_rest_defaults WHERE user_id = ? AND exercise_id = ?"
+    )
+    .bind(user_id)
+    .bind(exercise_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(seconds.unwrap_or(DEFAULT_REST_SECONDS))
+}
+
+pub async fn set_rest_default(
+    pool: &SqlitePool,
+    user_id: i64,
+    exercise_id: i64,
+    seconds: i64,
+) -> Result<RestDefault, sqlx::Error> {
+    sqlx::query(
+        "
+        INSERT INTO exercise_rest_defaults (user_id, exercise_id, seconds) VALUES (?, ?, ?)
+        ON CONFLICT (user_id, exercise_id) DO UPDATE SET seconds = excluded.seconds
+        "
+    )
+    .bind(user_id)
+    .bind(exercise_id)
+    .bind(seconds)
+    .execute(pool)
+    .await?;
+
+    Ok(RestDefault { exercise_id, seconds })
+}
+
+pub async fn get_rest_timer(pool: &SqlitePool, workout_id: i64) -> Result<Option<RestTimerRow>, sqlx::Error> {
+    sqlx::query_as::<_, RestTimerRow>(
+        "SELECT workout_id, entry_id, started_at, duration_seconds FROM rest_timers WHERE workout_id = ?"
+    )
+    .bind(workout_id)
+    .fetch_optional(pool)
+    .await
+}
+
+// Timers of every workout that haven't run out at `now`
+pub async fn get_running_rest_timers(pool: &SqlitePool, now: i64) -> Result<Vec<RestTimerRow>, sqlx::Error> {
+    sqlx::query_as::<_, RestTimerRow>(
+        "
+        SELECT workout_id, entry_id, started_at, duration_seconds FROM rest_timers
+        WHERE started_at + duration_seconds > ?
+        "
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+// Stops the workout's timer and records the rest taken so far on its entry
+async fn finish_timer(
+    conn: &mut SqliteConnection,
+    workout_id: i64,
+    now: i64,
+) -> Result<Option<FinishedRest>, sqlx::Error> {
+    let timer = sqlx::query_as::<_, RestTimerRow>(
+        "SELECT workout_id, entry_id, started_at, duration_seconds FROM rest_timers WHERE workout_id = ?"
+    )
+    .bind(workout_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let Some(timer) = timer else {
+        return Ok(None);
+    };
+
+    let rest_seconds = (now - timer.started_at).max(0);
+
+    sqlx::query("DELETE FROM rest_timers WHERE workout_id = ?")
+        .bind(workout_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query("UPDATE workout_entries SET rest_seconds = ?, version = version + 1 WHERE id = ?")
+        .bind(rest_seconds)
+        .bind(timer.entry_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Some(FinishedRest {
+        entry_id: timer.entry_id,
+        rest_seconds,
+    }))
+}
+
+pub async fn stop_rest_timer_db(
+    pool: &SqlitePool,
+    workout_id: i64,
+    now: i64,
+) -> Result<Option<FinishedRest>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let finished = finish_timer(&mut tx, workout_id, now).await?;
+    tx.commit().await?;
+
+    Ok(finished)
+}
+
+// Replaces the running timer, if any; the rest before it is recorded as finished
+pub async fn start_rest_timer_db(
+    pool: &SqlitePool,
+    workout_id: i64,
+    entry_id: i64,
+    seconds: i64,
+    now: i64,
+) -> Result<(RestTimerRow, Option<FinishedRest>), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let finished = finish_timer(&mut tx, workout_id, now).await?;
+
+    let timer = RestTimerRow {
+        workout_id,
+        entry_id,
+        started_at: now,
+        duration_seconds: seconds,
+    };
+
+    sqlx::query(
+        "INSERT INTO rest_timers (workout_id, entry_id, started_at, duration_seconds) VALUES (?, ?, ?, ?)"
+    )
+    .bind(timer.workout_id)
+    .bind(timer.entry_id)
+    .bind(timer.started_at)
+    .bind(timer.duration_seconds)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok((timer, finished))
+}
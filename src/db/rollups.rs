@@ -0,0 +1,241 @@
+use sqlx::{FromRow, Sqlite, SqlitePool, Transaction};
+use crate::models::analytics::{RollupGroup, RollupPeriod, RollupRow};
+
+// Daily and weekly totals per user and exercise, kept current by the triggers installed in
+// init_db (see db::connection). Each row carries the exercise's muscle group, so per-muscle
+// reads don't need the exercises table. Everything here can be recomputed from the entries:
+// rebuild_rollups does that, check_rollups compares the two.
+
+pub const ROLLUP_PERIODS: [RollupPeriod; 2] = [RollupPeriod::Day, RollupPeriod::Week];
+
+// (table, period column)
+pub fn rollup_table(period: RollupPeriod) -> (&'static str, &'static str) {
+    match period {
+        RollupPeriod::Day => ("analytics_daily", "day"),
+        RollupPeriod::Week => ("analytics_weekly", "week"),
+    }
+}
+
+// Start of the period containing a workout date. NULL for dates that aren't YYYY-MM-DD,
+// which are left out of the rollups like they are out of all other date maths.
+fn period_start(period: RollupPeriod, date: &str) -> String {
+    match period {
+        RollupPeriod::Day => format!("date(substr({}, 1, 10))", date),
+        RollupPeriod::Week => format!("date(substr({}, 1, 10), 'weekday 0', '-6 days')", date),
+    }
+}
+
+// Where the entries of a rollup statement come from, and whose workout they belong to
+pub(crate) struct RollupSource<'a> {
+    pub entries: &'a str, // a table or subquery, aliased `we`
+    pub user_id: &'a str,
+    pub date: &'a str,
+    pub joins: &'a str,
+    pub filter: &'a str,
+}
+
+impl RollupSource<'_> {
+    // Entries that live in workout_entries, with their owner read from the workout
+    pub const TABLE: RollupSource<'static> = RollupSource {
+        entries: "workout_entries",
+        user_id: "w.user_id",
+        date: "w.date",
+        joins: "JOIN workouts w ON w.id = we.workout_id",
+        filter: "1",
+    };
+}
+
+// The rollup rows of some entries, each value multiplied by `sign`. Sets count once per
+// round of the entry's group, as in the workout summary.
+pub(crate) fn rollup_select(period: RollupPeriod, source: &RollupSource, rounds: &str, sign: &str) -> String {
+    let start = period_start(period, source.date);
+    format!(
+        "
+        SELECT {user_id} AS user_id, {start} AS period, e.id AS exercise_id, e.muscle_group,
+            {sign}COUNT(*) AS entries,
+            {sign}SUM(we.sets * {rounds}) AS sets,
+            {sign}SUM(we.reps) AS reps,
+            {sign}TOTAL(we.sets * {rounds} * we.reps * COALESCE(we.weight, 0.0)) AS volume
+        FROM {entries} we
+        JOIN exercises e ON e.id = we.exercise_id
+        LEFT JOIN entry_groups g ON g.id = we.group_id
+        {joins}
+        WHERE {filter} AND {start} IS NOT NULL
+        GROUP BY 1, 2, e.id
+        ",
+        user_id = source.user_id,
+        entries = source.entries,
+        joins = source.joins,
+        filter = source.filter,
+    )
+}
+
+// Adds the rows of rollup_select to what's stored
+pub(crate) fn rollup_upsert(period: RollupPeriod, source: &RollupSource, rounds: &str, sign: &str) -> String {
+    let (table, column) = rollup_table(period);
+    format!(
+        "
+        INSERT INTO {table} (user_id, {column}, exercise_id, muscle_group, entries, sets, reps, volume)
+        {select}
+        ON CONFLICT (user_id, {column}, exercise_id) DO UPDATE SET
+            entries = entries + excluded.entries,
+            sets = sets + excluded.sets,
+            reps = reps + excluded.reps,
+            volume = volume + excluded.volume
+        ",
+        select = rollup_select(period, source, rounds, sign),
+    )
+}
+
+pub struct RebuiltRollups {
+    pub daily: u64,
+    pub weekly: u64,
+}
+
+// Recomputes every rollup from the entries, in one transaction
+pub async fn rebuild_rollups(pool: &SqlitePool) -> Result<RebuiltRollups, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let daily = rebuild(&mut tx, RollupPeriod::Day).await?;
+    let weekly = rebuild(&mut tx, RollupPeriod::Week).await?;
+    tx.commit().await?;
+
+    Ok(RebuiltRollups { daily, weekly })
+}
+
+async fn rebuild(tx: &mut Transaction<'_, Sqlite>, period: RollupPeriod) -> Result<u64, sqlx::Error> {
+    let (table, column) = rollup_table(period);
+    sqlx::query(&format!("DELETE FROM {}", table)).execute(&mut **tx).await?;
+
+    let inserted = sqlx::query(&format!(
+        "INSERT INTO {table} (user_id, {column}, exercise_id, muscle_group, entries, sets, reps, volume) {select}",
+        select = rollup_select(period, &RollupSource::TABLE, "COALESCE(g.rounds, 1)", ""),
+    ))
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(inserted.rows_affected())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RollupTotals {
+    pub entries: i64,
+    pub sets: i64,
+    pub reps: i64,
+    pub volume: f64,
+}
+
+// A rollup row that differs from what the entries add up to. `stored` is None when the
+// row is missing, `expected` is None when it shouldn't exist.
+#[derive(Debug, Clone)]
+pub struct RollupMismatch {
+    pub period: RollupPeriod,
+    pub user_id: i64,
+    pub start: String,
+    pub exercise_id: i64,
+    pub stored: Option<RollupTotals>,
+    pub expected: Option<RollupTotals>,
+}
+
+#[derive(FromRow)]
+struct MismatchRow {
+    user_id: i64,
+    start: String,
+    exercise_id: i64,
+    stored_entries: Option<i64>,
+    stored_sets: Option<i64>,
+    stored_reps: Option<i64>,
+    stored_volume: Option<f64>,
+    expected_entries: Option<i64>,
+    expected_sets: Option<i64>,
+    expected_reps: Option<i64>,
+    expected_volume: Option<f64>,
+}
+
+fn totals(entries: Option<i64>, sets: Option<i64>, reps: Option<i64>, volume: Option<f64>) -> Option<RollupTotals> {
+    Some(RollupTotals { entries: entries?, sets: sets?, reps: reps?, volume: volume? })
+}
+
+// Every stored rollup row that disagrees with a fresh computation; empty when consistent
+pub async fn check_rollups(pool: &SqlitePool) -> Result<Vec<RollupMismatch>, sqlx::Error> {
+    let mut mismatches = Vec::new();
+
+    for period in ROLLUP_PERIODS {
+        let (table, column) = rollup_table(period);
+        // Volume is summed in a different order incrementally, so allow for rounding
+        let rows = sqlx::query_as::<_, MismatchRow>(&format!(
+            "
+            WITH expected AS ({select})
+            SELECT COALESCE(s.user_id, x.user_id) AS user_id,
+                COALESCE(s.{column}, x.period) AS start,
+                COALESCE(s.exercise_id, x.exercise_id) AS exercise_id,
+                s.entries AS stored_entries, s.sets AS stored_sets,
+                s.reps AS stored_reps, s.volume AS stored_volume,
+                x.entries AS expected_entries, x.sets AS expected_sets,
+                x.reps AS expected_reps, x.volume AS expected_volume
+            FROM {table} s
+            FULL OUTER JOIN expected x
+                ON x.user_id = s.user_id AND x.period = s.{column} AND x.exercise_id = s.exercise_id
+            WHERE s.user_id IS NULL OR x.user_id IS NULL
+                OR s.entries <> x.entries OR s.sets <> x.sets OR s.reps <> x.reps
+                OR abs(s.volume - x.volume) > 1e-6 OR s.muscle_group IS NOT x.muscle_group
+            ORDER BY 1, 2, 3
+            ",
+            select = rollup_select(period, &RollupSource::TABLE, "COALESCE(g.rounds, 1)", ""),
+        ))
+        .fetch_all(pool)
+        .await?;
+
+        mismatches.extend(rows.into_iter().map(|row| RollupMismatch {
+            period,
+            user_id: row.user_id,
+            start: row.start,
+            exercise_id: row.exercise_id,
+            stored: totals(row.stored_entries, row.stored_sets, row.stored_reps, row.stored_volume),
+            expected: totals(row.expected_entries, row.expected_sets, row.expected_reps, row.expected_volume),
+        }));
+    }
+
+    Ok(mismatches)
+}
+
+// Oldest period first; per exercise or muscle, heaviest first within a period
+pub async fn get_rollups(
+    pool: &SqlitePool,
+    user_id: i64,
+    period: RollupPeriod,
+    by: RollupGroup,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> Result<Vec<RollupRow>, sqlx::Error> {
+    let (table, column) = rollup_table(period);
+    let (fields, group) = match by {
+        RollupGroup::Total => ("NULL AS exercise_id, NULL AS exercise_name, NULL AS muscle_group", ""),
+        RollupGroup::Exercise => (
+            "r.exercise_id, e.name AS exercise_name, r.muscle_group",
+            ", r.exercise_id",
+        ),
+        RollupGroup::Muscle => ("NULL AS exercise_id, NULL AS exercise_name, r.muscle_group", ", r.muscle_group"),
+    };
+
+    sqlx::query_as::<_, RollupRow>(&format!(
+        "
+        SELECT r.{column} AS period, {fields},
+            SUM(r.entries) AS entries, SUM(r.sets) AS sets, SUM(r.reps) AS reps,
+            TOTAL(r.volume) AS volume
+        FROM {table} r
+        JOIN exercises e ON e.id = r.exercise_id
+        WHERE r.user_id = ?
+          AND (? IS NULL OR r.{column} >= ?)
+          AND (? IS NULL OR r.{column} <= ?)
+        GROUP BY r.{column}{group}
+        ORDER BY r.{column}, volume DESC{group}
+        "
+    ))
+    .bind(user_id)
+    .bind(from)
+    .bind(from)
+    .bind(to)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
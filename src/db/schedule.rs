@@ -0,0 +1,130 @@
+use rand::{distributions::Alphanumeric, Rng};
+use sqlx::SqlitePool;
+use crate::models::{
+    schedule::{NewScheduledSession, ScheduledSession},
+    workout::Workout,
+};
+
+const FEED_TOKEN_LENGTH: usize = 40;
+
+const SESSION_COLUMNS: &str = "
+    s.id, s.user_id, s.date, s.template_id, t.name AS template_name, s.notes
+    FROM scheduled_sessions s
+    LEFT JOIN workout_templates t ON s.template_id = t.id
+";
+
+// Sessions on or after `from` (YYYY-MM-DD), soonest first
+pub async fn get_scheduled_sessions(
+    pool: &SqlitePool,
+    user_id: i64,
+    from: &str,
+) -> Result<Vec<ScheduledSession>, sqlx::Error> {
+    let sessions = sqlx::query_as::<_, ScheduledSession>(&format!(
+        "SELECT {} WHERE s.user_id = ? AND substr(s.date, 1, 10) >= ? ORDER BY s.date, s.id",
+        SESSION_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(from)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(sessions)
+}
+
+pub async fn create_scheduled_session(
+    pool: &SqlitePool,
+    user_id: i64,
+    session: &NewScheduledSession,
+) -> Result<ScheduledSession, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO scheduled_sessions (user_id, date, template_id, notes) VALUES (?, ?, ?, ?)"
+    )
+    .bind(user_id)
+    .bind(&session.date)
+    .bind(session.template_id)
+    .bind(&session.notes)
+    .execute(pool)
+    .await?;
+
+    let session = sqlx::query_as::<_, ScheduledSession>(&format!(
+        "SELECT {} WHERE s.id = ?",
+        SESSION_COLUMNS
+    ))
+    .bind(result.last_insert_rowid())
+    .fetch_one(pool)
+    .await?;
+
+    Ok(session)
+}
+
+// Returns false when the session doesn't exist or belongs to someone else
+pub async fn delete_scheduled_session(
+    pool: &SqlitePool,
+    user_id: i64,
+    id: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM scheduled_sessions WHERE id = ? AND user_id = ?")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+// Creates the feed token, or replaces it so the old feed URL stops working
+pub async fn rotate_feed_token(pool: &SqlitePool, user_id: i64) -> Result<String, sqlx::Error> {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(FEED_TOKEN_LENGTH)
+        .map(char::from)
+        .collect();
+
+    sqlx::query(
+        "
+        INSERT INTO calendar_feeds (user_id, token) VALUES (?, ?)
+        ON CONFLICT(user_id) DO UPDATE SET token = excluded.token
+        "
+    )
+    .bind(user_id)
+    .bind(&token)
+    .execute(pool)
+    .await?;
+
+    Ok(token)
+}
+
+pub async fn revoke_feed_token(pool: &SqlitePool, user_id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM calendar_feeds WHERE user_id = ?")
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_feed_user(pool: &SqlitePool, token: &str) -> Result<Option<i64>, sqlx::Error> {
+    let user: Option<(i64,)> = sqlx::query_as("SELECT user_id FROM calendar_feeds WHERE token = ?")
+        .bind(token)
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(user.map(|(id,)| id))
+}
+
+// Workouts on or after `from`, for the calendar feed
+pub async fn get_workouts_since(
+    pool: &SqlitePool,
+    user_id: i64,
+    from: &str,
+) -> Result<Vec<Workout>, sqlx::Error> {
+    let workouts = sqlx::query_as::<_, Workout>(
+        "SELECT id, user_id, date, notes, version, finished_at FROM workouts WHERE user_id = ? AND substr(date, 1, 10) >= ? ORDER BY date, id"
+    )
+    .bind(user_id)
+    .bind(from)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(workouts)
+}
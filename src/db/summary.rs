@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::Deserialize;
+use sqlx::{FromRow, SqlitePool};
+use crate::db::connection::entry_volume;
+use crate::db::loaders::json_ids;
+use crate::models::analytics::{
+    ExerciseVolume, SummaryExercise, SummaryGroup, WorkoutProgress, WorkoutSummary, WorkoutTotals,
+};
+use crate::models::entry_group::EntryGroup;
+
+// The shared layer behind the workout summary, progress and report. Every query applies the
+// group's rounds the same way, so the totals agree wherever they're shown. Progress is
+// grouped in SQL over all of a user's workouts at once; nothing here queries per workout.
+
+#[derive(FromRow, Debug, Clone)]
+pub struct EntryStats {
+    pub workout_id: i64,
+    pub name: String,
+    pub muscle_group: String,
+    pub position: i32,
+    pub group_id: Option<i64>,
+    pub sets: i32, // sets x the group's rounds
+    pub reps: i32,
+    pub weight: f64, // 0 for bodyweight
+}
+
+impl EntryStats {
+    pub fn volume(&self) -> f64 {
+        entry_volume(self.sets, self.reps, self.weight)
+    }
+}
+
+// Joins of an entry to its workout, exercise and group, and the entry's volume over them
+const ENTRY_JOINS: &str = "
+    FROM workout_entries we
+    JOIN workouts w ON we.workout_id = w.id
+    JOIN exercises e ON we.exercise_id = e.id
+    LEFT JOIN entry_groups g ON we.group_id = g.id
+";
+const ENTRY_SETS: &str = "we.sets * COALESCE(g.rounds, 1)";
+const ENTRY_VOLUME: &str = "we.sets * COALESCE(g.rounds, 1) * we.reps * COALESCE(we.weight, 0.0)";
+
+// Totals per workout, oldest first
+pub async fn get_user_progress(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<WorkoutTotals>, sqlx::Error> {
+    // Grouping in index order walks idx_workouts_user_date instead of every user's workouts.
+    // TOTAL() is always a float, and 0.0 for a workout without entries.
+    sqlx::query_as::<_, WorkoutTotals>(&format!(
+        "
+        SELECT w.id AS workout_id, w.date,
+            COALESCE(SUM({sets}), 0) AS total_sets,
+            COALESCE(SUM(we.reps), 0) AS total_reps,
+            TOTAL({volume}) AS total_volume
+        FROM workouts w
+        LEFT JOIN workout_entries we ON we.workout_id = w.id
+        LEFT JOIN entry_groups g ON we.group_id = g.id
+        WHERE w.user_id = ?
+        GROUP BY w.date, w.id
+        ORDER BY w.date, w.id
+        ",
+        sets = ENTRY_SETS,
+        volume = ENTRY_VOLUME,
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[derive(FromRow)]
+struct ProgressRow {
+    #[sqlx(flatten)]
+    totals: WorkoutTotals,
+    entries: String, // JSON array of EntryVolume, heaviest first
+}
+
+#[derive(Deserialize)]
+struct EntryVolume {
+    name: String,
+    muscle_group: String,
+    volume: f64,
+}
+
+// Totals plus volume per muscle group and the heaviest entries, per workout, oldest first.
+// One row per workout: its entries come back as a JSON array built by SQLite, which is
+// far cheaper than decoding a row per entry.
+pub async fn get_workout_progress(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<WorkoutProgress>, sqlx::Error> {
+    // group_concat rather than json_group_array, which turns the objects into strings when
+    // it has to sort them. Equal volumes keep workout order.
+    let rows = sqlx::query_as::<_, ProgressRow>(&format!(
+        "
+        SELECT w.id AS workout_id, w.date,
+            COALESCE(SUM({sets}), 0) AS total_sets,
+            COALESCE(SUM(we.reps), 0) AS total_reps,
+            TOTAL({volume}) AS total_volume,
+            '[' || COALESCE(group_concat(
+                json_object('name', e.name, 'muscle_group', e.muscle_group, 'volume', {volume}), ','
+                ORDER BY {volume} DESC, we.position, we.id
+            ) FILTER (WHERE we.id IS NOT NULL), '') || ']' AS entries
+        FROM workouts w
+        LEFT JOIN workout_entries we ON we.workout_id = w.id
+        LEFT JOIN exercises e ON we.exercise_id = e.id
+        LEFT JOIN entry_groups g ON we.group_id = g.id
+        WHERE w.user_id = ?
+        GROUP BY w.date, w.id
+        ORDER BY w.date, w.id
+        ",
+        sets = ENTRY_SETS,
+        volume = ENTRY_VOLUME,
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|row| {
+            let entries: Vec<EntryVolume> =
+                serde_json::from_str(&row.entries).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
+
+            let mut muscle_groups = BTreeMap::new();
+            for entry in &entries {
+                *muscle_groups.entry(entry.muscle_group.clone()).or_insert(0.0) += entry.volume;
+            }
+            let top_exercises = entries
+                .into_iter()
+                .map(|e| ExerciseVolume { name: e.name, volume: e.volume })
+                .collect();
+
+            Ok(WorkoutProgress { totals: row.totals, muscle_groups, top_exercises })
+        })
+        .collect()
+}
+
+// Totals, every entry and every superset/circuit of one workout
+pub async fn get_workout_summary(
+    pool: &SqlitePool,
+    workout_id: i64,
+) -> Result<WorkoutSummary, sqlx::Error> {
+    let mut summaries = get_workout_summaries(pool, &[workout_id]).await?;
+    Ok(summaries.remove(&workout_id).unwrap_or_else(|| WorkoutSummary::empty(workout_id)))
+}
+
+// Summaries of many workouts in two queries, keyed by workout id. Workouts without
+// entries or groups are missing from the map.
+pub async fn get_workout_summaries(
+    pool: &SqlitePool,
+    workout_ids: &[i64],
+) -> Result<HashMap<i64, WorkoutSummary>, sqlx::Error> {
+    let entries = sqlx::query_as::<_, EntryStats>(&format!(
+        "
+        SELECT we.workout_id, e.name, e.muscle_group, we.position, we.group_id,
+            {sets} AS sets, we.reps, COALESCE(we.weight, 0.0) AS weight
+        {joins}
+        WHERE we.workout_id IN (SELECT value FROM json_each(?))
+        ORDER BY we.workout_id, we.position, we.id
+        ",
+        sets = ENTRY_SETS,
+        joins = ENTRY_JOINS,
+    ))
+    .bind(json_ids(workout_ids))
+    .fetch_all(pool)
+    .await?;
+
+    let groups = sqlx::query_as::<_, EntryGroup>(
+        "
+        SELECT id, workout_id, kind, rounds, duration_seconds
+        FROM entry_groups
+        WHERE workout_id IN (SELECT value FROM json_each(?))
+        ORDER BY id
+        "
+    )
+    .bind(json_ids(workout_ids))
+    .fetch_all(pool)
+    .await?;
+
+    let mut by_workout: HashMap<i64, (Vec<EntryStats>, Vec<EntryGroup>)> = HashMap::new();
+    for entry in entries {
+        by_workout.entry(entry.workout_id).or_default().0.push(entry);
+    }
+    for group in groups {
+        by_workout.entry(group.workout_id).or_default().1.push(group);
+    }
+
+    Ok(by_workout
+        .into_iter()
+        .map(|(id, (entries, groups))| (id, summarize(id, entries, groups)))
+        .collect())
+}
+
+fn summarize(workout_id: i64, entries: Vec<EntryStats>, groups: Vec<EntryGroup>) -> WorkoutSummary {
+    let groups = groups
+        .into_iter()
+        .map(|group| {
+            let members: Vec<&EntryStats> =
+                entries.iter().filter(|e| e.group_id == Some(group.id)).collect();
+            let volume: f64 = members.iter().map(|e| e.volume()).sum();
+
+            SummaryGroup {
+                group_id: group.id,
+                kind: group.kind,
+                rounds: group.rounds,
+                duration_seconds: group.duration_seconds,
+                exercises: members.iter().map(|e| e.name.clone()).collect(),
+                volume,
+                // Density: volume moved per minute of the group
+                density: group.duration_seconds.map(|seconds| volume / (seconds as f64 / 60.0)),
+            }
+        })
+        .collect();
+
+    let total_sets = entries.iter().map(|e| e.sets as i64).sum();
+    let total_reps = entries.iter().map(|e| e.reps as i64).sum();
+    let total_volume = entries.iter().map(EntryStats::volume).sum();
+    let exercises = entries
+        .into_iter()
+        .map(|entry| SummaryExercise {
+            volume: entry.volume(),
+            name: entry.name,
+            position: entry.position,
+            group_id: entry.group_id,
+            sets: entry.sets,
+            reps: entry.reps,
+            weight: entry.weight,
+        })
+        .collect();
+
+    WorkoutSummary {
+        workout_id,
+        total_sets,
+        total_reps,
+        total_volume,
+        exercises,
+        groups,
+    }
+}
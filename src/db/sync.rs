@@ -0,0 +1,461 @@
+use sqlx::{SqliteConnection, SqlitePool};
+use crate::db::analytics::parse_date;
+use crate::models::workout::CreatedRecords;
+use crate::models::sync::{
+    AppliedChange, ConflictReason, SyncChange, SyncConflict, SyncEntry, SyncEntryData, SyncPull,
+    SyncPushResult, SyncRecord, SyncWorkout, SyncWorkoutData, Tombstone,
+};
+
+pub enum SyncError {
+    Invalid(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for SyncError {
+    fn from(e: sqlx::Error) -> Self {
+        SyncError::Database(e)
+    }
+}
+
+const WORKOUT_COLUMNS: &str =
+    "client_id AS id, id AS server_id, date, notes, version, change_seq AS seq FROM workouts";
+
+const ENTRY_COLUMNS: &str = "
+    e.client_id AS id, e.id AS server_id, w.client_id AS workout_id, e.exercise_id,
+    e.sets, e.reps, e.weight, e.rpe, e.position, e.version, e.change_seq AS seq
+    FROM workout_entries e
+    JOIN workouts w ON e.workout_id = w.id
+";
+
+// Accepts the 8-4-4-4-12 hex form in any case; stored lowercase
+pub fn normalize_uuid(id: &str) -> Option<String> {
+    let valid = id.len() == 36
+        && id.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+
+    valid.then(|| id.to_ascii_lowercase())
+}
+
+async fn current_seq(conn: &mut SqliteConnection, user_id: i64) -> Result<i64, sqlx::Error> {
+    let seq: Option<i64> = sqlx::query_scalar("SELECT seq FROM sync_state WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_optional(conn)
+        .await?;
+
+    Ok(seq.unwrap_or(0))
+}
+
+// Everything that changed after `since`, oldest first, at most `limit` changes
+pub async fn pull_changes(
+    pool: &SqlitePool,
+    user_id: i64,
+    since: i64,
+    limit: i64,
+) -> Result<SyncPull, sqlx::Error> {
+    // One read transaction, so the three lists agree with each other
+    let mut tx = pool.begin().await?;
+
+    let seqs: Vec<i64> = sqlx::query_scalar(
+        "
+        SELECT change_seq FROM (
+            SELECT change_seq FROM workouts WHERE user_id = ? AND change_seq > ?
+            UNION ALL
+            SELECT e.change_seq FROM workout_entries e JOIN workouts w ON e.workout_id = w.id
+            WHERE w.user_id = ? AND e.change_seq > ?
+            UNION ALL
+            SELECT change_seq FROM sync_tombstones WHERE user_id = ? AND change_seq > ?
+        )
+        ORDER BY change_seq
+        LIMIT ?
+        "
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(user_id)
+    .bind(since)
+    .bind(user_id)
+    .bind(since)
+    .bind(limit + 1)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let has_more = seqs.len() as i64 > limit;
+    let upto = if has_more {
+        seqs[limit as usize - 1]
+    } else {
+        current_seq(&mut tx, user_id).await?.max(since)
+    };
+
+    let workouts = sqlx::query_as::<_, SyncWorkout>(&format!(
+        "SELECT {} WHERE user_id = ? AND change_seq > ? AND change_seq <= ? ORDER BY change_seq",
+        WORKOUT_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let entries = sqlx::query_as::<_, SyncEntry>(&format!(
+        "SELECT {} WHERE w.user_id = ? AND e.change_seq > ? AND e.change_seq <= ? ORDER BY e.change_seq",
+        ENTRY_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let deleted = sqlx::query_as::<_, Tombstone>(
+        "
+        SELECT kind, client_id AS id, change_seq AS seq FROM sync_tombstones
+        WHERE user_id = ? AND change_seq > ? AND change_seq <= ?
+        ORDER BY change_seq
+        "
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(upto)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(SyncPull {
+        seq: upto,
+        has_more,
+        workouts,
+        entries,
+        deleted,
+    })
+}
+
+async fn is_tombstoned(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    kind: &str,
+    client_id: &str,
+) -> Result<bool, sqlx::Error> {
+    let found: Option<i64> = sqlx::query_scalar(
+        "SELECT 1 FROM sync_tombstones WHERE user_id = ? AND kind = ? AND client_id = ?"
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(client_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(found.is_some())
+}
+
+// (id, owner, version) of the workout with this UUID
+async fn find_workout(
+    conn: &mut SqliteConnection,
+    client_id: &str,
+) -> Result<Option<(i64, i64, i64)>, sqlx::Error> {
+    sqlx::query_as("SELECT id, user_id, version FROM workouts WHERE client_id = ?")
+        .bind(client_id)
+        .fetch_optional(conn)
+        .await
+}
+
+// (id, owner, version, workout id) of the entry with this UUID
+async fn find_entry(
+    conn: &mut SqliteConnection,
+    client_id: &str,
+) -> Result<Option<(i64, i64, i64, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "
+        SELECT e.id, w.user_id, e.version, e.workout_id
+        FROM workout_entries e JOIN workouts w ON e.workout_id = w.id
+        WHERE e.client_id = ?
+        "
+    )
+    .bind(client_id)
+    .fetch_optional(conn)
+    .await
+}
+
+async fn sync_workout(conn: &mut SqliteConnection, id: i64) -> Result<SyncWorkout, sqlx::Error> {
+    sqlx::query_as::<_, SyncWorkout>(&format!("SELECT {} WHERE id = ?", WORKOUT_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+}
+
+async fn sync_entry(conn: &mut SqliteConnection, id: i64) -> Result<SyncEntry, sqlx::Error> {
+    sqlx::query_as::<_, SyncEntry>(&format!("SELECT {} WHERE e.id = ?", ENTRY_COLUMNS))
+        .bind(id)
+        .fetch_one(conn)
+        .await
+}
+
+fn conflict(kind: &'static str, id: &str, reason: ConflictReason, server: Option<SyncRecord>) -> SyncConflict {
+    SyncConflict {
+        kind,
+        id: id.to_string(),
+        reason,
+        server,
+    }
+}
+
+fn applied(kind: &'static str, id: &str, version: Option<i64>) -> AppliedChange {
+    AppliedChange {
+        kind,
+        id: id.to_string(),
+        version,
+    }
+}
+
+fn validate_workout(data: &SyncWorkoutData) -> Result<(), String> {
+    match parse_date(&data.date) {
+        Some(_) => Ok(()),
+        None => Err(format!("invalid date '{}', expected YYYY-MM-DD", data.date)),
+    }
+}
+
+fn validate_entry(data: &SyncEntryData) -> Result<(), String> {
+    if data.sets <= 0 || data.reps <= 0 {
+        return Err("sets and reps must be positive".to_string());
+    }
+    if data.weight.is_some_and(|w| w < 0.0) {
+        return Err("weight must not be negative".to_string());
+    }
+    if data.rpe.is_some_and(|rpe| !(1.0..=10.0).contains(&rpe)) {
+        return Err("rpe must be between 1 and 10".to_string());
+    }
+    Ok(())
+}
+
+async fn push_workout(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    id: &str,
+    base_version: Option<i64>,
+    data: Option<&SyncWorkoutData>,
+) -> Result<Result<AppliedChange, SyncConflict>, sqlx::Error> {
+    let Some((server_id, owner, version)) = find_workout(conn, id).await? else {
+        let Some(data) = data else {
+            // Deleting something the server never had (or already deleted) is a no-op
+            return Ok(Ok(applied("workout", id, None)));
+        };
+        if is_tombstoned(conn, user_id, "workout", id).await? {
+            return Ok(Err(conflict("workout", id, ConflictReason::Deleted, None)));
+        }
+
+        sqlx::query("INSERT INTO workouts (user_id, date, notes, client_id) VALUES (?, ?, ?, ?)")
+            .bind(user_id)
+            .bind(&data.date)
+            .bind(&data.notes)
+            .bind(id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(Ok(applied("workout", id, Some(1))));
+    };
+
+    if owner != user_id {
+        return Ok(Err(conflict("workout", id, ConflictReason::Unavailable, None)));
+    }
+    if base_version != Some(version) {
+        let server = sync_workout(conn, server_id).await?;
+        return Ok(Err(conflict("workout", id, ConflictReason::VersionMismatch, Some(SyncRecord::Workout(server)))));
+    }
+
+    let Some(data) = data else {
+        sqlx::query("DELETE FROM workouts WHERE id = ?")
+            .bind(server_id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(Ok(applied("workout", id, None)));
+    };
+
+    sqlx::query("UPDATE workouts SET date = ?, notes = ?, version = version + 1 WHERE id = ?")
+        .bind(&data.date)
+        .bind(&data.notes)
+        .bind(server_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Ok(applied("workout", id, Some(version + 1))))
+}
+
+async fn push_entry(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    id: &str,
+    base_version: Option<i64>,
+    data: Option<&SyncEntryData>,
+) -> Result<Result<AppliedChange, SyncConflict>, SyncError> {
+    let existing = find_entry(conn, id).await?;
+
+    if existing.is_some_and(|(_, owner, _, _)| owner != user_id) {
+        return Ok(Err(conflict("entry", id, ConflictReason::Unavailable, None)));
+    }
+
+    // The workout the entry should end up in
+    let target = match data {
+        None => None,
+        Some(data) => {
+            let workout_id = normalize_uuid(&data.workout_id)
+                .ok_or_else(|| SyncError::Invalid(format!("workout_id '{}' is not a UUID", data.workout_id)))?;
+
+            match find_workout(conn, &workout_id).await? {
+                Some((workout, owner, _)) if owner == user_id => Some(workout),
+                Some(_) => return Ok(Err(conflict("entry", id, ConflictReason::Unavailable, None))),
+                None if is_tombstoned(conn, user_id, "workout", &workout_id).await? => {
+                    return Ok(Err(conflict("entry", id, ConflictReason::Deleted, None)));
+                }
+                None => {
+                    return Err(SyncError::Invalid(format!("unknown workout {}", workout_id)));
+                }
+            }
+        }
+    };
+
+    if let Some(data) = data {
+        let exercise: Option<i64> = sqlx::query_scalar("SELECT id FROM exercises WHERE id = ?")
+            .bind(data.exercise_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+        if exercise.is_none() {
+            return Err(SyncError::Invalid(format!("unknown exercise {}", data.exercise_id)));
+        }
+    }
+
+    let Some((server_id, _, version, _)) = existing else {
+        let (Some(data), Some(workout_id)) = (data, target) else {
+            return Ok(Ok(applied("entry", id, None)));
+        };
+        if is_tombstoned(conn, user_id, "entry", id).await? {
+            return Ok(Err(conflict("entry", id, ConflictReason::Deleted, None)));
+        }
+
+        sqlx::query(
+            "
+            INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, rpe, client_id, position)
+            VALUES (?, ?, ?, ?, ?, ?, ?,
+                (SELECT COALESCE(MAX(position), 0) + 1 FROM workout_entries WHERE workout_id = ?))
+            "
+        )
+        .bind(workout_id)
+        .bind(data.exercise_id)
+        .bind(data.sets)
+        .bind(data.reps)
+        .bind(data.weight)
+        .bind(data.rpe)
+        .bind(id)
+        .bind(workout_id)
+        .execute(&mut *conn)
+        .await?;
+        return Ok(Ok(applied("entry", id, Some(1))));
+    };
+
+    if base_version != Some(version) {
+        let server = sync_entry(conn, server_id).await?;
+        return Ok(Err(conflict("entry", id, ConflictReason::VersionMismatch, Some(SyncRecord::Entry(server)))));
+    }
+
+    let (Some(data), Some(workout_id)) = (data, target) else {
+        sqlx::query("DELETE FROM workout_entries WHERE id = ?")
+            .bind(server_id)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(Ok(applied("entry", id, None)));
+    };
+
+    sqlx::query(
+        "
+        UPDATE workout_entries
+        SET workout_id = ?, exercise_id = ?, sets = ?, reps = ?, weight = ?, rpe = ?,
+            group_id = CASE WHEN workout_id = ? THEN group_id ELSE NULL END,
+            version = version + 1
+        WHERE id = ?
+        "
+    )
+    .bind(workout_id)
+    .bind(data.exercise_id)
+    .bind(data.sets)
+    .bind(data.reps)
+    .bind(data.weight)
+    .bind(data.rpe)
+    .bind(workout_id)
+    .bind(server_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(Ok(applied("entry", id, Some(version + 1))))
+}
+
+// Applies offline changes in order, in one transaction. An invalid change rejects the whole
+// batch; a conflicting one is skipped and reported with the server's copy, which wins.
+pub async fn push_changes(
+    pool: &SqlitePool,
+    user_id: i64,
+    changes: &[SyncChange],
+) -> Result<(SyncPushResult, CreatedRecords), SyncError> {
+    let mut tx = pool.begin().await?;
+    let mut applied = Vec::new();
+    let mut conflicts = Vec::new();
+    let mut created = CreatedRecords::default();
+
+    for (index, change) in changes.iter().enumerate() {
+        let invalid = |message: String| SyncError::Invalid(format!("change {}: {}", index + 1, message));
+
+        let (id, base_version, deleted, has_data) = match change {
+            SyncChange::Workout { id, base_version, deleted, data } => (id, *base_version, *deleted, data.is_some()),
+            SyncChange::Entry { id, base_version, deleted, data } => (id, *base_version, *deleted, data.is_some()),
+        };
+        let id = normalize_uuid(id).ok_or_else(|| invalid(format!("id '{}' is not a UUID", id)))?;
+        if !deleted && !has_data {
+            return Err(invalid("data is required unless deleted is true".to_string()));
+        }
+
+        let outcome = match change {
+            SyncChange::Workout { data, .. } => {
+                let data = data.as_ref().filter(|_| !deleted);
+                if let Some(data) = data {
+                    validate_workout(data).map_err(invalid)?;
+                }
+                push_workout(&mut tx, user_id, &id, base_version, data).await?
+            }
+            SyncChange::Entry { data, .. } => {
+                let data = data.as_ref().filter(|_| !deleted);
+                if let Some(data) = data {
+                    validate_entry(data).map_err(invalid)?;
+                }
+                push_entry(&mut tx, user_id, &id, base_version, data)
+                    .await
+                    .map_err(|e| match e {
+                        SyncError::Invalid(message) => invalid(message),
+                        e => e,
+                    })?
+            }
+        };
+
+        match outcome {
+            Ok(change) => {
+                // Only a new record starts at version 1
+                if change.version == Some(1) {
+                    match change.kind {
+                        "workout" => created.workouts.extend(find_workout(&mut tx, &id).await?.map(|w| w.0)),
+                        _ => created.entries.extend(find_entry(&mut tx, &id).await?.map(|e| e.0)),
+                    }
+                }
+                applied.push(change);
+            }
+            Err(change) => conflicts.push(change),
+        }
+    }
+
+    let seq = current_seq(&mut tx, user_id).await?;
+    tx.commit().await?;
+
+    let result = SyncPushResult {
+        seq,
+        applied,
+        conflicts,
+    };
+    Ok((result, created))
+}
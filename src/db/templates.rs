@@ -0,0 +1,141 @@
+use sqlx::SqlitePool;
+use crate::db::connection::{create_workout_db, get_exercise_by_id};
+use crate::db::progression::get_next_session;
+use crate::models::{
+    template::{
+        NewWorkoutTemplate, StartTemplate, StartedWorkout, TemplateExercise, WorkoutTemplate,
+        WorkoutTemplateDetailed,
+    },
+    workout::NewWorkout,
+};
+
+pub async fn get_templates_for_user(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<WorkoutTemplate>, sqlx::Error> {
+    let templates = sqlx::query_as::<_, WorkoutTemplate>(
+        "SELECT id, user_id, name, notes FROM workout_templates WHERE user_id = ? ORDER BY id"
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(templates)
+}
+
+pub async fn get_template_detailed(
+    pool: &SqlitePool,
+    id: i64,
+) -> Result<Option<WorkoutTemplateDetailed>, sqlx::Error> {
+    let template = sqlx::query_as::<_, WorkoutTemplate>(
+        "SELECT id, user_id, name, notes FROM workout_templates WHERE id = ?"
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some(template) = template else {
+        return Ok(None);
+    };
+
+    let exercises = sqlx::query_as::<_, TemplateExercise>(
+        "
+        SELECT
+            te.id,
+            te.template_id,
+            te.exercise_id,
+            e.name AS exercise_name,
+            e.muscle_group AS muscle_group,
+            te.position,
+            te.sets,
+            te.reps
+        FROM workout_template_exercises te
+        JOIN exercises e ON te.exercise_id = e.id
+        WHERE te.template_id = ?
+        ORDER BY te.position;
+        "
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Some(WorkoutTemplateDetailed { template, exercises }))
+}
+
+// Template and its exercises are written together or not at all
+pub async fn create_template_db(
+    pool: &SqlitePool,
+    user_id: i64,
+    new_template: &NewWorkoutTemplate,
+) -> Result<WorkoutTemplateDetailed, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "INSERT INTO workout_templates (user_id, name, notes) VALUES (?, ?, ?)"
+    )
+    .bind(user_id)
+    .bind(&new_template.name)
+    .bind(&new_template.notes)
+    .execute(&mut *tx)
+    .await?;
+
+    let template_id = result.last_insert_rowid();
+
+    for (position, exercise) in new_template.exercises.iter().enumerate() {
+        sqlx::query(
+            "
+            INSERT INTO workout_template_exercises (template_id, exercise_id, position, sets, reps)
+            VALUES (?, ?, ?, ?, ?)
+            "
+        )
+        .bind(template_id)
+        .bind(exercise.exercise_id)
+        .bind(position as i32)
+        .bind(exercise.sets)
+        .bind(exercise.reps)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    get_template_detailed(pool, template_id)
+        .await?
+        .ok_or(sqlx::Error::RowNotFound)
+}
+
+// Creates an empty workout from the template and suggests loads for every planned exercise
+pub async fn start_template(
+    pool: &SqlitePool,
+    user_id: i64,
+    template: &WorkoutTemplateDetailed,
+    start: &StartTemplate,
+) -> Result<StartedWorkout, sqlx::Error> {
+    let workout = create_workout_db(
+        pool,
+        &NewWorkout {
+            user_id,
+            date: start.date.clone(),
+            notes: start.notes.clone().or_else(|| Some(template.template.name.clone())),
+        },
+    )
+    .await?;
+
+    let mut recommendations = Vec::new();
+
+    for planned in &template.exercises {
+        let Some(exercise) = get_exercise_by_id(pool, planned.exercise_id).await? else {
+            continue;
+        };
+
+        let recommendation =
+            get_next_session(pool, user_id, &exercise, Some((planned.sets, planned.reps))).await?;
+        recommendations.push(recommendation);
+    }
+
+    Ok(StartedWorkout {
+        workout,
+        template_id: template.template.id,
+        recommendations,
+    })
+}
@@ -0,0 +1,196 @@
+use sqlx::SqlitePool;
+use crate::models::webhook::{PendingDelivery, WebhookDelivery, WebhookRow};
+
+const WEBHOOK_COLUMNS: &str = "id, user_id, url, secret, events, all_users, created_at";
+
+const DELIVERY_COLUMNS: &str = "id, webhook_id, event, payload, status, attempts, next_attempt_at, \
+    response_status, last_error, created_at, delivered_at";
+
+pub async fn get_webhooks(pool: &SqlitePool, user_id: i64) -> Result<Vec<WebhookRow>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookRow>(&format!(
+        "SELECT {} FROM webhooks WHERE user_id = ? ORDER BY id",
+        WEBHOOK_COLUMNS
+    ))
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_webhook(pool: &SqlitePool, id: i64) -> Result<Option<WebhookRow>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookRow>(&format!("SELECT {} FROM webhooks WHERE id = ?", WEBHOOK_COLUMNS))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn create_webhook(
+    pool: &SqlitePool,
+    user_id: i64,
+    url: &str,
+    secret: &str,
+    events: &[String],
+    all_users: bool,
+    now: i64,
+) -> Result<WebhookRow, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO webhooks (user_id, url, secret, events, all_users, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(user_id)
+    .bind(url)
+    .bind(secret)
+    .bind(events.join(","))
+    .bind(all_users)
+    .bind(now)
+    .execute(pool)
+    .await?;
+
+    Ok(WebhookRow {
+        id: result.last_insert_rowid(),
+        user_id,
+        url: url.to_string(),
+        secret: secret.to_string(),
+        events: events.join(","),
+        all_users,
+        created_at: now,
+    })
+}
+
+// Removes the webhook with its delivery log
+pub async fn delete_webhook(pool: &SqlitePool, user_id: i64, id: i64) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        "DELETE FROM webhook_deliveries WHERE webhook_id IN (SELECT id FROM webhooks WHERE id = ? AND user_id = ?)"
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let result = sqlx::query("DELETE FROM webhooks WHERE id = ? AND user_id = ?")
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(result.rows_affected() > 0)
+}
+
+// Queues the payload for every webhook that listens to the event: the user's own hooks and
+// the all-users hooks. Returns how many deliveries were queued.
+pub async fn enqueue_deliveries(
+    pool: &SqlitePool,
+    user_id: i64,
+    event: &str,
+    payload: &str,
+    now: i64,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "
+        INSERT INTO webhook_deliveries (webhook_id, event, payload, next_attempt_at, created_at)
+        SELECT id, ?, ?, ?, ? FROM webhooks
+        WHERE (user_id = ? OR all_users = 1)
+          AND ',' || events || ',' LIKE '%,' || ? || ',%'
+        ORDER BY id
+        "
+    )
+    .bind(event)
+    .bind(payload)
+    .bind(now)
+    .bind(now)
+    .bind(user_id)
+    .bind(event)
+    .execute(pool)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+// Pending deliveries whose next attempt is due, oldest first
+pub async fn get_due_deliveries(
+    pool: &SqlitePool,
+    now: i64,
+    limit: i64,
+) -> Result<Vec<PendingDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, PendingDelivery>(
+        "
+        SELECT d.id, d.event, d.payload, d.attempts, h.url, h.secret
+        FROM webhook_deliveries d
+        JOIN webhooks h ON d.webhook_id = h.id
+        WHERE d.status = 'pending' AND d.next_attempt_at <= ?
+        ORDER BY d.next_attempt_at, d.id
+        LIMIT ?
+        "
+    )
+    .bind(now)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn mark_delivered(
+    pool: &SqlitePool,
+    id: i64,
+    response_status: u16,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "
+        UPDATE webhook_deliveries
+        SET status = 'delivered', attempts = attempts + 1, next_attempt_at = NULL,
+            response_status = ?, last_error = NULL, delivered_at = ?
+        WHERE id = ?
+        "
+    )
+    .bind(response_status)
+    .bind(now)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Records a failed attempt; `retry_at` None gives the delivery up
+pub async fn mark_attempt_failed(
+    pool: &SqlitePool,
+    id: i64,
+    response_status: Option<u16>,
+    error: &str,
+    retry_at: Option<i64>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "
+        UPDATE webhook_deliveries
+        SET status = CASE WHEN ? IS NULL THEN 'failed' ELSE 'pending' END,
+            attempts = attempts + 1, next_attempt_at = ?, response_status = ?, last_error = ?
+        WHERE id = ?
+        "
+    )
+    .bind(retry_at)
+    .bind(retry_at)
+    .bind(response_status)
+    .bind(error)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// Newest first
+pub async fn get_deliveries(
+    pool: &SqlitePool,
+    webhook_id: i64,
+    limit: i64,
+) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, WebhookDelivery>(&format!(
+        "SELECT {} FROM webhook_deliveries WHERE webhook_id = ? ORDER BY id DESC LIMIT ?",
+        DELIVERY_COLUMNS
+    ))
+    .bind(webhook_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_graphql::{
+    dataloader::{DataLoader, Loader},
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Schema,
+};
+use sqlx::SqlitePool;
+use crate::db::connection::{get_user, get_workout_by_id};
+use crate::db::loaders::{get_entries_for_workouts, get_exercises_by_ids, get_recent_workouts};
+use crate::middleware::auth::AuthUser;
+use crate::models::{exercise::Exercise, user::User, workout::Workout, workout_entry::WorkoutEntry};
+
+// Deep enough for me { workouts { entries { exercise { name } } } } with room to spare
+pub const MAX_DEPTH: usize = 10;
+pub const MAX_COMPLEXITY: usize = 2000;
+
+pub const DEFAULT_WORKOUTS: i64 = 20;
+pub const MAX_WORKOUTS: i64 = 100;
+// Complexity assumes this many entries per workout, since the real count isn't known up front
+const ESTIMATED_ENTRIES: usize = 10;
+
+pub type GymBuddySchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema() -> GymBuddySchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish()
+}
+
+// Loaders live for one request, so batching never serves one user's rows from another's cache
+pub fn request_data(
+    request: async_graphql::Request,
+    pool: SqlitePool,
+    auth: AuthUser,
+) -> async_graphql::Request {
+    request
+        .data(DataLoader::new(EntriesByWorkout(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(ExerciseById(pool.clone()), tokio::spawn))
+        .data(pool)
+        .data(auth)
+}
+
+// Logs the error and hides it from the client, like internal_error in the REST handlers
+fn internal_error(e: impl std::fmt::Debug) -> async_graphql::Error {
+    tracing::error!("Database error: {:?}", e);
+    async_graphql::Error::new("Internal server error")
+}
+
+pub struct EntriesByWorkout(SqlitePool);
+
+impl Loader<i64> for EntriesByWorkout {
+    type Value = Vec<WorkoutEntry>;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, workout_ids: &[i64]) -> Result<HashMap<i64, Vec<WorkoutEntry>>, Self::Error> {
+        let mut entries: HashMap<i64, Vec<WorkoutEntry>> = HashMap::new();
+        for entry in get_entries_for_workouts(&self.0, workout_ids).await? {
+            entries.entry(entry.workout_id).or_default().push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+pub struct ExerciseById(SqlitePool);
+
+impl Loader<i64> for ExerciseById {
+    type Value = Exercise;
+    type Error = Arc<sqlx::Error>;
+
+    async fn load(&self, ids: &[i64]) -> Result<HashMap<i64, Exercise>, Self::Error> {
+        let exercises = get_exercises_by_ids(&self.0, ids).await?;
+        Ok(exercises.into_iter().map(|e| (e.id, e)).collect())
+    }
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    // The signed-in user
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<User> {
+        let pool = ctx.data::<SqlitePool>()?;
+        let auth = ctx.data::<AuthUser>()?;
+
+        get_user(pool, auth.user_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| async_graphql::Error::new("User not found"))
+    }
+
+    // One of your workouts; someone else's is null, as in the REST API's 404
+    async fn workout(&self, ctx: &Context<'_>, id: i64) -> async_graphql::Result<Option<Workout>> {
+        let pool = ctx.data::<SqlitePool>()?;
+        let auth = ctx.data::<AuthUser>()?;
+
+        let workout = get_workout_by_id(pool, id).await.map_err(internal_error)?;
+        Ok(workout.filter(|w| w.user_id == auth.user_id))
+    }
+}
+
+#[ComplexObject]
+impl User {
+    // Newest first
+    #[graphql(complexity = "limit.unwrap_or(DEFAULT_WORKOUTS).clamp(1, MAX_WORKOUTS) as usize * child_complexity")]
+    async fn workouts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Earliest date, YYYY-MM-DD")] from: Option<String>,
+        #[graphql(desc = "Latest date, YYYY-MM-DD")] to: Option<String>,
+        #[graphql(desc = "At most 100, default 20")] limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<Workout>> {
+        let pool = ctx.data::<SqlitePool>()?;
+        let limit = limit.unwrap_or(DEFAULT_WORKOUTS).clamp(1, MAX_WORKOUTS);
+
+        get_recent_workouts(pool, self.id, from.as_deref(), to.as_deref(), limit)
+            .await
+            .map_err(internal_error)
+    }
+}
+
+#[ComplexObject]
+impl Workout {
+    // In workout order; batched across every workout in the response
+    #[graphql(complexity = "ESTIMATED_ENTRIES * child_complexity")]
+    async fn entries(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<WorkoutEntry>> {
+        let loader = ctx.data::<DataLoader<EntriesByWorkout>>()?;
+        let entries = loader.load_one(self.id).await.map_err(internal_error)?;
+        Ok(entries.unwrap_or_default())
+    }
+}
+
+#[ComplexObject]
+impl WorkoutEntry {
+    async fn exercise(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<Exercise>> {
+        let loader = ctx.data::<DataLoader<ExerciseById>>()?;
+        loader.load_one(self.exercise_id).await.map_err(internal_error)
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::NaiveDateTime;
+use csv::{ReaderBuilder, StringRecord};
+use crate::db::analytics::parse_date;
+use crate::models::{
+    export::WeightUnit,
+    import::{ImportSource, ImportedSet},
+};
+
+// Sets parsed from an export file, plus how many rows were not sets we can store
+#[derive(Debug, Default)]
+pub struct ParsedImport {
+    pub sets: Vec<ImportedSet>,
+    pub skipped_rows: usize,
+}
+
+pub fn detect_source(csv: &str) -> Option<ImportSource> {
+    let header = csv.lines().next()?.to_lowercase();
+
+    if header.contains("exercise_title") && header.contains("start_time") {
+        Some(ImportSource::Hevy)
+    } else if header.contains("exercise name") && header.contains("set order") {
+        Some(ImportSource::Strong)
+    } else {
+        None
+    }
+}
+
+pub fn parse_import(
+    csv: &str,
+    source: ImportSource,
+    unit: WeightUnit,
+) -> Result<ParsedImport, String> {
+    // Older Strong versions export with semicolons
+    let first_line = csv.lines().next().unwrap_or_default();
+    let delimiter = if !first_line.contains(',') && first_line.contains(';') { b';' } else { b',' };
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_reader(csv.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Could not read CSV header: {}", e))?
+        .clone();
+
+    let columns = Columns::new(&headers);
+    let mut parsed = ParsedImport::default();
+
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Row {}: {}", index + 2, e))?;
+
+        let set = match source {
+            ImportSource::Strong => strong_set(&columns, &record, unit),
+            ImportSource::Hevy => hevy_set(&columns, &record),
+        }
+        .map_err(|e| format!("Row {}: {}", index + 2, e))?;
+
+        match set {
+            Some(set) => parsed.sets.push(set),
+            None => parsed.skipped_rows += 1,
+        }
+    }
+
+    Ok(parsed)
+}
+
+// Case-insensitive column lookup
+struct Columns(Vec<String>);
+
+impl Columns {
+    fn new(headers: &StringRecord) -> Self {
+        Columns(headers.iter().map(|h| h.trim().to_lowercase()).collect())
+    }
+
+    fn index(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|h| h == name)
+    }
+
+    fn get<'r>(&self, record: &'r StringRecord, name: &str) -> Option<&'r str> {
+        self.index(name)
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    fn require<'r>(&self, record: &'r StringRecord, name: &str) -> Result<&'r str, String> {
+        if self.index(name).is_none() {
+            return Err(format!("missing column '{}'", name));
+        }
+        self.get(record, name)
+            .ok_or_else(|| format!("empty value in column '{}'", name))
+    }
+}
+
+fn parse_number(value: Option<&str>, column: &str) -> Result<Option<f64>, String> {
+    value
+        .map(|v| {
+            v.replace(',', ".")
+                .parse::<f64>()
+                .map_err(|_| format!("'{}' in column '{}' is not a number", v, column))
+        })
+        .transpose()
+}
+
+// Rows without reps are cardio or timed sets, which entries can't hold
+fn parse_reps(value: Option<&str>) -> Result<Option<i32>, String> {
+    Ok(parse_number(value, "reps")?
+        .map(|r| r.round() as i32)
+        .filter(|&r| r > 0))
+}
+
+fn strong_set(
+    columns: &Columns,
+    record: &StringRecord,
+    unit: WeightUnit,
+) -> Result<Option<ImportedSet>, String> {
+    let date = columns.require(record, "date")?;
+    let exercise = columns.require(record, "exercise name")?;
+
+    // Newer exports mark warm-up sets with "W" in Set Order
+    if columns.get(record, "set order").is_some_and(|o| o.eq_ignore_ascii_case("w")) {
+        return Ok(None);
+    }
+
+    let Some(reps) = parse_reps(columns.get(record, "reps"))? else {
+        return Ok(None);
+    };
+
+    let day = parse_date(date).ok_or_else(|| format!("unrecognised date '{}'", date))?;
+    let workout_name = columns.get(record, "workout name").map(str::to_string);
+
+    Ok(Some(ImportedSet {
+        workout_key: format!("{}|{}", date, workout_name.as_deref().unwrap_or_default()),
+        date: day.to_string(),
+        workout_name,
+        workout_notes: columns.get(record, "workout notes").map(str::to_string),
+        exercise: exercise.to_string(),
+        weight: parse_number(columns.get(record, "weight"), "weight")?
+            .filter(|&w| w > 0.0)
+            .map(|w| unit.to_kg(w)),
+        reps,
+        rpe: parse_number(columns.get(record, "rpe"), "rpe")?,
+    }))
+}
+
+fn hevy_set(columns: &Columns, record: &StringRecord) -> Result<Option<ImportedSet>, String> {
+    let start_time = columns.require(record, "start_time")?;
+    let exercise = columns.require(record, "exercise_title")?;
+
+    if columns.get(record, "set_type").is_some_and(|t| t.eq_ignore_ascii_case("warmup")) {
+        return Ok(None);
+    }
+
+    let Some(reps) = parse_reps(columns.get(record, "reps"))? else {
+        return Ok(None);
+    };
+
+    // e.g. "1 May 2023, 18:32"; some exports use ISO timestamps instead
+    let day = NaiveDateTime::parse_from_str(start_time, "%d %b %Y, %H:%M")
+        .map(|t| t.date())
+        .ok()
+        .or_else(|| parse_date(start_time))
+        .ok_or_else(|| format!("unrecognised start_time '{}'", start_time))?;
+
+    let weight = match (columns.index("weight_kg"), columns.index("weight_lbs")) {
+        (Some(_), _) => parse_number(columns.get(record, "weight_kg"), "weight_kg")?,
+        (None, Some(_)) => parse_number(columns.get(record, "weight_lbs"), "weight_lbs")?
+            .map(|w| WeightUnit::Lb.to_kg(w)),
+        (None, None) => None,
+    };
+
+    let title = columns.get(record, "title").map(str::to_string);
+
+    Ok(Some(ImportedSet {
+        workout_key: format!("{}|{}", start_time, title.as_deref().unwrap_or_default()),
+        date: day.to_string(),
+        workout_name: title,
+        workout_notes: columns.get(record, "description").map(str::to_string),
+        exercise: exercise.to_string(),
+        weight: weight.filter(|&w| w > 0.0),
+        reps,
+        rpe: parse_number(columns.get(record, "rpe"), "rpe")?,
+    }))
+}
+
+// "Bench Press (Barbell)" and "bench press" both become "bench press"
+pub fn normalize_exercise_name(name: &str) -> String {
+    let base = match name.find('(') {
+        Some(i) if i > 0 => &name[..i],
+        _ => name,
+    };
+    base.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
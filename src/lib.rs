@@ -4,3 +4,10 @@ pub mod models;
 pub mod auth;
 pub mod jwt;
 pub mod middleware;
+pub mod import;
+pub mod report;
+pub mod backup;
+pub mod calendar;
+pub mod live;
+pub mod webhooks;
+pub mod graphql;
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::models::live::LiveEvent;
+
+// Events a slow client may fall behind by before it misses some
+const CHANNEL_CAPACITY: usize = 64;
+
+// Fans live events of a workout out to every device connected to it. Cheap to clone;
+// channels exist only while someone is connected.
+#[derive(Clone, Default)]
+pub struct LiveHub {
+    channels: Arc<Mutex<HashMap<i64, broadcast::Sender<LiveEvent>>>>,
+}
+
+impl LiveHub {
+    pub fn subscribe(&self, workout_id: i64) -> Subscription {
+        let mut channels = self.channels.lock().unwrap();
+        let receiver = channels
+            .entry(workout_id)
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe();
+
+        Subscription {
+            hub: self.clone(),
+            workout_id,
+            receiver: Some(receiver),
+        }
+    }
+
+    // Returns how many connections got the event; nobody listening is fine
+    pub fn publish(&self, workout_id: i64, event: LiveEvent) -> usize {
+        let channels = self.channels.lock().unwrap();
+        channels
+            .get(&workout_id)
+            .and_then(|sender| sender.send(event).ok())
+            .unwrap_or(0)
+    }
+
+    fn release(&self, workout_id: i64) {
+        let mut channels = self.channels.lock().unwrap();
+        if channels.get(&workout_id).is_some_and(|sender| sender.receiver_count() == 0) {
+            channels.remove(&workout_id);
+        }
+    }
+}
+
+// One connection's view of a workout's events; the channel goes away with its last subscription
+pub struct Subscription {
+    hub: LiveHub,
+    workout_id: i64,
+    receiver: Option<broadcast::Receiver<LiveEvent>>,
+}
+
+impl Subscription {
+    // None once the hub is gone. Events missed by a lagging client are skipped.
+    pub async fn recv(&mut self) -> Option<LiveEvent> {
+        let receiver = self.receiver.as_mut()?;
+        loop {
+            match receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Live client on workout {} missed {} events", self.workout_id, missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.receiver.take();
+        self.hub.release(self.workout_id);
+    }
+}
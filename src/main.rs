@@ -1,11 +1,7 @@
-mod api;
-mod db;
-mod models;
-pub mod auth;
-mod jwt;
-mod middleware;
 use axum::Router;
+use gymbuddy::{api, db, webhooks};
 use db::connection::establish_connection;
+use sqlx::SqlitePool;
 use tracing_subscriber::EnvFilter;
 
 #[tokio::main]
@@ -24,8 +20,17 @@ async fn main() {
         .await
         .expect("DB init failed");
 
-    let app: Router = api::routes::create_api_router()
-        .with_state(pool);
+    // `gymbuddy rollups rebuild|check` runs a maintenance command instead of the server
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let [command, action] = args.as_slice()
+        && command == "rollups"
+    {
+        std::process::exit(rollups_command(&pool, action).await);
+    }
+
+    webhooks::spawn_worker(pool.clone());
+
+    let app: Router = api::routes::create_app(pool);
 
     let addr = ([127, 0, 0, 1], 3000).into();
     tracing::info!("GymBuddy API running on http://{}", addr);
@@ -34,4 +39,39 @@ async fn main() {
         .serve(app.into_make_service())
         .await
         .unwrap();
-}
\ No newline at end of file
+}
+
+// Exit code: 0 when done or consistent, 1 when the check finds stale rows, 2 on bad usage
+async fn rollups_command(pool: &SqlitePool, action: &str) -> i32 {
+    match action {
+        "rebuild" => {
+            let rebuilt = db::rollups::rebuild_rollups(pool)
+                .await
+                .expect("Rollup rebuild failed");
+            println!("Rebuilt rollups: {} daily rows, {} weekly rows", rebuilt.daily, rebuilt.weekly);
+            0
+        }
+        "check" => {
+            let mismatches = db::rollups::check_rollups(pool)
+                .await
+                .expect("Rollup check failed");
+            for m in &mismatches {
+                println!(
+                    "{:?} {} user {} exercise {}: stored {:?}, expected {:?}",
+                    m.period, m.start, m.user_id, m.exercise_id, m.stored, m.expected
+                );
+            }
+            if mismatches.is_empty() {
+                println!("Rollups are consistent");
+                0
+            } else {
+                println!("{} rollup rows out of date; run `gymbuddy rollups rebuild`", mismatches.len());
+                1
+            }
+        }
+        _ => {
+            eprintln!("Usage: gymbuddy rollups rebuild|check");
+            2
+        }
+    }
+}
@@ -3,6 +3,8 @@ use axum::{
     extract::{FromRequestParts},
     http::{request::Parts, StatusCode},
 };
+use sqlx::SqlitePool;
+use crate::db::connection::is_admin;
 use crate::jwt::verify_jwt;
 
 pub struct AuthUser {
@@ -42,3 +44,30 @@ where
         }
     }
 }
+
+// Signed-in user with users.is_admin set
+pub struct AdminUser {
+    pub user_id: i64,
+}
+
+#[async_trait]
+impl FromRequestParts<SqlitePool> for AdminUser {
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        pool: &SqlitePool,
+    ) -> Result<Self, Self::Rejection> {
+        let AuthUser { user_id } = AuthUser::from_request_parts(parts, pool).await?;
+
+        let is_admin = is_admin(pool, user_id).await.map_err(|e| {
+            tracing::error!("Database error: {:?}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Database error")
+        })?;
+
+        match is_admin {
+            true => Ok(AdminUser { user_id }),
+            false => Err((StatusCode::FORBIDDEN, "Admin access required")),
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use axum::{
+    body::{boxed, Body, Full},
+    extract::State,
+    http::{header, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body::{LengthLimitError, Limited};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use crate::api::backup::MAX_BACKUP_BYTES;
+use crate::api::import::MAX_IMPORT_BYTES;
+use crate::api::versioning::ApiVersion;
+use crate::db::idempotency::{claim_key, complete_key, purge_expired_keys, release_key};
+use crate::jwt::verify_jwt;
+use crate::models::idempotency::IdempotencyRecord;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+const DEFAULT_WINDOW_HOURS: i64 = 24;
+const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024; // axum's own default
+
+// The creating POSTs that take an Idempotency-Key, relative to the API prefix (`:name` matches
+// any segment), with the body limit of each route. The body is buffered here, before the
+// route's own DefaultBodyLimit applies. Login, registration, user creation and calendar feeds
+// are left out: their responses carry credentials, which don't belong in idempotency_keys.
+pub const IDEMPOTENT_ROUTES: &[(&str, usize)] = &[
+    ("/exercises", DEFAULT_BODY_LIMIT),
+    ("/templates", DEFAULT_BODY_LIMIT),
+    ("/templates/:id/start", DEFAULT_BODY_LIMIT),
+    ("/workouts", DEFAULT_BODY_LIMIT),
+    ("/workouts/:id/groups", DEFAULT_BODY_LIMIT),
+    ("/workout_entries", DEFAULT_BODY_LIMIT),
+    ("/import", MAX_IMPORT_BYTES),
+    ("/sync/push", DEFAULT_BODY_LIMIT),
+    ("/webhooks", DEFAULT_BODY_LIMIT),
+    ("/schedule", DEFAULT_BODY_LIMIT),
+    ("/admin/restore", MAX_BACKUP_BYTES),
+];
+
+// How long a key is remembered, from IDEMPOTENCY_WINDOW_HOURS
+pub fn idempotency_window_secs() -> i64 {
+    let hours = std::env::var("IDEMPOTENCY_WINDOW_HOURS")
+        .ok()
+        .and_then(|v| v.parse::<i64>().ok())
+        .filter(|h| *h > 0)
+        .unwrap_or(DEFAULT_WINDOW_HOURS);
+
+    hours * 60 * 60
+}
+
+// Same method, path and body give the same fingerprint
+fn fingerprint(method: &Method, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b"\n");
+    hasher.update(path);
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn matches_route(route: &str, path: &str) -> bool {
+    let mut segments = path.split('/');
+    route.split('/').all(|expected| {
+        segments
+            .next()
+            .is_some_and(|s| expected == s || (expected.starts_with(':') && !s.is_empty()))
+    }) && segments.next().is_none()
+}
+
+// The body limit of an IDEMPOTENT_ROUTES path, under any API prefix
+fn idempotent_route(path: &str) -> Option<usize> {
+    let path = [ApiVersion::V1.prefix(), ApiVersion::V2.prefix(), "/api"]
+        .into_iter()
+        .find_map(|prefix| path.strip_prefix(prefix).filter(|rest| rest.starts_with('/')))?;
+
+    IDEMPOTENT_ROUTES
+        .iter()
+        .find(|(route, _)| matches_route(route, path))
+        .map(|(_, limit)| *limit)
+}
+
+// Keys are scoped per user; all idempotent routes need a token
+fn key_owner(request: &Request<Body>) -> Option<i64> {
+    request
+        .headers()
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .and_then(verify_jwt)
+}
+
+fn replay(record: IdempotencyRecord, status: StatusCode) -> Response {
+    let mut response = Response::new(boxed(Full::from(record.body.unwrap_or_default())));
+    *response.status_mut() = status;
+
+    let headers = response.headers_mut();
+    if let Some(content_type) = record.content_type.and_then(|c| HeaderValue::from_str(&c).ok()) {
+        headers.insert(header::CONTENT_TYPE, content_type);
+    }
+    headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+
+    response
+}
+
+fn database_error(e: sqlx::Error) -> Response {
+    tracing::error!("Database error: {:?}", e);
+    (StatusCode::INTERNAL_SERVER_ERROR, "Database error").into_response()
+}
+
+// Holds a claimed key until its response is stored. Dropped while still armed (the client went
+// away, the handler panicked or the response couldn't be stored), it frees the key so a retry
+// can run instead of getting 409 for the whole window.
+struct Claim {
+    pool: SqlitePool,
+    user_id: i64,
+    key: String,
+    armed: bool,
+}
+
+impl Claim {
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+
+    // Frees the key before answering, so an immediate retry isn't refused
+    async fn release(self) {
+        match release_key(&self.pool, self.user_id, &self.key).await {
+            Ok(()) => self.disarm(),
+            Err(e) => tracing::error!("Failed to release idempotency key: {:?}", e),
+        }
+    }
+}
+
+impl Drop for Claim {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+
+        let (pool, user_id, key) = (self.pool.clone(), self.user_id, std::mem::take(&mut self.key));
+        tokio::spawn(async move {
+            if let Err(e) = release_key(&pool, user_id, &key).await {
+                tracing::error!("Failed to release idempotency key: {:?}", e);
+            }
+        });
+    }
+}
+
+// Runs a keyed POST to a creating route at most once: retries with the same key and body get the stored response back
+pub async fn idempotency(
+    State(pool): State<SqlitePool>,
+    request: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    if request.method() != Method::POST {
+        return next.run(request).await;
+    }
+
+    let Some(limit) = idempotent_route(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+        _ => {
+            return (
+                StatusCode::BAD_REQUEST,
+                "Idempotency-Key must be 1 to 255 visible ASCII characters",
+            )
+                .into_response();
+        }
+    };
+
+    // A missing or bad token is rejected by the handler itself; nothing to remember
+    let Some(user_id) = key_owner(&request) else {
+        return next.run(request).await;
+    };
+
+    let (parts, body) = request.into_parts();
+    let body = match hyper::body::to_bytes(Limited::new(body, limit)).await {
+        Ok(body) => body,
+        Err(e) if e.is::<LengthLimitError>() => {
+            return (StatusCode::PAYLOAD_TOO_LARGE, "Request body is too large").into_response();
+        }
+        Err(_) => return (StatusCode::BAD_REQUEST, "Failed to read request body").into_response(),
+    };
+
+    let path = parts.uri.path_and_query().map_or("/", |p| p.as_str());
+    let fingerprint = fingerprint(&parts.method, path, &body);
+    let now = chrono::Utc::now().timestamp();
+
+    if let Err(e) = purge_expired_keys(&pool, now - idempotency_window_secs()).await {
+        return database_error(e);
+    }
+
+    match claim_key(&pool, user_id, &key, &fingerprint, now).await {
+        Err(e) => return database_error(e),
+        Ok(None) => {}
+        Ok(Some(record)) if record.fingerprint != fingerprint => {
+            return (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Idempotency-Key was already used for a different request",
+            )
+                .into_response();
+        }
+        Ok(Some(record)) => {
+            let status = record.status.and_then(|s| StatusCode::from_u16(s as u16).ok());
+            return match status {
+                Some(status) => {
+                    tracing::info!("Replaying idempotent request for user {}", user_id);
+                    replay(record, status)
+                }
+                None => (
+                    StatusCode::CONFLICT,
+                    "A request with this Idempotency-Key is still in progress",
+                )
+                    .into_response(),
+            };
+        }
+    }
+
+    let claim = Claim { pool: pool.clone(), user_id, key: key.clone(), armed: true };
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+
+    let (parts, body) = response.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to buffer response: {:?}", e);
+            claim.release().await;
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+
+    // Server errors are not an answer worth replaying; let the client try again
+    if parts.status.is_server_error() {
+        claim.release().await;
+    } else {
+        let content_type = parts
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok());
+        match complete_key(&pool, user_id, &key, parts.status.as_u16(), content_type, &body).await {
+            Ok(()) => claim.disarm(),
+            Err(e) => tracing::error!("Failed to store idempotent response: {:?}", e),
+        }
+    }
+
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
@@ -0,0 +1,64 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use super::{
+    analytics::VolumeTarget,
+    entry_group::EntryGroup,
+    exercise::Exercise,
+    progression::ProgressionRule,
+    recovery::RecoverySetting,
+    rest::RestDefault,
+    schedule::ScheduledSession,
+    template::WorkoutTemplateDetailed,
+    webhook::Webhook,
+    workout_entry::WorkoutEntry,
+};
+
+pub const ACCOUNT_EXPORT_VERSION: u32 = 1;
+
+// User row without the password hash
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct AccountProfile {
+    pub id: i64,
+    pub username: String,
+    pub email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ExportedWorkout {
+    pub id: i64,
+    pub date: String,
+    pub notes: Option<String>,
+    pub entries: Vec<WorkoutEntry>,
+    pub groups: Vec<EntryGroup>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ExportedProgressionRule {
+    pub exercise_id: i64,
+    pub rule: ProgressionRule,
+}
+
+// Everything stored about one user, returned by GET /api/me/export
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct AccountExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub user: AccountProfile,
+    pub workouts: Vec<ExportedWorkout>,
+    pub exercises: Vec<Exercise>, // shared exercises referenced by the user's data
+    pub templates: Vec<WorkoutTemplateDetailed>,
+    pub progression_rules: Vec<ExportedProgressionRule>,
+    pub volume_targets: Vec<VolumeTarget>,
+    pub recovery_settings: Vec<RecoverySetting>,
+    pub scheduled_sessions: Vec<ScheduledSession>,
+    pub rest_defaults: Vec<RestDefault>,
+    pub webhooks: Vec<Webhook>, // without their secrets
+}
+
+// Body of DELETE /api/me
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct DeleteAccount {
+    pub password: String,
+}
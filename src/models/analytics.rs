@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use serde::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::entry_group::GroupKind;
+
+// Query parameters of GET /api/analytics/fatigue
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FatigueQuery {
+    pub stall_sessions: Option<usize>,
+    pub acwr_threshold: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FatigueWarningKind {
+    Stall,
+    Regression,
+    WorkloadSpike,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct FatigueWarning {
+    pub kind: FatigueWarningKind,
+    pub exercise_id: Option<i64>,
+    pub exercise_name: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct DeloadSuggestion {
+    pub start_date: String,
+    pub end_date: String,
+    pub volume_reduction_percent: u32,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct FatigueReport {
+    pub acute_volume: f64,   // last 7 days
+    pub chronic_volume: f64, // weekly average over the last 28 days
+    pub acwr: Option<f64>,
+    pub acwr_threshold: f64,
+    pub warnings: Vec<FatigueWarning>,
+    pub suggested_deload: Option<DeloadSuggestion>,
+}
+
+// Weekly hard-set landmarks for one muscle group: minimum effective, maximum adaptive
+// and maximum recoverable volume
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct VolumeTarget {
+    pub muscle_group: String,
+    pub mev: i32,
+    pub mav: i32,
+    pub mrv: i32,
+}
+
+// Body of PUT /api/volume_targets/:muscle_group
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewVolumeTarget {
+    pub mev: i32,
+    pub mav: i32,
+    pub mrv: i32,
+}
+
+impl NewVolumeTarget {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mev < 0 || self.mev > self.mav || self.mav > self.mrv {
+            return Err("targets must satisfy 0 <= mev <= mav <= mrv".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeStatus {
+    Under,
+    InRange,
+    Over,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct MuscleWeekSets {
+    pub week_start: String,
+    pub sets: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct MuscleVolumeStatus {
+    pub muscle_group: String,
+    pub sets: i64, // hard sets this week
+    pub target: VolumeTarget,
+    pub status: VolumeStatus,
+    pub trend: Vec<MuscleWeekSets>, // oldest week first, ending with this week
+}
+
+// Query parameters of GET /api/analytics/muscle_volume
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MuscleVolumeQuery {
+    pub weeks: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct MuscleVolumeReport {
+    pub week_start: String,
+    pub weeks: u32,
+    pub muscles: Vec<MuscleVolumeStatus>,
+}
+
+// Totals of one workout. Sets inside a superset/circuit are per round, so they count once per round.
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct WorkoutTotals {
+    pub workout_id: i64,
+    pub date: String,
+    pub total_sets: i64,
+    pub total_reps: i64,
+    pub total_volume: f64, // sets x reps x weight; bodyweight entries count as 0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SummaryExercise {
+    pub name: String,
+    pub position: i32,
+    pub group_id: Option<i64>,
+    pub sets: i32, // including the group's rounds
+    pub reps: i32,
+    pub weight: f64,
+    pub volume: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SummaryGroup {
+    pub group_id: i64,
+    pub kind: GroupKind,
+    pub rounds: i32,
+    pub duration_seconds: Option<i32>,
+    pub exercises: Vec<String>, // names, in workout order
+    pub volume: f64,
+    pub density: Option<f64>, // volume per minute, when the duration is known
+}
+
+// GET /api/workouts/:id/summary
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct WorkoutSummary {
+    pub workout_id: i64,
+    pub total_sets: i64,
+    pub total_reps: i64,
+    pub total_volume: f64,
+    pub exercises: Vec<SummaryExercise>, // in workout order
+    pub groups: Vec<SummaryGroup>,
+}
+
+impl WorkoutSummary {
+    // A workout with nothing logged yet
+    pub fn empty(workout_id: i64) -> Self {
+        WorkoutSummary {
+            workout_id,
+            total_sets: 0,
+            total_reps: 0,
+            total_volume: 0.0,
+            exercises: Vec::new(),
+            groups: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct ExerciseVolume {
+    pub name: String,
+    pub volume: f64,
+}
+
+// One workout in GET /api/users/:id/progress
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct WorkoutProgress {
+    #[serde(flatten)]
+    pub totals: WorkoutTotals,
+    pub muscle_groups: BTreeMap<String, f64>, // volume per muscle group
+    pub top_exercises: Vec<ExerciseVolume>, // one per entry, highest volume first
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupPeriod {
+    Day,
+    #[default]
+    Week, // Monday-based, like the weekly analytics
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RollupGroup {
+    #[default]
+    Total,
+    Exercise,
+    Muscle,
+}
+
+// Query parameters of GET /api/analytics/rollups. `from` and `to` are inclusive YYYY-MM-DD
+// bounds on the start of each period.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RollupQuery {
+    pub period: Option<RollupPeriod>,
+    pub by: Option<RollupGroup>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+// One period of the rollups, for you overall, one exercise or one muscle group
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct RollupRow {
+    pub period: String, // the day, or the Monday of the week
+    pub exercise_id: Option<i64>,
+    pub exercise_name: Option<String>,
+    pub muscle_group: Option<String>,
+    pub entries: i64,
+    pub sets: i64, // sets x the group's rounds, as in the workout summary
+    pub reps: i64,
+    pub volume: f64,
+}
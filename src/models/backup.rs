@@ -0,0 +1,72 @@
+use std::collections::BTreeMap;
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use utoipa::{IntoParams, ToSchema};
+
+pub const BACKUP_FORMAT: &str = "gymbuddy-backup";
+
+// Bump whenever a table or column is added, so older archives are rejected instead of half-restored
+pub const BACKUP_SCHEMA_VERSION: u32 = 6;
+
+pub type BackupRow = Map<String, Value>;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BackupHeader {
+    pub format: String,
+    pub schema_version: u32,
+    pub created_at: String,
+}
+
+// The JSON archive: header fields plus every row, keyed by table name
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupArchive {
+    #[serde(flatten)]
+    pub header: BackupHeader,
+    pub tables: BTreeMap<String, Vec<BackupRow>>,
+}
+
+// Every NDJSON line after the header
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupLine {
+    pub table: String,
+    pub row: BackupRow,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BackupFormat {
+    #[default]
+    Json,
+    Ndjson,
+}
+
+// Query parameters of GET /api/admin/backup
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct BackupQuery {
+    #[serde(default)]
+    pub format: BackupFormat,
+}
+
+// Query parameters of POST /api/admin/restore
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RestoreQuery {
+    #[serde(default)]
+    pub replace: bool, // wipe existing data first instead of merging into it
+}
+
+#[derive(Serialize, Debug, PartialEq, ToSchema)]
+pub struct RestoredTable {
+    pub table: String,
+    pub restored: usize,
+    pub reused: usize, // exercises matched to an existing one by name
+    pub remapped: usize, // rows that got a new id because theirs was taken
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct RestoreReport {
+    pub schema_version: u32,
+    pub replaced: bool,
+    pub tables: Vec<RestoredTable>,
+}
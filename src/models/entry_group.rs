@@ -0,0 +1,46 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+#[derive(sqlx::Type, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[sqlx(rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum GroupKind {
+    Superset, // two exercises back to back
+    GiantSet, // three or more exercises back to back
+    Circuit,  // a loop of exercises repeated for several rounds
+}
+
+impl GroupKind {
+    pub fn min_entries(self) -> usize {
+        match self {
+            GroupKind::Superset => 2,
+            GroupKind::GiantSet => 3,
+            GroupKind::Circuit => 2,
+        }
+    }
+}
+
+// Entries in a group are performed back to back; `rounds` multiplies each entry's sets
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct EntryGroup {
+    pub id: i64,
+    pub workout_id: i64,
+    pub kind: GroupKind,
+    pub rounds: i32,
+    pub duration_seconds: Option<i32>, // time taken for all rounds, used for density
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewEntryGroup {
+    pub kind: GroupKind,
+    pub rounds: Option<i32>,
+    pub duration_seconds: Option<i32>,
+    pub entry_ids: Vec<i64>,
+}
+
+// Body of PUT /api/workouts/:id/order: every entry of the workout, in the new order
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct EntryOrder {
+    pub entry_ids: Vec<i64>,
+}
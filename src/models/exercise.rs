@@ -1,7 +1,9 @@
 use serde::{Serialize, Deserialize};
 use sqlx::FromRow;
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema, SimpleObject)]
 pub struct Exercise {
     pub id: i64,
     pub name: String,
@@ -9,7 +11,7 @@ pub struct Exercise {
     pub description: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct NewExercise {
     pub name: String,
     pub muscle_group: String,
@@ -0,0 +1,61 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WeightUnit {
+    #[default]
+    Kg,
+    Lb,
+}
+
+impl WeightUnit {
+    pub const LB_PER_KG: f64 = 2.204_622_6;
+
+    // Weights are stored in kg
+    pub fn from_kg(self, kg: f64) -> f64 {
+        let value = match self {
+            WeightUnit::Kg => kg,
+            WeightUnit::Lb => kg * Self::LB_PER_KG,
+        };
+        (value * 100.0).round() / 100.0
+    }
+
+    pub fn to_kg(self, value: f64) -> f64 {
+        match self {
+            WeightUnit::Kg => value,
+            WeightUnit::Lb => value / Self::LB_PER_KG,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WeightUnit::Kg => "kg",
+            WeightUnit::Lb => "lb",
+        }
+    }
+}
+
+// Query parameters of GET /api/export/workouts.csv; dates are inclusive YYYY-MM-DD
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub unit: WeightUnit,
+}
+
+// One entry of the history; workouts without entries have no exercise columns
+#[derive(FromRow, Debug)]
+pub struct ExportRow {
+    pub workout_id: i64,
+    pub date: String,
+    pub notes: Option<String>,
+    pub exercise: Option<String>,
+    pub muscle_group: Option<String>,
+    pub sets: Option<i32>,
+    pub reps: Option<i32>,
+    pub weight: Option<f64>,
+}
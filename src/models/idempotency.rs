@@ -0,0 +1,10 @@
+use sqlx::FromRow;
+
+// Stored outcome of a keyed request; status stays empty while the first attempt is running
+#[derive(FromRow, Debug)]
+pub struct IdempotencyRecord {
+    pub fingerprint: String,
+    pub status: Option<i64>,
+    pub content_type: Option<String>,
+    pub body: Option<Vec<u8>>,
+}
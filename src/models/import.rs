@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::export::WeightUnit;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportSource {
+    Strong,
+    Hevy,
+}
+
+// Query parameters of POST /api/import
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    pub source: Option<ImportSource>, // detected from the header when missing
+    #[serde(default)]
+    pub dry_run: bool,
+    pub unit: Option<WeightUnit>, // unit of Strong's Weight column; Hevy names it in the header
+}
+
+// One logged set from the other app, weight already converted to kg
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedSet {
+    pub workout_key: String,
+    pub date: String,
+    pub workout_name: Option<String>,
+    pub workout_notes: Option<String>,
+    pub exercise: String,
+    pub weight: Option<f64>,
+    pub reps: i32,
+    pub rpe: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MappingStatus {
+    Matched,
+    Created,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ExerciseMapping {
+    pub source_name: String,
+    pub exercise_id: Option<i64>, // None for exercises that a dry run would create
+    pub exercise_name: String,
+    pub status: MappingStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct ImportReport {
+    pub source: ImportSource,
+    pub dry_run: bool,
+    pub workouts: usize,
+    pub entries: usize,
+    pub sets: usize,
+    pub skipped_rows: usize, // warm-ups and rows without reps (cardio, timed holds)
+    pub exercises: Vec<ExerciseMapping>,
+}
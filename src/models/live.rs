@@ -0,0 +1,62 @@
+use serde::{Serialize, Deserialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::rest::RestTimer;
+use super::workout_entry::{WorkoutEntry, WorkoutEntryDetailed};
+
+// ?token= for clients that can't set headers on a WebSocket or EventSource (browsers)
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LiveQuery {
+    pub token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RestTimerAction {
+    Start,
+    Stop,
+}
+
+// Server -> client messages: WebSocket text frames and SSE events
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    // First WebSocket message after connecting: the workout as it is now
+    Snapshot {
+        workout_id: i64,
+        entries: Vec<WorkoutEntryDetailed>,
+    },
+    EntryCreated { entry: WorkoutEntry },
+    EntryUpdated { entry: WorkoutEntry },
+    EntryDeleted { id: i64 },
+    RestTimerStarted { timer: RestTimer },
+    RestTimerStopped { entry_id: i64, rest_seconds: i64 },
+    RestTimerExpired { entry_id: i64, seconds: i64 },
+}
+
+impl LiveEvent {
+    // Same as the serialized "type"; used as the SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            LiveEvent::Snapshot { .. } => "snapshot",
+            LiveEvent::EntryCreated { .. } => "entry_created",
+            LiveEvent::EntryUpdated { .. } => "entry_updated",
+            LiveEvent::EntryDeleted { .. } => "entry_deleted",
+            LiveEvent::RestTimerStarted { .. } => "rest_timer_started",
+            LiveEvent::RestTimerStopped { .. } => "rest_timer_stopped",
+            LiveEvent::RestTimerExpired { .. } => "rest_timer_expired",
+        }
+    }
+}
+
+// Client -> server messages over the WebSocket; timers run on the server
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    RestTimer {
+        action: RestTimerAction,
+        entry_id: Option<i64>,
+        seconds: Option<i64>,
+    },
+}
@@ -1,4 +1,22 @@
 pub mod user; // points to user.rs in models 
 pub mod workout;
 pub mod exercise;
-pub mod workout_entry;
\ No newline at end of file
+pub mod workout_entry;
+pub mod progression;
+pub mod template;
+pub mod analytics;
+pub mod recovery;
+pub mod entry_group;
+pub mod export;
+pub mod import;
+pub mod account;
+pub mod report;
+pub mod backup;
+pub mod schedule;
+pub mod pagination;
+pub mod patch;
+pub mod idempotency;
+pub mod sync;
+pub mod live;
+pub mod rest;
+pub mod webhook;
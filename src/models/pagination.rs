@@ -0,0 +1,60 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+pub const DEFAULT_PAGE_SIZE: u32 = 50;
+pub const MAX_PAGE_SIZE: u32 = 200;
+
+// Query parameters of GET /api/workouts; dates are inclusive YYYY-MM-DD
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WorkoutListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: Option<String>, // date, -date (default), id, -id
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub q: Option<String>, // text in the notes
+}
+
+// Query parameters of GET /api/workout_entries
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct EntryListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: Option<String>, // id (default), -id, date, -date
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub workout_id: Option<i64>,
+    pub exercise_id: Option<i64>,
+    pub muscle_group: Option<String>,
+    pub q: Option<String>, // text in the workout's notes
+}
+
+// Query parameters of GET /api/exercises
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExerciseListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: Option<String>, // id (default), -id, name, -name, muscle_group, -muscle_group
+    pub muscle_group: Option<String>,
+    pub q: Option<String>, // text in the name
+}
+
+// Query parameters of GET /api/users
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserListQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<u32>,
+    pub sort: Option<String>, // id (default), -id, username, -username
+    pub q: Option<String>, // text in the username
+}
+
+// One page of a list; next_cursor is None on the last page
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Deserializer};
+
+// For nullable fields in PATCH bodies: a missing field stays None, an explicit null
+// becomes Some(None). Use together with #[serde(default)].
+pub fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
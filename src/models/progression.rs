@@ -0,0 +1,86 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+// How the next session is derived from the last one, configurable per user and exercise
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum ProgressionRule {
+    // Add reps inside the range, add weight once the top of the range is reached
+    DoubleProgression {
+        rep_min: i32,
+        rep_max: i32,
+        weight_increment: f32,
+    },
+    // Add the same amount of weight every session unless the last one stalled
+    FixedIncrement {
+        weight_increment: f32,
+    },
+    // Adjust the load so the hardest set lands on the target RPE
+    RpeBased {
+        target_rpe: f32,
+        weight_increment: f32,
+    },
+}
+
+impl Default for ProgressionRule {
+    fn default() -> Self {
+        ProgressionRule::DoubleProgression {
+            rep_min: 8,
+            rep_max: 12,
+            weight_increment: 2.5,
+        }
+    }
+}
+
+impl ProgressionRule {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            ProgressionRule::DoubleProgression { rep_min, rep_max, weight_increment } => {
+                if rep_min <= 0 || rep_max < rep_min {
+                    return Err("rep_min must be positive and not above rep_max".into());
+                }
+                if weight_increment < 0.0 {
+                    return Err("weight_increment must not be negative".into());
+                }
+            }
+            ProgressionRule::FixedIncrement { weight_increment } => {
+                if weight_increment < 0.0 {
+                    return Err("weight_increment must not be negative".into());
+                }
+            }
+            ProgressionRule::RpeBased { target_rpe, weight_increment } => {
+                if !(1.0..=10.0).contains(&target_rpe) {
+                    return Err("target_rpe must be between 1 and 10".into());
+                }
+                if weight_increment <= 0.0 {
+                    return Err("weight_increment must be positive".into());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Top set of one past workout for a single exercise
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SessionSnapshot {
+    pub workout_id: i64,
+    pub date: String,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct NextSessionRecommendation {
+    pub exercise_id: i64,
+    pub exercise_name: String,
+    pub rule: ProgressionRule,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub reason: String,
+    pub last_session: Option<SessionSnapshot>,
+}
@@ -0,0 +1,58 @@
+use serde::{Serialize, Deserialize};
+use utoipa::ToSchema;
+
+// Per-user override of how fast a muscle group recovers
+#[derive(sqlx::FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct RecoverySetting {
+    pub muscle_group: String,
+    pub half_life_hours: f64,
+}
+
+// Body of PUT /api/recovery/half_lives/:muscle_group
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewRecoverySetting {
+    pub half_life_hours: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct MuscleRecovery {
+    pub muscle_group: String,
+    pub recovery_percent: f64,
+    pub fatigue: f64, // remaining hard-set equivalents after decay
+    pub half_life_hours: f64,
+    pub last_trained: Option<String>,
+    pub days_since_last: Option<i64>,
+    pub recent_sets: i64, // hard sets inside the decay window
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct RecoveryReport {
+    pub as_of: String,
+    pub muscles: Vec<MuscleRecovery>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct MuscleSuggestion {
+    pub muscle_group: String,
+    pub recovery_percent: f64,
+    pub days_since_last: Option<i64>,
+    pub typical_interval_days: f64,
+    pub score: f64,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TemplateSuggestion {
+    pub template_id: i64,
+    pub name: String,
+    pub muscle_groups: Vec<String>,
+    pub recovery_percent: f64,
+    pub score: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct TrainingSuggestions {
+    pub as_of: String,
+    pub muscle_groups: Vec<MuscleSuggestion>,
+    pub templates: Vec<TemplateSuggestion>,
+}
@@ -0,0 +1,53 @@
+use serde::{Serialize, Deserialize};
+use utoipa::IntoParams;
+use super::export::WeightUnit;
+
+// Query parameters of GET /api/reports/progress; dates are inclusive YYYY-MM-DD.
+// Without dates the report covers the last 30 days.
+#[derive(Deserialize, Debug, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub unit: WeightUnit,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct WeekVolume {
+    pub week_start: String,
+    pub workouts: i64,
+    pub volume: f64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct MuscleShare {
+    pub muscle_group: String,
+    pub volume: f64,
+    pub percent: f64,
+}
+
+// Best estimated 1RM of an exercise in the range, when it beats everything logged before it
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PersonalRecord {
+    pub exercise: String,
+    pub date: String,
+    pub weight: f64,
+    pub reps: i32,
+    pub estimated_one_rep_max: f64,
+    pub previous_best: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProgressReport {
+    pub username: String,
+    pub from: String,
+    pub to: String,
+    pub workouts: i64,
+    pub total_sets: i64,
+    pub total_reps: i64,
+    pub total_volume: f64,
+    pub weekly_volume: Vec<WeekVolume>,
+    pub muscle_distribution: Vec<MuscleShare>,
+    pub personal_records: Vec<PersonalRecord>,
+}
@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+// Used for exercises without a default of their own
+pub const DEFAULT_REST_SECONDS: i64 = 90;
+pub const MAX_REST_SECONDS: i64 = 60 * 60;
+
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct RestDefault {
+    pub exercise_id: i64,
+    pub seconds: i64,
+}
+
+// PUT /api/rest_defaults/:exercise_id
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SetRestDefault {
+    pub seconds: i64,
+}
+
+// POST /api/sessions/:workout_id/rest; without `seconds` the exercise's default is used
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct StartRestTimer {
+    pub entry_id: i64,
+    pub seconds: Option<i64>,
+}
+
+#[derive(FromRow, Debug, Clone, PartialEq)]
+pub struct RestTimerRow {
+    pub workout_id: i64,
+    pub entry_id: i64,
+    pub started_at: i64, // unix seconds
+    pub duration_seconds: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct RestTimer {
+    pub workout_id: i64,
+    pub entry_id: i64, // the set the rest follows
+    pub seconds: i64,
+    pub started_at: String,
+    pub ends_at: String,
+    pub remaining_seconds: i64,
+}
+
+impl RestTimer {
+    pub fn from_row(row: &RestTimerRow, now: i64) -> Self {
+        let timestamp = |secs: i64| {
+            DateTime::<Utc>::from_timestamp(secs, 0)
+                .unwrap_or_default()
+                .to_rfc3339()
+        };
+        let ends_at = row.started_at + row.duration_seconds;
+
+        RestTimer {
+            workout_id: row.workout_id,
+            entry_id: row.entry_id,
+            seconds: row.duration_seconds,
+            started_at: timestamp(row.started_at),
+            ends_at: timestamp(ends_at),
+            remaining_seconds: (ends_at - now).max(0),
+        }
+    }
+}
+
+// Rest actually taken, recorded on the entry when the timer stops
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct FinishedRest {
+    pub entry_id: i64,
+    pub rest_seconds: i64,
+}
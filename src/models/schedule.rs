@@ -0,0 +1,27 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct ScheduledSession {
+    pub id: i64,
+    pub user_id: i64,
+    pub date: String,
+    pub template_id: Option<i64>,
+    pub template_name: Option<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewScheduledSession {
+    pub date: String,
+    pub template_id: Option<i64>,
+    pub notes: Option<String>,
+}
+
+// Subscription address of the iCalendar feed; the token is the only credential
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CalendarFeed {
+    pub token: String,
+    pub path: String,
+}
@@ -0,0 +1,138 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+pub const DEFAULT_PULL_LIMIT: i64 = 500;
+pub const MAX_PULL_LIMIT: i64 = 2000;
+pub const MAX_PUSH_CHANGES: usize = 500;
+
+// GET /api/sync/pull?since=&limit=
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PullQuery {
+    pub since: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+// Workout as the sync API sees it: addressed by its client UUID
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SyncWorkout {
+    pub id: String,
+    pub server_id: i64,
+    pub date: String,
+    pub notes: Option<String>,
+    pub version: i64,
+    pub seq: i64,
+}
+
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct SyncEntry {
+    pub id: String,
+    pub server_id: i64,
+    pub workout_id: String, // UUID of the workout
+    pub exercise_id: i64,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+    pub position: i32,
+    pub version: i64,
+    pub seq: i64,
+}
+
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct Tombstone {
+    pub kind: String, // "workout" or "entry"
+    pub id: String,
+    pub seq: i64,
+}
+
+// Changes after `since`, up to `seq`; pass `seq` back as `since` next time
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SyncPull {
+    pub seq: i64,
+    pub has_more: bool,
+    pub workouts: Vec<SyncWorkout>,
+    pub entries: Vec<SyncEntry>,
+    pub deleted: Vec<Tombstone>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SyncWorkoutData {
+    pub date: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SyncEntryData {
+    pub workout_id: String,
+    pub exercise_id: i64,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+}
+
+// One offline change. `base_version` is the version the client last saw, absent for new records;
+// `deleted: true` removes the record, otherwise `data` is required.
+#[derive(Deserialize, Debug, ToSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncChange {
+    Workout {
+        id: String,
+        base_version: Option<i64>,
+        #[serde(default)]
+        deleted: bool,
+        data: Option<SyncWorkoutData>,
+    },
+    Entry {
+        id: String,
+        base_version: Option<i64>,
+        #[serde(default)]
+        deleted: bool,
+        data: Option<SyncEntryData>,
+    },
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct SyncPush {
+    pub changes: Vec<SyncChange>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct AppliedChange {
+    pub kind: &'static str,
+    pub id: String,
+    pub version: Option<i64>, // None once deleted
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictReason {
+    VersionMismatch, // someone changed the record since base_version
+    Deleted, // the record (or the entry's workout) was deleted on the server
+    Unavailable, // the UUID belongs to another account
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(untagged)]
+pub enum SyncRecord {
+    Workout(SyncWorkout),
+    Entry(SyncEntry),
+}
+
+// A change that was not applied; `server` is the copy that won
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SyncConflict {
+    pub kind: &'static str,
+    pub id: String,
+    pub reason: ConflictReason,
+    pub server: Option<SyncRecord>,
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct SyncPushResult {
+    pub seq: i64,
+    pub applied: Vec<AppliedChange>,
+    pub conflicts: Vec<SyncConflict>,
+}
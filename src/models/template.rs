@@ -0,0 +1,60 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use super::{progression::NextSessionRecommendation, workout::Workout};
+
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct WorkoutTemplate {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub notes: Option<String>,
+}
+
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema)]
+pub struct TemplateExercise {
+    pub id: i64,
+    pub template_id: i64,
+    pub exercise_id: i64,
+    pub exercise_name: String,
+    pub muscle_group: String,
+    pub position: i32,
+    pub sets: i32,
+    pub reps: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct WorkoutTemplateDetailed {
+    #[serde(flatten)]
+    pub template: WorkoutTemplate,
+    pub exercises: Vec<TemplateExercise>,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewTemplateExercise {
+    pub exercise_id: i64,
+    pub sets: i32,
+    pub reps: i32,
+}
+
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewWorkoutTemplate {
+    pub name: String,
+    pub notes: Option<String>,
+    pub exercises: Vec<NewTemplateExercise>,
+}
+
+// Body of POST /api/templates/:id/start
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct StartTemplate {
+    pub date: String,
+    pub notes: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct StartedWorkout {
+    pub workout: Workout,
+    pub template_id: i64,
+    pub recommendations: Vec<NextSessionRecommendation>,
+}
@@ -1,23 +1,39 @@
 use serde::{Serialize, Deserialize};
 use sqlx::FromRow;
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+#[derive(FromRow, Serialize, Deserialize, Debug, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct User {
     pub id: i64,
     pub username: String,
     pub email: String,
+    // Never sent to clients, and not selected by most queries
+    #[sqlx(default)]
+    #[serde(default, skip_serializing)]
+    #[graphql(skip)]
     pub password_hash: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct NewUser {
     pub username: String,
     pub email: String,
     pub password: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct LoginUser {
     pub email: String,
     pub password: String,
-}
\ No newline at end of file
+}
+
+// PATCH /api/users/:id; missing fields are left unchanged
+#[derive(Deserialize, Debug, Default, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserPatch {
+    pub username: Option<String>,
+    pub email: Option<String>,
+    pub password: Option<String>,
+}
@@ -0,0 +1,143 @@
+use serde::{Serialize, Deserialize};
+use sqlx::FromRow;
+use utoipa::{IntoParams, ToSchema};
+
+use super::workout::Workout;
+use super::workout_entry::WorkoutEntry;
+
+pub const WEBHOOK_EVENTS: [&str; 4] = ["workout.created", "workout.finished", "entry.created", "pr.achieved"];
+
+// A delivery is given up after this many failed attempts
+pub const MAX_DELIVERY_ATTEMPTS: i64 = 8;
+// Wait before the first retry; doubles after every further failure
+pub const RETRY_BASE_SECONDS: i64 = 30;
+
+pub const DEFAULT_LOG_LIMIT: i64 = 50;
+pub const MAX_LOG_LIMIT: i64 = 200;
+
+#[derive(FromRow, Debug, Clone)]
+pub struct WebhookRow {
+    pub id: i64,
+    pub user_id: i64,
+    pub url: String,
+    pub secret: String,
+    pub events: String, // comma separated
+    pub all_users: bool,
+    pub created_at: i64, // unix seconds
+}
+
+// A webhook as the API shows it; the secret is only returned once, on creation
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    pub events: Vec<String>,
+    pub all_users: bool,
+    pub created_at: i64,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        Webhook {
+            id: row.id,
+            url: row.url,
+            events: row.events.split(',').map(str::to_string).collect(),
+            all_users: row.all_users,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, ToSchema)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String, // HMAC-SHA256 key for X-GymBuddy-Signature
+}
+
+// POST /api/webhooks
+#[derive(Deserialize, Debug, ToSchema)]
+pub struct NewWebhook {
+    pub url: String,
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub all_users: bool, // admins only
+}
+
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: String,
+    pub payload: String, // the exact body that is signed and sent
+    pub status: String, // "pending", "delivered" or "failed"
+    pub attempts: i64,
+    pub next_attempt_at: Option<i64>, // unix seconds, while pending
+    pub response_status: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub delivered_at: Option<i64>,
+}
+
+// A due delivery together with where it goes
+#[derive(FromRow, Debug, Clone)]
+pub struct PendingDelivery {
+    pub id: i64,
+    pub event: String,
+    pub payload: String,
+    pub attempts: i64,
+    pub url: String,
+    pub secret: String,
+}
+
+// GET /api/webhooks/:id/deliveries
+#[derive(Deserialize, Debug, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeliveryLogQuery {
+    pub limit: Option<i64>,
+}
+
+// Personal record: the best estimated 1RM the user has logged for the exercise
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct PersonalRecordAchieved {
+    pub entry_id: i64,
+    pub workout_id: i64,
+    pub exercise_id: i64,
+    pub weight: f64,
+    pub reps: i32,
+    pub estimated_one_rep_max: f64,
+    pub previous_best: f64,
+}
+
+#[derive(Serialize, Debug, Clone, ToSchema)]
+#[serde(tag = "event", content = "data")]
+pub enum WebhookEvent {
+    #[serde(rename = "workout.created")]
+    WorkoutCreated(Workout),
+    #[serde(rename = "workout.finished")]
+    WorkoutFinished(Workout),
+    #[serde(rename = "entry.created")]
+    EntryCreated(WorkoutEntry),
+    #[serde(rename = "pr.achieved")]
+    PersonalRecord(PersonalRecordAchieved),
+}
+
+impl WebhookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WebhookEvent::WorkoutCreated(_) => "workout.created",
+            WebhookEvent::WorkoutFinished(_) => "workout.finished",
+            WebhookEvent::EntryCreated(_) => "entry.created",
+            WebhookEvent::PersonalRecord(_) => "pr.achieved",
+        }
+    }
+}
+
+// Body of every delivery: {"event", "data", "user_id", "occurred_at"}
+#[derive(Serialize, Debug)]
+pub struct WebhookPayload<'a> {
+    #[serde(flatten)]
+    pub event: &'a WebhookEvent,
+    pub user_id: i64,
+    pub occurred_at: String,
+}
@@ -1,18 +1,41 @@
 use serde::{Serialize, Deserialize};
 use sqlx::FromRow;
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+use super::patch::nullable;
+
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct Workout {
     pub id: i64,
     pub user_id: i64,
     pub date: String,
     pub notes: Option<String>,
+    pub version: i64, // Bumped on every change, used for ETags
+    pub finished_at: Option<String>, // Set by POST /api/workouts/:id/finish
+}
+
+// Rows a bulk write (offline sync, import) created, so their events go out once it commits
+#[derive(Debug, Default)]
+pub struct CreatedRecords {
+    pub workouts: Vec<i64>,
+    pub entries: Vec<i64>,
 }
 
 // Your existing NewWorkout
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct NewWorkout {
     pub user_id: i64,
     pub date: String,
     pub notes: Option<String>,
-}
\ No newline at end of file
+}
+
+// PATCH /api/workouts/:id; missing fields are left unchanged, `"notes": null` clears the notes
+#[derive(Deserialize, Debug, Default, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WorkoutPatch {
+    pub date: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub notes: Option<Option<String>>,
+}
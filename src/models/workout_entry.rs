@@ -1,7 +1,12 @@
 use serde::{Serialize, Deserialize};
 use sqlx::FromRow;
+use async_graphql::SimpleObject;
+use utoipa::ToSchema;
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+use super::patch::nullable;
+
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema, SimpleObject)]
+#[graphql(complex)]
 pub struct WorkoutEntry {
     pub id: i64,
     pub workout_id: i64,
@@ -9,18 +14,24 @@ pub struct WorkoutEntry {
     pub sets: i32,
     pub reps: i32,
     pub weight: Option<f32>,
+    pub rpe: Option<f32>, // Rate of perceived exertion (1-10) of the hardest set
+    pub position: i32, // Order inside the workout
+    pub group_id: Option<i64>, // Superset / giant set / circuit the entry belongs to
+    pub version: i64, // Bumped on every change, used for ETags
+    pub rest_seconds: Option<i32>, // Actual rest taken after this entry, from the rest timer
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, ToSchema)]
 pub struct NewWorkoutEntry {
     pub workout_id: i64,
     pub exercise_id: i64,
     pub sets: i32,
     pub reps: i32,
     pub weight: Option<f32>,
+    pub rpe: Option<f32>,
 }
 
-#[derive(FromRow, Serialize, Deserialize, Debug)]
+#[derive(FromRow, Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct WorkoutEntryDetailed { // Used to return more details than just id
     pub id: i64,
     pub workout_id: i64,
@@ -30,4 +41,96 @@ pub struct WorkoutEntryDetailed { // Used to return more details than just id
     pub sets: i32,
     pub reps: i32,
     pub weight: Option<f32>,
-}
\ No newline at end of file
+    pub rpe: Option<f32>,
+    pub position: i32,
+    pub group_id: Option<i64>,
+}
+
+// PATCH /api/workout_entries/:id; missing fields are left unchanged, null clears weight or rpe
+#[derive(Deserialize, Debug, Default, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct WorkoutEntryPatch {
+    pub workout_id: Option<i64>,
+    pub exercise_id: Option<i64>,
+    pub sets: Option<i32>,
+    pub reps: Option<i32>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub weight: Option<Option<f32>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub rpe: Option<Option<f32>>,
+}
+
+// API v2 shapes. v1 keeps the structs above unchanged; /api/v2 converts them with these From impls.
+
+// v2 drops `version`, which the ETag header already carries
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct WorkoutEntryV2 {
+    pub id: i64,
+    pub workout_id: i64,
+    pub exercise_id: i64,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+    pub position: i32,
+    pub group_id: Option<i64>,
+    pub rest_seconds: Option<i32>,
+}
+
+impl From<WorkoutEntry> for WorkoutEntryV2 {
+    fn from(entry: WorkoutEntry) -> Self {
+        WorkoutEntryV2 {
+            id: entry.id,
+            workout_id: entry.workout_id,
+            exercise_id: entry.exercise_id,
+            sets: entry.sets,
+            reps: entry.reps,
+            weight: entry.weight,
+            rpe: entry.rpe,
+            position: entry.position,
+            group_id: entry.group_id,
+            rest_seconds: entry.rest_seconds,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct EntryExercise {
+    pub id: i64,
+    pub name: String,
+    pub muscle_group: String,
+}
+
+// v2 nests the exercise instead of flattening its fields into the entry
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+pub struct WorkoutEntryDetailedV2 {
+    pub id: i64,
+    pub workout_id: i64,
+    pub exercise: EntryExercise,
+    pub sets: i32,
+    pub reps: i32,
+    pub weight: Option<f32>,
+    pub rpe: Option<f32>,
+    pub position: i32,
+    pub group_id: Option<i64>,
+}
+
+impl From<WorkoutEntryDetailed> for WorkoutEntryDetailedV2 {
+    fn from(entry: WorkoutEntryDetailed) -> Self {
+        WorkoutEntryDetailedV2 {
+            id: entry.id,
+            workout_id: entry.workout_id,
+            exercise: EntryExercise {
+                id: entry.exercise_id,
+                name: entry.exercise_name,
+                muscle_group: entry.muscle_group,
+            },
+            sets: entry.sets,
+            reps: entry.reps,
+            weight: entry.weight,
+            rpe: entry.rpe,
+            position: entry.position,
+            group_id: entry.group_id,
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write;
+use crate::models::{export::WeightUnit, report::ProgressReport};
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 200.0;
+const CHART_LABEL_HEIGHT: f64 = 20.0;
+const BAR_COLOR: &str = "#2f6fdf";
+
+const STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; color: #222; max-width: 760px; margin: 2em auto; padding: 0 1em; }
+h1 { margin-bottom: 0; }
+.period { color: #666; margin-top: 0.2em; }
+.stats { display: flex; gap: 1em; margin: 1.5em 0; }
+.stat { flex: 1; border: 1px solid #ddd; border-radius: 6px; padding: 0.8em; text-align: center; }
+.stat b { display: block; font-size: 1.5em; }
+table { width: 100%; border-collapse: collapse; }
+th, td { text-align: left; padding: 0.35em 0.5em; border-bottom: 1px solid #eee; }
+td.num, th.num { text-align: right; }
+.empty { color: #888; }
+section { page-break-inside: avoid; margin-bottom: 2em; }
+@page { size: A4; margin: 15mm; }
+@media print { body { margin: 0; max-width: none; } }
+";
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Whole numbers print without decimals, everything else with one
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 { format!("{:.0}", value) } else { format!("{:.1}", value) }
+}
+
+// Vertical bar chart; labels under every bar, values as hover titles
+fn bar_chart(bars: &[(String, f64)], unit: &str) -> String {
+    let max = bars.iter().map(|(_, v)| *v).fold(0.0, f64::max);
+    let slot = CHART_WIDTH / bars.len().max(1) as f64;
+    let bar_width = (slot * 0.7).max(1.0);
+    let label_every = (bars.len() / 12).max(1);
+
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" role=\"img\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT + CHART_LABEL_HEIGHT,
+    );
+
+    for (i, (label, value)) in bars.iter().enumerate() {
+        let height = if max > 0.0 { value / max * CHART_HEIGHT } else { 0.0 };
+        let x = i as f64 * slot + (slot - bar_width) / 2.0;
+        let _ = write!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\"><title>{}: {} {}</title></rect>",
+            x, CHART_HEIGHT - height, bar_width, height, BAR_COLOR, escape(label), number(*value), unit,
+        );
+        if i % label_every == 0 {
+            let _ = write!(
+                svg,
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"10\" text-anchor=\"middle\" fill=\"#666\">{}</text>",
+                i as f64 * slot + slot / 2.0, CHART_HEIGHT + 14.0, escape(label),
+            );
+        }
+    }
+
+    svg.push_str("</svg>");
+    svg
+}
+
+// Self-contained HTML document (no external assets), laid out to print cleanly to PDF
+pub fn render_progress_report(report: &ProgressReport, unit: WeightUnit) -> String {
+    let u = unit.as_str();
+    let mut html = String::new();
+
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Progress report for {name}</title>\n<style>{style}</style>\n</head>\n<body>\n\
+         <h1>Progress report for {name}</h1>\n<p class=\"period\">{from} to {to}</p>\n",
+        name = escape(&report.username),
+        style = STYLE,
+        from = report.from,
+        to = report.to,
+    );
+
+    let _ = writeln!(
+        html,
+        "<div class=\"stats\">\
+         <div class=\"stat\"><b>{}</b>workouts</div>\
+         <div class=\"stat\"><b>{}</b>sets</div>\
+         <div class=\"stat\"><b>{}</b>reps</div>\
+         <div class=\"stat\"><b>{}</b>{} volume</div>\
+         </div>",
+        report.workouts,
+        report.total_sets,
+        report.total_reps,
+        number(unit.from_kg(report.total_volume)),
+        u,
+    );
+
+    let weeks: Vec<(String, f64)> = report
+        .weekly_volume
+        .iter()
+        .map(|w| (w.week_start.get(5..).unwrap_or(&w.week_start).to_string(), unit.from_kg(w.volume)))
+        .collect();
+    let _ = write!(
+        html,
+        "<section>\n<h2>Weekly volume ({})</h2>\n{}\n</section>\n",
+        u,
+        bar_chart(&weeks, u),
+    );
+
+    html.push_str("<section>\n<h2>Muscle distribution</h2>\n");
+    if report.muscle_distribution.is_empty() {
+        html.push_str("<p class=\"empty\">No training logged in this period.</p>\n");
+    } else {
+        let _ = write!(
+            html,
+            "<table>\n<tr><th>Muscle group</th><th class=\"num\">Volume ({})</th><th class=\"num\">Share</th><th></th></tr>\n",
+            u,
+        );
+        for muscle in &report.muscle_distribution {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{:.1}%</td>\
+                 <td><svg xmlns=\"http://www.w3.org/2000/svg\" width=\"200\" height=\"10\"><rect width=\"{:.1}\" height=\"10\" fill=\"{}\"/></svg></td></tr>",
+                escape(&muscle.muscle_group),
+                number(unit.from_kg(muscle.volume)),
+                muscle.percent,
+                muscle.percent * 2.0,
+                BAR_COLOR,
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</section>\n");
+
+    html.push_str("<section>\n<h2>Personal records</h2>\n");
+    if report.personal_records.is_empty() {
+        html.push_str("<p class=\"empty\">No new personal records in this period.</p>\n");
+    } else {
+        let _ = write!(
+            html,
+            "<table>\n<tr><th>Date</th><th>Exercise</th><th class=\"num\">Set</th><th class=\"num\">Est. 1RM ({u})</th><th class=\"num\">Previous ({u})</th></tr>\n",
+            u = u,
+        );
+        for record in &report.personal_records {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{} {} &times; {}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+                record.date,
+                escape(&record.exercise),
+                number(unit.from_kg(record.weight)),
+                u,
+                record.reps,
+                number(unit.from_kg(record.estimated_one_rep_max)),
+                record.previous_best.map_or("&ndash;".to_string(), |p| number(unit.from_kg(p))),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</section>\n</body>\n</html>\n");
+
+    html
+}
@@ -0,0 +1,236 @@
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use hmac::{Hmac, Mac};
+use hyper::client::connect::dns::Name;
+use hyper::{client::HttpConnector, service::Service, Body, Client, Request, Uri};
+use hyper_rustls::{HttpsConnector, HttpsConnectorBuilder};
+use sha2::Sha256;
+use sqlx::SqlitePool;
+use crate::db::webhooks::{get_due_deliveries, mark_attempt_failed, mark_delivered};
+use crate::models::webhook::{PendingDelivery, MAX_DELIVERY_ATTEMPTS, RETRY_BASE_SECONDS};
+
+// The HTTP client deliveries go through, and whether it may reach loopback receivers
+#[derive(Clone)]
+pub struct WebhookClient {
+    http: Client<HttpsConnector<HttpConnector<TargetResolver>>>,
+    allow_loopback: bool,
+}
+
+// How often the worker looks for due deliveries
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+// Deliveries sent per round
+const BATCH_SIZE: i64 = 50;
+
+// Loopback receivers are refused unless WEBHOOK_ALLOW_LOOPBACK is set, for a stand-in
+// receiver on the same machine (tests, local development)
+pub fn allow_loopback() -> bool {
+    std::env::var("WEBHOOK_ALLOW_LOOPBACK").is_ok_and(|v| v == "1" || v == "true")
+}
+
+// Whether webhooks may reach an address. Private, link-local (cloud metadata), shared and
+// unspecified addresses are internal to wherever the server runs, so never.
+pub fn is_allowed_target(ip: IpAddr, allow_loopback: bool) -> bool {
+    let ip = match ip {
+        IpAddr::V6(v6) => v6.to_ipv4_mapped().map_or(IpAddr::V6(v6), IpAddr::V4),
+        ip => ip,
+    };
+    if ip.is_loopback() {
+        return allow_loopback;
+    }
+
+    match ip {
+        IpAddr::V4(v4) => {
+            let [first, second, ..] = v4.octets();
+            !(v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || (first == 100 && second & 0xc0 == 64)) // 100.64.0.0/10
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            !(v6.is_unspecified()
+                || v6.is_multicast()
+                || first & 0xfe00 == 0xfc00 // unique local
+                || first & 0xffc0 == 0xfe80) // link-local
+        }
+    }
+}
+
+fn literal_ip(uri: &Uri) -> Option<IpAddr> {
+    uri.host()?.trim_start_matches('[').trim_end_matches(']').parse().ok()
+}
+
+// Refuses a webhook url whose host is, or resolves to, an address webhooks may not reach.
+// A host that doesn't resolve yet is let through: the worker checks again on every delivery.
+pub async fn check_target(uri: &Uri, allow_loopback: bool) -> Result<(), String> {
+    let refused = |ip: IpAddr| Err(format!("Webhooks can't be sent to {}", ip));
+
+    if let Some(ip) = literal_ip(uri) {
+        return match is_allowed_target(ip, allow_loopback) {
+            true => Ok(()),
+            false => refused(ip),
+        };
+    }
+
+    let Some(host) = uri.host() else {
+        return Ok(());
+    };
+    let Ok(addrs) = tokio::net::lookup_host((host, uri.port_u16().unwrap_or(443))).await else {
+        return Ok(());
+    };
+    for addr in addrs {
+        if !is_allowed_target(addr.ip(), allow_loopback) {
+            return refused(addr.ip());
+        }
+    }
+
+    Ok(())
+}
+
+// Resolves receiver hosts for the client, keeping only addresses webhooks may reach, so a
+// name can't be pointed at an internal address after it was registered
+#[derive(Clone)]
+pub struct TargetResolver {
+    allow_loopback: bool,
+}
+
+impl Service<Name> for TargetResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        let allow_loopback = self.allow_loopback;
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_allowed_target(addr.ip(), allow_loopback))
+                .collect();
+            if addrs.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!("{} has no address webhooks may reach", name),
+                ));
+            }
+            Ok(addrs.into_iter())
+        })
+    }
+}
+
+pub fn webhook_client(allow_loopback: bool) -> WebhookClient {
+    let mut http = HttpConnector::new_with_resolver(TargetResolver { allow_loopback });
+    http.enforce_http(false);
+
+    let https = HttpsConnectorBuilder::new()
+        .with_webpki_roots()
+        .https_or_http()
+        .enable_http1()
+        .wrap_connector(http);
+
+    WebhookClient { http: Client::builder().build(https), allow_loopback }
+}
+
+// Hex HMAC-SHA256 of "{timestamp}.{body}". Receivers recompute it with the webhook's secret
+// and should reject old timestamps to stop replays.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// When to try again after `attempts` failures (30s, 1m, 2m, ...), or None once they are used up
+pub fn next_retry_at(attempts: i64, now: i64) -> Option<i64> {
+    if attempts >= MAX_DELIVERY_ATTEMPTS {
+        return None;
+    }
+    Some(now + RETRY_BASE_SECONDS * (1 << (attempts - 1).clamp(0, 30)))
+}
+
+// POSTs the payload; Ok with the status code on 2xx, Err with what went wrong otherwise
+async fn send(
+    client: &WebhookClient,
+    delivery: &PendingDelivery,
+    now: i64,
+) -> Result<u16, (Option<u16>, String)> {
+    let uri: Uri = delivery.url.parse().map_err(|e: hyper::http::uri::InvalidUri| (None, e.to_string()))?;
+    // The resolver only sees names; addresses written into the url are checked here
+    if let Some(ip) = literal_ip(&uri)
+        && !is_allowed_target(ip, client.allow_loopback)
+    {
+        return Err((None, format!("Webhooks can't be sent to {}", ip)));
+    }
+
+    let request = Request::post(uri)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "GymBuddy-Webhooks")
+        .header("X-GymBuddy-Event", &delivery.event)
+        .header("X-GymBuddy-Delivery", delivery.id)
+        .header("X-GymBuddy-Timestamp", now)
+        .header(
+            "X-GymBuddy-Signature",
+            format!("sha256={}", sign(&delivery.secret, now, &delivery.payload)),
+        )
+        .body(Body::from(delivery.payload.clone()))
+        .map_err(|e| (None, e.to_string()))?;
+
+    let response = tokio::time::timeout(REQUEST_TIMEOUT, client.http.request(request))
+        .await
+        .map_err(|_| (None, "Timed out".to_string()))?
+        .map_err(|e| (None, e.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(status.as_u16())
+    } else {
+        Err((Some(status.as_u16()), format!("Receiver answered {}", status)))
+    }
+}
+
+// Sends every delivery due at `now` once and records the outcome. Returns how many were sent.
+pub async fn deliver_due(pool: &SqlitePool, client: &WebhookClient, now: i64) -> Result<usize, sqlx::Error> {
+    let due = get_due_deliveries(pool, now, BATCH_SIZE).await?;
+
+    for delivery in &due {
+        match send(client, delivery, now).await {
+            Ok(status) => mark_delivered(pool, delivery.id, status, now).await?,
+            Err((status, error)) => {
+                let retry_at = next_retry_at(delivery.attempts + 1, now);
+                tracing::warn!("Webhook delivery {} failed: {}", delivery.id, error);
+                mark_attempt_failed(pool, delivery.id, status, &error, retry_at).await?;
+            }
+        }
+    }
+
+    Ok(due.len())
+}
+
+// Works through the delivery queue in the background, as long as the server runs
+pub fn spawn_worker(pool: SqlitePool) {
+    tokio::spawn(async move {
+        let client = webhook_client(allow_loopback());
+
+        loop {
+            let now = chrono::Utc::now().timestamp();
+            match deliver_due(&pool, &client, now).await {
+                // A full batch means more may be waiting
+                Ok(sent) if sent as i64 == BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => tracing::error!("Database error: {:?}", e),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
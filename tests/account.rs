@@ -0,0 +1,62 @@
+use axum::{http::StatusCode, Router};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use gymbuddy::auth::hash_password;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    let users = format!(
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', '{}'), ('other', 'other@example.com', 'x')",
+        hash_password("secret")
+    );
+    common::setup(name, &[
+        &users,
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date, notes) VALUES (1, '2024-01-01', 'Push'), (2, '2024-01-02', NULL)",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100), (2, 2, 5, 5, 140)",
+        "INSERT INTO muscle_volume_targets (user_id, muscle_group, mev, mav, mrv) VALUES (1, 'Chest', 8, 14, 20)",
+    ])
+    .await
+}
+
+#[tokio::test]
+async fn exports_everything_owned_by_the_user() {
+    let (app, _pool) = setup("account_export").await;
+
+    let (status, archive) = send(&app, "GET", "/api/me/export", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(archive["version"], 1);
+    assert_eq!(archive["user"]["username"], "lifter");
+    assert!(archive["user"].get("password_hash").is_none());
+
+    let workouts = archive["workouts"].as_array().unwrap();
+    assert_eq!(workouts.len(), 1);
+    assert_eq!(workouts[0]["entries"][0]["weight"], 100.0);
+    assert_eq!(archive["exercises"].as_array().unwrap().len(), 1);
+    assert_eq!(archive["volume_targets"][0]["muscle_group"], "Chest");
+}
+
+#[tokio::test]
+async fn deletes_account_only_with_correct_password() {
+    let (app, pool) = setup("account_delete").await;
+
+    let (status, _) = send(&app, "DELETE", "/api/me", Some(json!({ "password": "wrong" }))).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, _) = send(&app, "DELETE", "/api/me", Some(json!({ "password": "secret" }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    for (query, expected) in [
+        ("SELECT COUNT(*) FROM users", 1),
+        ("SELECT COUNT(*) FROM workouts", 1),
+        ("SELECT COUNT(*) FROM workout_entries", 1),
+        ("SELECT COUNT(*) FROM muscle_volume_targets", 0),
+        ("SELECT COUNT(*) FROM exercises", 2),
+    ] {
+        let (count,): (i64,) = sqlx::query_as(query).fetch_one(&pool).await.unwrap();
+        assert_eq!(count, expected, "{}", query);
+    }
+}
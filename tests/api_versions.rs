@@ -0,0 +1,90 @@
+use axum::{
+    http::{HeaderMap, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+
+use gymbuddy::api::versioning::{v1_is_gone, V1_SUNSET_AT};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs'), ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, position) VALUES (1, 1, 3, 5, 100, 1), (1, 2, 3, 8, 60, 2)",
+    ])
+    .await
+    .0
+}
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, HeaderMap, Value) {
+    let request = common::request(method, uri, Some(1))
+        .header("Content-Type", "application/json")
+        .body(common::json_body(body))
+        .unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    (status, headers, common::json(&bytes))
+}
+
+#[tokio::test]
+async fn v1_is_deprecated_and_unversioned_paths_are_v1() {
+    let app = setup("versions_v1").await;
+
+    for uri in ["/api/v1/workout_entries/1", "/api/workout_entries/1"] {
+        let (status, headers, entry) = send(&app, "GET", uri, None).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!((entry["version"].as_i64(), entry["weight"].as_f64()), (Some(1), Some(100.0)));
+
+        assert_eq!(headers["deprecation"], "@1792368000");
+        assert_eq!(headers["sunset"], "Fri, 30 Apr 2027 00:00:00 GMT");
+    }
+
+    let (_, _, entries) = send(&app, "GET", "/api/v1/workouts/1/entries", None).await;
+    assert_eq!(entries[0]["exercise_name"], "Squat");
+
+    // Errors carry the headers too
+    let (status, headers, _) = send(&app, "GET", "/api/v1/workout_entries/99", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(headers.contains_key("sunset"));
+
+    // Switched off at the Sunset header's date
+    let sunset = chrono::DateTime::parse_from_rfc2822(headers["sunset"].to_str().unwrap()).unwrap();
+    assert_eq!(sunset.timestamp(), V1_SUNSET_AT);
+    assert!(!v1_is_gone(V1_SUNSET_AT - 1) && v1_is_gone(V1_SUNSET_AT));
+}
+
+#[tokio::test]
+async fn v2_reshapes_workout_entries() {
+    let app = setup("versions_v2").await;
+
+    let (status, headers, entry) = send(&app, "GET", "/api/v2/workout_entries/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(entry.get("version").is_none());
+    assert_eq!(entry["exercise_id"], 1);
+    assert!(headers.contains_key("etag"));
+    assert!(!headers.contains_key("deprecation"));
+
+    let (_, _, entries) = send(&app, "GET", "/api/v2/workouts/1/entries", None).await;
+    assert_eq!(entries[1]["exercise"], json!({ "id": 2, "name": "Bench Press", "muscle_group": "Chest" }));
+    assert!(entries[1].get("exercise_name").is_none());
+
+    // Paging links stay inside v2
+    let (_, headers, page) = send(&app, "GET", "/api/v2/workout_entries?limit=1", None).await;
+    assert_eq!(page[0]["exercise"]["name"], "Squat");
+    assert!(headers["link"].to_str().unwrap().starts_with("</api/v2/workout_entries?limit=1&cursor="));
+
+    let set = json!({ "workout_id": 1, "exercise_id": 2, "sets": 1, "reps": 10, "weight": 50 });
+    let (_, _, created) = send(&app, "POST", "/api/v2/workout_entries", Some(set)).await;
+    assert_eq!(created["status"], "success");
+    assert_eq!(created["entry"]["reps"], 10);
+    assert!(created["entry"].get("version").is_none());
+
+    // Anything that isn't an entry passes through as in v1
+    let (status, _, message) = send(&app, "DELETE", "/api/v2/workout_entries/3", None).await;
+    assert_eq!((status, message.as_str()), (StatusCode::OK, Some("Workout entry with id 3 deleted")));
+    let (status, _, _) = send(&app, "GET", "/api/v2/workout_entries/3", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,115 @@
+use axum::{body::Body, http::StatusCode, Router};
+use serde_json::Value;
+
+use gymbuddy::db::backup::{BACKUP_TABLES, SKIPPED_TABLES};
+
+mod common;
+use common::setup;
+
+const SOURCE: &[&str] = &[
+    "INSERT INTO users (username, email, password_hash, is_admin) VALUES ('admin', 'admin@example.com', 'x', 1), ('lifter', 'lifter@example.com', 'x', 0)",
+    "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+    "INSERT INTO workouts (user_id, date) VALUES (2, '2024-03-01')",
+    "INSERT INTO entry_groups (workout_id, kind, rounds) VALUES (1, 'superset', 3)",
+    "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, group_id) VALUES (1, 2, 3, 5, 140, 1)",
+    "INSERT INTO muscle_volume_targets (user_id, muscle_group, mev, mav, mrv) VALUES (2, 'Legs', 8, 14, 20)",
+];
+
+async fn send(app: &Router, method: &str, uri: &str, user_id: i64, body: String) -> (StatusCode, String) {
+    let request = common::request(method, uri, Some(user_id)).body(Body::from(body)).unwrap();
+    let (status, _, bytes) = common::call(app, request).await;
+    (status, common::text(bytes))
+}
+
+#[tokio::test]
+async fn round_trips_into_existing_database_with_remapped_ids() {
+    let (source, source_pool) = setup("backup_source", SOURCE).await;
+
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'"
+    )
+    .fetch_all(&source_pool)
+    .await
+    .unwrap();
+    for (table,) in tables {
+        let covered = BACKUP_TABLES.iter().any(|t| t.name == table) || SKIPPED_TABLES.contains(&table.as_str());
+        assert!(covered, "{} is not backed up", table);
+    }
+
+    let (status, _) = send(&source, "GET", "/api/admin/backup", 2, String::new()).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    let (status, ndjson) = send(&source, "GET", "/api/admin/backup?format=ndjson", 1, String::new()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(ndjson.lines().next().unwrap().contains("\"schema_version\":6"));
+
+    // Target already has its own user and workout occupying id 1, and a Squat exercise
+    let (target, pool) = setup("backup_target", &[
+        "INSERT INTO users (username, email, password_hash, is_admin) VALUES ('root', 'root@example.com', 'x', 1)",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2023-01-01')",
+    ]).await;
+
+    let (status, body) = send(&target, "POST", "/api/admin/restore", 1, ndjson).await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    let report: Value = serde_json::from_str(&body).unwrap();
+    let exercises = report["tables"].as_array().unwrap().iter().find(|t| t["table"] == "exercises").unwrap();
+    assert_eq!((exercises["restored"].as_i64(), exercises["reused"].as_i64()), (Some(1), Some(1)));
+
+    let (username, date, exercise, muscle, rounds): (String, String, String, String, i64) = sqlx::query_as(
+        "
+        SELECT u.username, w.date, e.name, t.muscle_group, g.rounds
+        FROM workout_entries we
+        JOIN workouts w ON we.workout_id = w.id
+        JOIN users u ON w.user_id = u.id
+        JOIN exercises e ON we.exercise_id = e.id
+        JOIN entry_groups g ON we.group_id = g.id
+        JOIN muscle_volume_targets t ON t.user_id = u.id
+        "
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        (username.as_str(), date.as_str(), exercise.as_str(), muscle.as_str(), rounds),
+        ("lifter", "2024-03-01", "squat", "Legs", 3)
+    );
+
+    // Restoring the same archive again would duplicate login emails
+    let (_, json) = send(&source, "GET", "/api/admin/backup", 1, String::new()).await;
+    let (status, body) = send(&target, "POST", "/api/admin/restore", 1, json).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert!(body.contains("lifter@example.com"), "{}", body);
+}
+
+#[tokio::test]
+async fn replace_checks_the_version_and_leaves_no_stale_queues() {
+    let (app, pool) = setup("backup_version", SOURCE).await;
+
+    let (_, json) = send(&app, "GET", "/api/admin/backup", 1, String::new()).await;
+    let mut archive: Value = serde_json::from_str(&json).unwrap();
+    archive["schema_version"] = Value::from(99);
+
+    let (status, body) = send(&app, "POST", "/api/admin/restore?replace=true", 1, archive.to_string()).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert!(body.contains("schema version 99"));
+
+    let (users,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM users").fetch_one(&pool).await.unwrap();
+    assert_eq!(users, 2);
+
+    // A pending delivery would otherwise go to whichever restored webhook gets its id
+    for query in [
+        "INSERT INTO webhooks (user_id, url, secret, events, created_at) VALUES (2, 'https://example.com/hook', 's', '[]', 0)",
+        "INSERT INTO webhook_deliveries (webhook_id, event, payload, next_attempt_at, created_at) VALUES (1, 'workout.created', '{}', 0, 0)",
+        "INSERT INTO rest_timers (workout_id, entry_id, started_at, duration_seconds) VALUES (1, 1, 0, 90)",
+        "INSERT INTO idempotency_keys (user_id, key, fingerprint, status, created_at) VALUES (2, 'k', 'f', 200, 0)",
+    ] {
+        sqlx::query(query).execute(&pool).await.unwrap();
+    }
+
+    let (status, _) = send(&app, "POST", "/api/admin/restore?replace=true", 1, json).await;
+    assert_eq!(status, StatusCode::OK);
+    for table in ["webhook_deliveries", "rest_timers", "idempotency_keys"] {
+        assert_eq!(common::count(&pool, table).await, 0, "{}", table);
+    }
+}
@@ -0,0 +1,80 @@
+use axum::{
+    http::{header, StatusCode},
+    Router,
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    let yesterday = (Utc::now().date_naive() - Duration::days(1)).to_string();
+    let workout = format!("INSERT INTO workouts (user_id, date, notes) VALUES (1, '{}', 'Felt strong; new PR')", yesterday);
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest')",
+        "INSERT INTO workout_templates (user_id, name) VALUES (1, 'Push Day'), (2, 'Not yours')",
+        &workout,
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100)",
+    ])
+    .await
+    .0
+}
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Option<String>, String) {
+    // The feed itself is fetched with its token instead of a JWT
+    let user_id = if uri.ends_with(".ics") { None } else { Some(1) };
+    let request = common::request(method, uri, user_id)
+        .header("Content-Type", "application/json")
+        .body(common::json_body(body))
+        .unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    let content_type = headers.get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    (status, content_type, common::text(bytes))
+}
+
+#[tokio::test]
+async fn feed_lists_completed_and_planned_workouts_without_jwt() {
+    let app = setup("calendar_feed_events").await;
+    let next_week = Utc::now().date_naive() + Duration::days(7);
+
+    let (status, _, _) = send(&app, "POST", "/api/schedule", Some(json!({ "date": next_week.to_string(), "template_id": 2 }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _, _) = send(&app, "POST", "/api/schedule", Some(json!({ "date": next_week.to_string(), "template_id": 1 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, _, body) = send(&app, "POST", "/api/calendar/feed", None).await;
+    let feed: Value = serde_json::from_str(&body).unwrap();
+    let path = feed["path"].as_str().unwrap().to_string();
+
+    let (status, content_type, ics) = send(&app, "GET", &path, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/calendar; charset=utf-8"));
+    assert!(ics.starts_with("BEGIN:VCALENDAR\r\n") && ics.ends_with("END:VCALENDAR\r\n"));
+    assert!(ics.lines().all(|line| line.len() <= 75));
+
+    // Unfold continuation lines before looking at the content
+    let unfolded = ics.replace("\r\n ", "");
+    assert!(unfolded.contains("UID:workout-1@gymbuddy"));
+    assert!(unfolded.contains("Bench Press: 3 x 5 @ 100 kg"));
+    assert!(unfolded.contains(r"Felt strong\; new PR"));
+    assert!(unfolded.contains(&format!("DTSTART;VALUE=DATE:{}", next_week.format("%Y%m%d"))));
+    assert!(unfolded.contains("SUMMARY:Planned: Push Day"));
+}
+
+#[tokio::test]
+async fn rotating_the_token_disables_the_old_feed() {
+    let app = setup("calendar_feed_rotate").await;
+
+    let (_, _, body) = send(&app, "POST", "/api/calendar/feed", None).await;
+    let old: Value = serde_json::from_str(&body).unwrap();
+    send(&app, "POST", "/api/calendar/feed", None).await;
+
+    let (status, _, _) = send(&app, "GET", old["path"].as_str().unwrap(), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _, _) = send(&app, "DELETE", "/api/calendar/feed", None).await;
+    assert_eq!(status, StatusCode::OK);
+}
@@ -0,0 +1,97 @@
+// Setup shared by the integration tests. Seed data stays in each test file.
+#![allow(dead_code)] // each test binary uses only part of it
+
+use axum::{
+    body::{Body, Bytes},
+    http::{request, HeaderMap, Request, StatusCode},
+    Router,
+};
+use serde_json::Value;
+use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
+use tower::ServiceExt;
+
+use gymbuddy::api::routes::create_api_router;
+use gymbuddy::db::connection::init_db;
+use gymbuddy::jwt::generate_jwt;
+
+// A fresh in-memory database named after the test, with the schema and `seed` applied
+pub async fn test_pool(name: &str, seed: &[&str]) -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .connect(&format!("sqlite:file:{}?mode=memory&cache=shared", name))
+        .await
+        .unwrap();
+    init_db(&pool).await.unwrap();
+
+    for query in seed {
+        sqlx::query(query).execute(&pool).await.unwrap();
+    }
+
+    pool
+}
+
+// The API router over a fresh database, and the pool for checking what it wrote
+pub async fn setup(name: &str, seed: &[&str]) -> (Router, SqlitePool) {
+    let pool = test_pool(name, seed).await;
+    (create_api_router().with_state(pool.clone()), pool)
+}
+
+pub fn bearer(user_id: i64) -> String {
+    format!("Bearer {}", generate_jwt(user_id))
+}
+
+// A request signed in as `user_id`, or anonymous with None
+pub fn request(method: &str, uri: &str, user_id: Option<i64>) -> request::Builder {
+    let request = Request::builder().method(method).uri(uri);
+    match user_id {
+        Some(user_id) => request.header("Authorization", bearer(user_id)),
+        None => request,
+    }
+}
+
+pub fn json_body(body: Option<Value>) -> Body {
+    body.map_or_else(Body::empty, |b| Body::from(b.to_string()))
+}
+
+pub async fn call(app: &Router, request: Request<Body>) -> (StatusCode, HeaderMap, Bytes) {
+    let response = app.clone().oneshot(request).await.unwrap();
+    let (status, headers) = (response.status(), response.headers().clone());
+    let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    (status, headers, bytes)
+}
+
+// Null when the body is empty or not JSON
+pub fn json(bytes: &[u8]) -> Value {
+    serde_json::from_slice(bytes).unwrap_or(Value::Null)
+}
+
+pub fn text(bytes: Bytes) -> String {
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+// Sends a JSON request as `user_id` and returns the JSON response
+pub async fn send_as(
+    app: &Router,
+    user_id: i64,
+    method: &str,
+    uri: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = request(method, uri, Some(user_id))
+        .header("Content-Type", "application/json")
+        .body(json_body(body))
+        .unwrap();
+
+    let (status, _, bytes) = call(app, request).await;
+    (status, json(&bytes))
+}
+
+pub async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, Value) {
+    send_as(app, 1, method, uri, body).await
+}
+
+pub async fn count(pool: &SqlitePool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
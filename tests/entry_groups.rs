@@ -0,0 +1,97 @@
+use axum::{http::StatusCode, Router};
+use serde_json::{json, Value};
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> Router {
+    let (app, _) = common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Row', 'Back'), ('Squat', 'Legs')",
+    ])
+    .await;
+
+    send(&app, "POST", "/api/workouts", Some(json!({ "user_id": 1, "date": "2024-03-01", "notes": null }))).await;
+    for exercise_id in [1, 3, 2] {
+        send(&app, "POST", "/api/workout_entries", Some(json!({
+            "workout_id": 1, "exercise_id": exercise_id, "sets": 1, "reps": 10, "weight": 50.0
+        }))).await;
+    }
+
+    app
+}
+
+fn entry_ids(entries: &Value) -> Vec<i64> {
+    entries.as_array().unwrap().iter().map(|e| e["id"].as_i64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn entries_can_be_reordered() {
+    let app = setup("entry_groups_order").await;
+
+    let (_, entries) = send(&app, "GET", "/api/workouts/1/entries", None).await;
+    assert_eq!(entry_ids(&entries), vec![1, 2, 3]);
+
+    let (status, entries) = send(&app, "PUT", "/api/workouts/1/order", Some(json!({ "entry_ids": [3, 1, 2] }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry_ids(&entries), vec![3, 1, 2]);
+
+    let (status, _) = send(&app, "PUT", "/api/workouts/1/order", Some(json!({ "entry_ids": [3, 1] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn circuit_rounds_multiply_sets_in_summary() {
+    let app = setup("entry_groups_circuit").await;
+
+    // Bench and row become a circuit and end up next to each other
+    let (status, group) = send(&app, "POST", "/api/workouts/1/groups", Some(json!({
+        "kind": "circuit", "rounds": 3, "duration_seconds": 300, "entry_ids": [1, 3]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(group["kind"], json!("circuit"));
+
+    let (_, entries) = send(&app, "GET", "/api/workouts/1/entries", None).await;
+    assert_eq!(entry_ids(&entries), vec![1, 3, 2]);
+    assert_eq!(entries[1]["group_id"], group["id"]);
+
+    let (status, _) = send(&app, "POST", "/api/workouts/1/groups", Some(json!({
+        "kind": "superset", "entry_ids": [1, 2]
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, summary) = send(&app, "GET", "/api/workouts/1/summary", None).await;
+    assert_eq!(summary["total_sets"], json!(7));
+    assert_eq!(summary["groups"][0]["rounds"], json!(3));
+    assert_eq!(summary["groups"][0]["volume"], json!(3000.0));
+    assert_eq!(summary["groups"][0]["density"], json!(600.0));
+
+    let (status, _) = send(&app, "DELETE", "/api/workouts/1/groups/1", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, summary) = send(&app, "GET", "/api/workouts/1/summary", None).await;
+    assert_eq!(summary["total_sets"], json!(3));
+}
+
+#[tokio::test]
+async fn moved_entries_leave_their_group() {
+    let app = setup("entry_groups_move").await;
+    send(&app, "POST", "/api/workouts", Some(json!({ "user_id": 1, "date": "2024-03-02", "notes": null }))).await;
+    send(&app, "POST", "/api/workout_entries", Some(json!({
+        "workout_id": 2, "exercise_id": 1, "sets": 1, "reps": 5, "weight": 60.0
+    }))).await;
+    send(&app, "POST", "/api/workouts/1/groups", Some(json!({
+        "kind": "circuit", "rounds": 3, "entry_ids": [1, 3]
+    }))).await;
+
+    let (status, entry) = send(&app, "PATCH", "/api/workout_entries/1", Some(json!({ "workout_id": 2 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(entry["group_id"], Value::Null);
+    assert_eq!(entry["position"], json!(2));
+
+    let (_, entries) = send(&app, "GET", "/api/workouts/2/entries", None).await;
+    assert_eq!(entry_ids(&entries), vec![4, 1]);
+
+    // The entry's sets are no longer multiplied by the circuit it left
+    let (_, summary) = send(&app, "GET", "/api/workouts/2/summary", None).await;
+    assert_eq!(summary["total_sets"], json!(2));
+}
@@ -0,0 +1,86 @@
+use axum::{
+    http::{header, StatusCode},
+    Router,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date, notes) VALUES (1, '2024-03-01', 'Push')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100), (1, 1, 3, 5, 105)",
+    ])
+    .await
+    .0
+}
+
+async fn send(
+    app: &Router,
+    method: &str,
+    uri: &str,
+    condition: Option<(header::HeaderName, &str)>,
+    body: Option<Value>,
+) -> (StatusCode, Option<String>, Value) {
+    let mut request = common::request(method, uri, Some(1)).header("Content-Type", "application/json");
+    if let Some((name, value)) = condition {
+        request = request.header(name, value);
+    }
+    let request = request.body(common::json_body(body)).unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    let etag = headers.get(header::ETAG).map(|v| v.to_str().unwrap().to_string());
+    (status, etag, common::json(&bytes))
+}
+
+#[tokio::test]
+async fn stale_if_match_is_rejected_and_fresh_one_bumps_the_version() {
+    let app = setup("etags_if_match").await;
+
+    let (status, tag, workout) = send(&app, "GET", "/api/workouts/1", None, None).await;
+    let tag = tag.unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((tag.as_str(), workout["version"].as_i64()), ("\"1-1\"", Some(1)));
+
+    let (status, _, _) = send(&app, "GET", "/api/workouts/1", Some((header::IF_NONE_MATCH, &tag)), None).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    let (status, new_tag, workout) = send(&app, "PATCH", "/api/workouts/1", Some((header::IF_MATCH, &tag)), Some(json!({ "notes": "Heavy" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((new_tag.as_deref(), workout["version"].as_i64()), (Some("\"1-2\""), Some(2)));
+
+    // Every write with the old tag now loses
+    let put = json!({ "user_id": 1, "date": "2024-03-02", "notes": null });
+    for (method, body) in [("PATCH", Some(json!({ "notes": "Lost" }))), ("PUT", Some(put)), ("DELETE", None)] {
+        let (status, _, _) = send(&app, method, "/api/workouts/1", Some((header::IF_MATCH, &tag)), body).await;
+        assert_eq!(status, StatusCode::PRECONDITION_FAILED, "{}", method);
+    }
+
+    let (status, _, _) = send(&app, "GET", "/api/workouts/1", Some((header::IF_NONE_MATCH, &tag)), None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _, _) = send(&app, "DELETE", "/api/workouts/1", Some((header::IF_MATCH, "\"1-2\"")), None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn entry_collection_etag_follows_entry_edits() {
+    let app = setup("etags_entries").await;
+
+    let (status, list_tag, entries) = send(&app, "GET", "/api/workouts/1/entries", None, None).await;
+    let list_tag = list_tag.unwrap();
+    assert_eq!((status, entries.as_array().map(Vec::len)), (StatusCode::OK, Some(2)));
+
+    let (status, _, _) = send(&app, "GET", "/api/workouts/1/entries", Some((header::IF_NONE_MATCH, &format!("W/{}", list_tag))), None).await;
+    assert_eq!(status, StatusCode::NOT_MODIFIED);
+
+    let (_, entry_tag, _) = send(&app, "GET", "/api/workout_entries/2", None, None).await;
+    let (status, _, entry) = send(&app, "PATCH", "/api/workout_entries/2", Some((header::IF_MATCH, &entry_tag.unwrap())), Some(json!({ "reps": 6 }))).await;
+    assert_eq!((status, entry["version"].as_i64()), (StatusCode::OK, Some(2)));
+
+    let (status, new_list_tag, _) = send(&app, "GET", "/api/workouts/1/entries", Some((header::IF_NONE_MATCH, &list_tag)), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_ne!(new_list_tag.unwrap(), list_tag);
+}
@@ -0,0 +1,59 @@
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    Router,
+};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date, notes) VALUES (1, '2024-01-01', 'Push, heavy'), (1, '2024-02-01', NULL), (2, '2024-02-01', NULL), (1, '2024-03-01', 'Rest day')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100), (2, 2, 5, 5, 140), (3, 2, 1, 1, 200)",
+    ])
+    .await
+    .0
+}
+
+async fn export(app: &Router, query: &str) -> (StatusCode, Option<String>, String) {
+    let uri = format!("/api/export/workouts.csv{}", query);
+    let request = common::request("GET", &uri, Some(1)).body(Body::empty()).unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    let content_type = headers.get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    (status, content_type, common::text(bytes))
+}
+
+#[tokio::test]
+async fn exports_only_own_history_as_csv() {
+    let app = setup("export_csv_all").await;
+
+    let (status, content_type, body) = export(&app, "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/csv; charset=utf-8"));
+
+    let lines: Vec<_> = body.lines().collect();
+    assert_eq!(lines, vec![
+        "date,workout_id,exercise,muscle_group,sets,reps,weight,unit,notes",
+        "2024-01-01,1,Bench Press,Chest,3,5,100,kg,\"Push, heavy\"",
+        "2024-02-01,2,Squat,Legs,5,5,140,kg,",
+        "2024-03-01,4,,,,,,,Rest day",
+    ]);
+}
+
+#[tokio::test]
+async fn export_filters_dates_and_converts_units() {
+    let app = setup("export_csv_filtered").await;
+
+    let (status, _, body) = export(&app, "?from=2024-01-15&to=2024-02-28&unit=lb").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let lines: Vec<_> = body.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[1], "2024-02-01,2,Squat,Legs,5,5,308.65,lb,");
+
+    let (status, _, _) = export(&app, "?from=yesterday").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,90 @@
+use axum::{body::Body, http::StatusCode, Router};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+mod common;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+    ])
+    .await
+}
+
+// Logs one workout `days_ago` with (exercise_id, sets, reps, weight) entries
+async fn log_workout(pool: &SqlitePool, days_ago: i64, entries: &[(i64, i32, i32, f64)]) {
+    let date = (Utc::now().date_naive() - Duration::days(days_ago)).to_string();
+    let workout_id = sqlx::query("INSERT INTO workouts (user_id, date) VALUES (1, ?)")
+        .bind(date)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+    for &(exercise_id, sets, reps, weight) in entries {
+        sqlx::query("INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (?, ?, ?, ?, ?)")
+            .bind(workout_id)
+            .bind(exercise_id)
+            .bind(sets)
+            .bind(reps)
+            .bind(weight)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+async fn get_fatigue(app: &Router, query: &str) -> (StatusCode, Value) {
+    let uri = format!("/api/analytics/fatigue{}", query);
+    let request = common::request("GET", &uri, Some(1)).body(Body::empty()).unwrap();
+    let (status, _, bytes) = common::call(app, request).await;
+    (status, common::json(&bytes))
+}
+
+#[tokio::test]
+async fn regressions_trigger_deload_suggestion() {
+    let (app, pool) = setup("fatigue_regression").await;
+
+    for (days_ago, weight) in [(24, 100.0), (17, 97.5), (10, 95.0), (2, 92.5)] {
+        log_workout(&pool, days_ago, &[(1, 3, 5, weight), (2, 3, 5, weight + 40.0)]).await;
+    }
+
+    let (status, report) = get_fatigue(&app, "").await;
+    assert_eq!(status, StatusCode::OK);
+
+    let kinds: Vec<_> = report["warnings"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| w["kind"].clone())
+        .collect();
+    assert_eq!(kinds, vec![json!("regression"), json!("regression")]);
+    assert_eq!(report["suggested_deload"]["volume_reduction_percent"], json!(40));
+
+    // A longer window than the history disables stall detection
+    let (_, report) = get_fatigue(&app, "?stall_sessions=4").await;
+    assert!(report["warnings"].as_array().unwrap().is_empty());
+    assert!(report["suggested_deload"].is_null());
+}
+
+#[tokio::test]
+async fn workload_spike_is_flagged() {
+    let (app, pool) = setup("fatigue_spike").await;
+
+    // 28 days ago is just outside the chronic window
+    log_workout(&pool, 28, &[(1, 5, 5, 200.0)]).await;
+    log_workout(&pool, 20, &[(1, 3, 5, 50.0)]).await;
+    log_workout(&pool, 1, &[(1, 5, 5, 100.0), (2, 5, 5, 140.0)]).await;
+
+    let (status, report) = get_fatigue(&app, "").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["chronic_volume"].as_f64(), Some((750.0 + 6000.0) / 4.0));
+    assert!(report["acwr"].as_f64().unwrap() > 1.5);
+    assert_eq!(report["warnings"][0]["kind"], json!("workload_spike"));
+    assert!(!report["suggested_deload"].is_null());
+
+    let (status, _) = get_fatigue(&app, "?acwr_threshold=0").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,76 @@
+use axum::{body::Body, http::StatusCode, Router};
+use serde_json::{json, Value};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs'), ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (1, '2024-03-04T10:00:00'), (2, '2024-03-02')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, position) VALUES
+            (1, 1, 3, 5, 100, 1), (1, 2, 3, 8, 60, 2), (2, 1, 5, 5, 105, 1), (3, 2, 1, 1, 200, 1)",
+    ])
+    .await
+    .0
+}
+
+async fn graphql(app: &Router, user_id: Option<i64>, query: &str) -> (StatusCode, Value) {
+    let request = common::request("POST", "/api/graphql", user_id)
+        .header("Content-Type", "application/json")
+        .body(Body::from(json!({ "query": query }).to_string()))
+        .unwrap();
+
+    let (status, _, bytes) = common::call(app, request).await;
+    (status, common::json(&bytes))
+}
+
+#[tokio::test]
+async fn nested_workouts_in_one_request() {
+    let app = setup("graphql_nested").await;
+
+    let query = "{ me { username workouts { date entries { reps weight exercise { name } } } } }";
+    let (status, body) = graphql(&app, Some(1), query).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body.get("errors").is_none(), "{}", body);
+
+    // Newest first, entries in workout order, only your own workouts
+    assert_eq!(body["data"]["me"], json!({
+        "username": "lifter",
+        "workouts": [
+            { "date": "2024-03-04T10:00:00", "entries": [{ "reps": 5, "weight": 105.0, "exercise": { "name": "Squat" } }] },
+            { "date": "2024-03-01", "entries": [
+                { "reps": 5, "weight": 100.0, "exercise": { "name": "Squat" } },
+                { "reps": 8, "weight": 60.0, "exercise": { "name": "Bench Press" } },
+            ] },
+        ],
+    }));
+
+    // `to` includes the whole last day
+    let (_, body) = graphql(&app, Some(1), r#"{ me { workouts(from: "2024-03-02", to: "2024-03-04") { id } } }"#).await;
+    assert_eq!(body["data"]["me"]["workouts"], json!([{ "id": 2 }]));
+
+    let (_, body) = graphql(&app, Some(1), "{ mine: workout(id: 1) { id } theirs: workout(id: 3) { id } }").await;
+    assert_eq!(body["data"], json!({ "mine": { "id": 1 }, "theirs": null }));
+
+    let (status, _) = graphql(&app, None, "{ me { username } }").await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn deep_and_expensive_queries_are_rejected() {
+    let app = setup("graphql_limits").await;
+
+    let deep = "{ __schema { types { fields { type { ofType { ofType { ofType { ofType { ofType { ofType { name } } } } } } } } } } }";
+    let (_, body) = graphql(&app, Some(1), deep).await;
+    assert!(body["errors"][0]["message"].as_str().unwrap().contains("nested too deep"), "{}", body);
+
+    // 100 workouts x 10 estimated entries each is over the complexity budget
+    let wide = "{ me { workouts(limit: 100) { date entries { sets reps weight exercise { name muscleGroup } } } } }";
+    let (_, body) = graphql(&app, Some(1), wide).await;
+    assert!(body["errors"][0]["message"].as_str().unwrap().contains("too complex"), "{}", body);
+    assert!(body["data"].is_null());
+
+    let (_, body) = graphql(&app, Some(1), "{ me { workouts(limit: 5) { entries { exercise { name } } } } }").await;
+    assert!(body.get("errors").is_none(), "{}", body);
+}
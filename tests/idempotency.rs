@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use axum::{body::Body, http::StatusCode, middleware, routing, Router};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tower::ServiceExt;
+
+use gymbuddy::api::routes::create_app;
+use gymbuddy::middleware::idempotency::idempotency;
+
+mod common;
+use common::count;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    let pool = common::test_pool(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (2, '2024-03-01')",
+    ])
+    .await;
+
+    (create_app(pool.clone()), pool)
+}
+
+async fn wait_for_keys(pool: &SqlitePool, expected: i64) {
+    for _ in 0..100 {
+        if count(pool, "idempotency_keys").await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected {} idempotency keys", expected);
+}
+
+async fn post(app: &Router, user_id: Option<i64>, uri: &str, key: &str, body: Value) -> (StatusCode, bool, String) {
+    let request = common::request("POST", uri, user_id)
+        .header("Idempotency-Key", key)
+        .header("Content-Type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    (status, headers.contains_key("Idempotent-Replayed"), common::text(bytes))
+}
+
+#[tokio::test]
+async fn retried_post_replays_the_first_response() {
+    let (app, pool) = setup("idempotency_replay").await;
+    let set = json!({ "workout_id": 1, "exercise_id": 1, "sets": 1, "reps": 5, "weight": 100 });
+
+    let (status, replayed, first) = post(&app, Some(1), "/api/workout_entries", "set-1", set.clone()).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+
+    let (status, replayed, retry) = post(&app, Some(1), "/api/workout_entries", "set-1", set.clone()).await;
+    assert_eq!((status, replayed), (StatusCode::OK, true));
+    assert_eq!(retry, first);
+    assert_eq!(count(&pool, "workout_entries").await, 1);
+
+    let heavier = json!({ "workout_id": 1, "exercise_id": 1, "sets": 1, "reps": 5, "weight": 110 });
+    let (status, _, _) = post(&app, Some(1), "/api/workout_entries", "set-1", heavier).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let (status, replayed, _) = post(&app, Some(1), "/api/workout_entries", "set-2", set).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+    assert_eq!(count(&pool, "workout_entries").await, 2);
+
+    // Buffered bodies still respect the route's size limit
+    let huge = json!({ "workout_id": 1, "exercise_id": 1, "sets": 1, "reps": 5, "notes": "x".repeat(3 * 1024 * 1024) });
+    let (status, _, _) = post(&app, Some(1), "/api/workout_entries", "set-3", huge).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    // A client that goes away mid-request doesn't leave its key stuck in progress
+    let stuck = Router::new()
+        .route("/api/workouts", routing::post(std::future::pending::<StatusCode>))
+        .layer(middleware::from_fn_with_state(pool.clone(), idempotency))
+        .with_state(pool.clone());
+    let workout = json!({ "user_id": 0, "date": "2024-03-02" });
+    let request = common::request("POST", "/api/workouts", Some(1))
+        .header("Idempotency-Key", "workout-1")
+        .header("Content-Type", "application/json")
+        .body(Body::from(workout.to_string()))
+        .unwrap();
+    let abandoned = tokio::spawn(stuck.oneshot(request));
+    wait_for_keys(&pool, 3).await;
+    abandoned.abort();
+    wait_for_keys(&pool, 2).await;
+
+    let (status, replayed, _) = post(&app, Some(1), "/api/workouts", "workout-1", workout).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+}
+
+#[tokio::test]
+async fn keys_are_scoped_per_user_and_forgotten_with_the_account() {
+    let (app, pool) = setup("idempotency_scope").await;
+    let workout = json!({ "user_id": 0, "date": "2024-03-02" });
+
+    post(&app, Some(1), "/api/workouts", "same-key", workout.clone()).await;
+    let (status, replayed, _) = post(&app, Some(2), "/api/workouts", "same-key", workout).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+    assert_eq!(count(&pool, "workouts").await, 4);
+
+    // Registration and login answer with credentials; they are never remembered
+    let user = json!({ "username": "new", "email": "new@example.com", "password": "secret123" });
+    post(&app, None, "/api/register", "signup", user).await;
+    let login = json!({ "email": "new@example.com", "password": "secret123" });
+    let (status, replayed, _) = post(&app, None, "/api/login", "signin", login).await;
+    assert_eq!((status, replayed), (StatusCode::OK, false));
+    assert_eq!(count(&pool, "idempotency_keys").await, 2);
+
+    gymbuddy::db::account::delete_account(&pool, 2).await.unwrap();
+    assert_eq!(count(&pool, "idempotency_keys").await, 1);
+}
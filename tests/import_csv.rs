@@ -0,0 +1,105 @@
+use axum::{body::Body, http::StatusCode, Router};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+mod common;
+use common::count;
+
+const STRONG_CSV: &str = "\
+Date,Workout Name,Duration,Exercise Name,Set Order,Weight,Reps,Distance,Seconds,Notes,Workout Notes,RPE
+2024-05-01 18:00:00,Push,1h,Bench Press (Barbell),W,40,10,0,0,,,
+2024-05-01 18:00:00,Push,1h,Bench Press (Barbell),1,80,5,0,0,,,7
+2024-05-01 18:00:00,Push,1h,Bench Press (Barbell),2,80,5,0,0,,,8
+2024-05-01 18:00:00,Push,1h,Bench Press (Barbell),3,80,4,0,0,,,9
+2024-05-01 18:00:00,Push,1h,Cable Fly,1,20,12,0,0,,,
+2024-05-03 18:00:00,Cardio,30m,Running,1,0,0,5,1800,,,
+";
+
+const HEVY_CSV: &str = "\
+title,start_time,end_time,description,exercise_title,superset_id,exercise_notes,set_index,set_type,weight_lbs,reps,distance_miles,duration_seconds,rpe
+Legs,\"2 May 2024, 07:30\",\"2 May 2024, 08:30\",Felt good,Squat (Barbell),,,0,warmup,95,10,,,
+Legs,\"2 May 2024, 07:30\",\"2 May 2024, 08:30\",Felt good,Squat (Barbell),,,1,normal,225,5,,,8
+";
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+    ])
+    .await
+}
+
+async fn import(app: &Router, query: &str, csv: &str) -> (StatusCode, Value) {
+    let uri = format!("/api/import{}", query);
+    let request = common::request("POST", &uri, Some(1))
+        .header("content-type", "text/csv")
+        .body(Body::from(csv.to_string()))
+        .unwrap();
+
+    let (status, _, bytes) = common::call(app, request).await;
+    (status, common::json(&bytes))
+}
+
+#[tokio::test]
+async fn strong_dry_run_reports_without_writing() {
+    let (app, pool) = setup("import_strong").await;
+    sqlx::query("INSERT INTO webhooks (user_id, url, secret, events, created_at) VALUES (1, 'https://example.com/hook', 'x', 'workout.created,entry.created', 0)")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let (status, report) = import(&app, "?dry_run=true", STRONG_CSV).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["source"], json!("strong"));
+    assert_eq!(report["workouts"], json!(1));
+    assert_eq!(report["entries"], json!(3)); // 2x5 and 1x4 bench, then fly
+    assert_eq!(report["skipped_rows"], json!(2)); // warm-up and the run
+    assert_eq!(report["exercises"][0]["status"], json!("matched"));
+    assert_eq!(report["exercises"][0]["exercise_name"], json!("Bench Press"));
+    assert_eq!(report["exercises"][1]["status"], json!("created"));
+    assert!(report["exercises"][1]["exercise_id"].is_null());
+
+    assert_eq!(count(&pool, "workouts").await, 0);
+    assert_eq!(count(&pool, "exercises").await, 2);
+    assert_eq!(count(&pool, "webhook_deliveries").await, 0);
+
+    let (status, _) = import(&app, "", STRONG_CSV).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(count(&pool, "workouts").await, 1);
+    assert_eq!(count(&pool, "exercises").await, 3);
+
+    // Imported sets notify webhooks like sets logged one by one
+    let events: Vec<String> = sqlx::query_scalar("SELECT event FROM webhook_deliveries ORDER BY id")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(events, ["workout.created", "entry.created", "entry.created", "entry.created"]);
+
+    let (sets, rpe): (i32, Option<f64>) = sqlx::query_as("SELECT sets, rpe FROM workout_entries WHERE position = 1")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!((sets, rpe), (2, Some(8.0)));
+}
+
+#[tokio::test]
+async fn hevy_import_converts_pounds() {
+    let (app, pool) = setup("import_hevy").await;
+
+    let (status, report) = import(&app, "", HEVY_CSV).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(report["source"], json!("hevy"));
+    assert_eq!(report["entries"], json!(1));
+
+    let (date, weight): (String, f64) = sqlx::query_as(
+        "SELECT w.date, we.weight FROM workout_entries we JOIN workouts w ON we.workout_id = w.id"
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(date, "2024-05-02");
+    assert!((weight - 102.06).abs() < 0.01);
+
+    let (status, _) = import(&app, "", "just,some,columns\n1,2,3\n").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
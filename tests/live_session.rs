@@ -0,0 +1,111 @@
+use std::net::SocketAddr;
+
+use axum::{http::StatusCode, Router};
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, Error, Message},
+    MaybeTlsStream, WebSocketStream,
+};
+
+use gymbuddy::jwt::generate_jwt;
+
+mod common;
+
+type Socket = WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>;
+
+async fn setup(name: &str) -> (Router, SocketAddr) {
+    let (app, _) = common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (2, '2024-03-01')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100)",
+    ])
+    .await;
+
+    // REST calls go through the same router, so they share its live hub with the server
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.clone().into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    (app, addr)
+}
+
+async fn send(app: &Router, method: &str, uri: &str, body: Option<Value>) -> StatusCode {
+    common::send(app, method, uri, body).await.0
+}
+
+async fn connect(addr: SocketAddr, workout_id: i64, header_auth: bool) -> Result<Socket, Error> {
+    let token = generate_jwt(1);
+    let url = if header_auth {
+        format!("ws://{}/api/workouts/{}/live", addr, workout_id)
+    } else {
+        format!("ws://{}/api/workouts/{}/live?token={}", addr, workout_id, token)
+    };
+
+    let mut request = url.into_client_request().unwrap();
+    if header_auth {
+        request.headers_mut().insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+    }
+
+    connect_async(request).await.map(|(socket, _)| socket)
+}
+
+async fn next_event(socket: &mut Socket) -> Value {
+    loop {
+        if let Message::Text(text) = socket.next().await.unwrap().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[tokio::test]
+async fn every_device_sees_entry_changes_and_rest_timers() {
+    let (app, addr) = setup("live_broadcast").await;
+
+    let mut phone = connect(addr, 1, true).await.unwrap();
+    let mut tablet = connect(addr, 1, false).await.unwrap();
+    for socket in [&mut phone, &mut tablet] {
+        let snapshot = next_event(socket).await;
+        assert_eq!(snapshot["type"], "snapshot");
+        assert_eq!(snapshot["entries"].as_array().map(Vec::len), Some(1));
+    }
+
+    let set = json!({ "workout_id": 1, "exercise_id": 1, "sets": 1, "reps": 8, "weight": 80 });
+    assert_eq!(send(&app, "POST", "/api/workout_entries", Some(set)).await, StatusCode::OK);
+    send(&app, "PATCH", "/api/workout_entries/2", Some(json!({ "reps": 10 }))).await;
+    send(&app, "DELETE", "/api/workout_entries/1", None).await;
+
+    for socket in [&mut phone, &mut tablet] {
+        let created = next_event(socket).await;
+        assert_eq!((created["type"].as_str(), created["entry"]["reps"].as_i64()), (Some("entry_created"), Some(8)));
+        let updated = next_event(socket).await;
+        assert_eq!((updated["type"].as_str(), updated["entry"]["reps"].as_i64()), (Some("entry_updated"), Some(10)));
+        let deleted = next_event(socket).await;
+        assert_eq!((deleted["type"].as_str(), deleted["id"].as_i64()), (Some("entry_deleted"), Some(1)));
+    }
+
+    let timer = json!({ "type": "rest_timer", "action": "start", "entry_id": 2, "seconds": 90 });
+    phone.send(Message::Text(timer.to_string())).await.unwrap();
+
+    // Timers run on the server, which tells every device about them
+    let started = next_event(&mut tablet).await;
+    assert_eq!(started["type"], "rest_timer_started");
+    assert_eq!((started["timer"]["entry_id"].as_i64(), started["timer"]["seconds"].as_i64()), (Some(2), Some(90)));
+}
+
+#[tokio::test]
+async fn only_the_owner_can_join_a_workout() {
+    let (_, addr) = setup("live_auth").await;
+
+    let rejected = |result: Result<Socket, Error>| match result {
+        Err(Error::Http(response)) => response.status().as_u16(),
+        _ => panic!("connection should have been refused"),
+    };
+
+    assert_eq!(rejected(connect(addr, 2, true).await), 404);
+
+    let request = format!("ws://{}/api/workouts/1/live?token=garbage", addr).into_client_request().unwrap();
+    assert_eq!(rejected(connect_async(request).await.map(|(socket, _)| socket)), 401);
+}
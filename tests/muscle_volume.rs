@@ -0,0 +1,74 @@
+use axum::{http::StatusCode, Router};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+    ])
+    .await
+}
+
+// Logs one workout `days_ago` with (exercise_id, sets, rpe) entries
+async fn log_workout(pool: &SqlitePool, days_ago: i64, entries: &[(i64, i32, Option<f64>)]) {
+    let date = (Utc::now().date_naive() - Duration::days(days_ago)).to_string();
+    let workout_id = sqlx::query("INSERT INTO workouts (user_id, date) VALUES (1, ?)")
+        .bind(date)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+    for &(exercise_id, sets, rpe) in entries {
+        sqlx::query("INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, rpe) VALUES (?, ?, ?, 10, 50, ?)")
+            .bind(workout_id)
+            .bind(exercise_id)
+            .bind(sets)
+            .bind(rpe)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn weekly_hard_sets_are_compared_with_targets() {
+    let (app, pool) = setup("muscle_volume_targets").await;
+
+    // Warm-up sets (RPE below 7) don't count as hard sets
+    log_workout(&pool, 0, &[(1, 12, None), (1, 3, Some(5.0)), (2, 4, Some(8.0))]).await;
+    log_workout(&pool, 7, &[(1, 6, None)]).await;
+
+    let (status, _) = send(&app, "PUT", "/api/volume_targets/Chest", Some(json!({ "mev": 14, "mav": 8, "mrv": 20 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "PUT", "/api/volume_targets/chest", Some(json!({ "mev": 10, "mav": 14, "mrv": 20 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, report) = send(&app, "GET", "/api/analytics/muscle_volume?weeks=2", None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let chest = &report["muscles"][0];
+    assert_eq!(chest["muscle_group"], json!("Chest"));
+    assert_eq!(chest["sets"], json!(12));
+    assert_eq!(chest["status"], json!("in_range"));
+    assert_eq!(chest["target"]["mrv"], json!(20));
+    assert_eq!(chest["trend"][0]["sets"], json!(6));
+    assert_eq!(chest["trend"][1]["sets"], json!(12));
+
+    let legs = &report["muscles"][1];
+    assert_eq!(legs["sets"], json!(4));
+    assert_eq!(legs["status"], json!("under"));
+
+    let (status, weekly) = send(&app, "GET", "/api/analytics/weekly", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(weekly["muscles"][0]["muscle_group"], json!("Chest"));
+
+    let (status, _) = send(&app, "GET", "/api/analytics/muscle_volume?weeks=0", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,99 @@
+use axum::{body::Body, http::StatusCode, Router};
+use serde_json::Value;
+use tower::ServiceExt;
+
+use gymbuddy::api::routes::create_api_router;
+use gymbuddy::middleware::idempotency::IDEMPOTENT_ROUTES;
+
+mod common;
+
+async fn fetch_spec() -> Value {
+    let (app, _) = common::setup("openapi", &[]).await;
+
+    // Public: no Authorization header
+    let request = common::request("GET", "/api/openapi.json", None).body(Body::empty()).unwrap();
+    let (status, _, bytes) = common::call(&app, request).await;
+    assert_eq!(status, StatusCode::OK);
+
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+// Sends `method path` to the router without credentials. Unrouted paths hit the fallback
+// (418) and unrouted methods get 405; anything else, even an auth error, means it's routed.
+async fn routed_status(app: &Router, method: &str, path: &str) -> StatusCode {
+    // {id}, {token}, ... are filled with a placeholder value
+    let uri = path
+        .split('/')
+        .map(|s| if s.starts_with('{') { "1" } else { s })
+        .collect::<Vec<_>>()
+        .join("/");
+    let request = common::request(&method.to_uppercase(), &uri, None).body(Body::empty()).unwrap();
+
+    app.clone().oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn spec_documents_every_route() {
+    let spec = fetch_spec().await;
+    assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+
+    let pool = common::test_pool("openapi_routes", &[]).await;
+    let app = create_api_router()
+        .fallback(|| async { StatusCode::IM_A_TEAPOT })
+        .with_state(pool);
+    assert_eq!(routed_status(&app, "get", "/api/v1/nothing").await, StatusCode::IM_A_TEAPOT);
+
+    let paths = spec["paths"].as_object().unwrap();
+    let documented: usize = paths.values().map(|item| item.as_object().unwrap().len()).sum();
+    assert!(documented > 60, "only {} operations documented", documented);
+
+    for (path, item) in paths {
+        let item = item.as_object().unwrap();
+        for method in ["get", "post", "put", "patch", "delete"] {
+            let status = routed_status(&app, method, path).await;
+            let routed = status != StatusCode::IM_A_TEAPOT && status != StatusCode::METHOD_NOT_ALLOWED;
+
+            // Documented operations are routed, and the router has no others on the same path
+            assert_eq!(
+                routed,
+                item.contains_key(method),
+                "{} {} is {} but {} ({})",
+                method.to_uppercase(),
+                path,
+                if routed { "routed" } else { "not routed" },
+                if item.contains_key(method) { "documented" } else { "not documented" },
+                status
+            );
+        }
+    }
+
+    // The creating routes the idempotency middleware covers are documented too
+    for (route, _) in IDEMPOTENT_ROUTES {
+        let path = route
+            .split('/')
+            .map(|s| s.strip_prefix(':').map_or(s.to_string(), |p| format!("{{{}}}", p)))
+            .collect::<Vec<_>>()
+            .join("/");
+        assert!(spec["paths"][format!("/api/v1{}", path)]["post"].is_object(), "POST {} is undocumented", route);
+    }
+}
+
+#[tokio::test]
+async fn spec_describes_models_and_auth() {
+    let spec = fetch_spec().await;
+    let schemas = &spec["components"]["schemas"];
+
+    for model in ["Workout", "NewWorkoutEntry", "WorkoutEntryDetailed", "WebhookEvent"] {
+        assert!(schemas[model].is_object(), "{} schema missing", model);
+    }
+    assert!(schemas["NewWorkoutEntry"]["required"].as_array().unwrap().contains(&"workout_id".into()));
+
+    let entries = &spec["paths"]["/api/v1/workouts/{id}/entries"]["get"];
+    let body = &entries["responses"]["200"]["content"]["application/json"]["schema"];
+    assert_eq!(body["items"]["$ref"], "#/components/schemas/WorkoutEntryDetailed");
+
+    // Bearer JWT by default; login opts out
+    assert_eq!(spec["components"]["securitySchemes"]["bearer"]["scheme"], "bearer");
+    assert!(spec["security"][0]["bearer"].is_array());
+    assert_eq!(spec["paths"]["/api/v1/login"]["post"]["security"], serde_json::json!([{}]));
+}
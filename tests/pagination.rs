@@ -0,0 +1,86 @@
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    Router,
+};
+use serde_json::Value;
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs'), ('Incline Press', 'Chest'), ('Fly_Machine', 'Chest')",
+        "INSERT INTO workouts (user_id, date, notes) VALUES (1, '2024-01-03', 'Push'), (1, '2024-01-01', 'Legs'), (2, '2024-01-02', 'Push'), (1, '2024-01-05', 'Push again'), (1, '2024-01-03', NULL), (1, '2024-01-04', 'Legs')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100), (2, 2, 3, 5, 140), (3, 1, 1, 1, 200), (4, 3, 3, 8, 60), (6, 2, 5, 5, 150)",
+    ])
+    .await
+    .0
+}
+
+// Returns the body and the rel="next" target, if any
+async fn get(app: &Router, uri: &str) -> (StatusCode, Value, Option<String>) {
+    let request = common::request("GET", uri, Some(1)).body(Body::empty()).unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    let next = headers.get(header::LINK).map(|link| {
+        let link = link.to_str().unwrap();
+        link[1..link.find('>').unwrap()].to_string()
+    });
+    (status, common::json(&bytes), next)
+}
+
+fn ids(items: &Value) -> Vec<i64> {
+    items.as_array().unwrap().iter().map(|i| i["id"].as_i64().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn follows_next_links_through_own_workouts() {
+    let app = setup("pagination_workouts").await;
+
+    let mut uri = Some("/api/workouts?sort=date&limit=2".to_string());
+    let mut seen = Vec::new();
+    while let Some(next) = uri {
+        let (status, body, link) = get(&app, &next).await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.as_array().unwrap().len() <= 2);
+        seen.extend(ids(&body));
+        uri = link;
+    }
+    // Ties on date are broken by id; the other user's workout never shows up
+    assert_eq!(seen, vec![2, 1, 5, 6, 4]);
+
+    let (_, body, link) = get(&app, "/api/workouts?q=push&from=2024-01-02&to=2024-01-04").await;
+    assert_eq!(ids(&body), vec![1]);
+    assert!(link.is_none());
+
+    let (_, body, _) = get(&app, "/api/workout_entries?muscle_group=legs&sort=-date").await;
+    assert_eq!(ids(&body), vec![5, 2]);
+}
+
+#[tokio::test]
+async fn filters_exercises_and_rejects_bad_parameters() {
+    let app = setup("pagination_exercises").await;
+
+    let (_, body, next) = get(&app, "/api/exercises?muscle_group=chest&sort=-name&limit=2").await;
+    assert_eq!(ids(&body), vec![3, 4]);
+    let (_, body, _) = get(&app, &next.clone().unwrap()).await;
+    assert_eq!(ids(&body), vec![1]);
+
+    // Underscore is matched literally, not as a LIKE wildcard
+    let (_, body, _) = get(&app, "/api/exercises?q=y_m").await;
+    assert_eq!(ids(&body), vec![4]);
+
+    let cursor = next.unwrap().split("cursor=").nth(1).unwrap().to_string();
+    let (status, _, _) = get(&app, &format!("/api/exercises?sort=id&cursor={}", cursor)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = get(&app, "/api/exercises?sort=weight").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, body, next) = get(&app, "/api/users?sort=-username&limit=1").await;
+    assert_eq!(body[0]["username"], "other");
+    assert!(body[0].get("password_hash").is_none());
+    let (_, body, next) = get(&app, &next.unwrap()).await;
+    assert_eq!((ids(&body), next), (vec![1], None));
+}
@@ -0,0 +1,82 @@
+use axum::{http::StatusCode, Router};
+use serde_json::json;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest')",
+        "INSERT INTO workouts (user_id, date, notes) VALUES (1, '2024-03-01', 'Push'), (1, '2024-03-02', NULL), (2, '2024-03-01', NULL)",
+        "INSERT INTO entry_groups (workout_id, kind) VALUES (1, 'superset')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, rpe, group_id) VALUES (1, 1, 3, 5, 100, 8, 1)",
+    ])
+    .await
+    .0
+}
+
+#[tokio::test]
+async fn patches_single_fields_of_workouts_and_users() {
+    let app = setup("patch_workouts").await;
+
+    let (status, workout) = send(&app, "PATCH", "/api/workouts/1", Some(json!({ "date": "2024-03-05" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((workout["date"].as_str(), workout["notes"].as_str()), (Some("2024-03-05"), Some("Push")));
+
+    let (_, workout) = send(&app, "PATCH", "/api/workouts/1", Some(json!({ "notes": null }))).await;
+    assert_eq!((workout["date"].as_str(), workout["notes"].is_null()), (Some("2024-03-05"), true));
+
+    for (uri, body, expected) in [
+        ("/api/workouts/3", json!({ "notes": "mine now" }), StatusCode::NOT_FOUND),
+        ("/api/workouts/99", json!({ "notes": "ghost" }), StatusCode::NOT_FOUND),
+        ("/api/workouts/1", json!({ "date": "yesterday" }), StatusCode::BAD_REQUEST),
+        ("/api/workouts/1", json!({ "user_id": 2 }), StatusCode::UNPROCESSABLE_ENTITY),
+        ("/api/users/2", json!({ "email": "taken@example.com" }), StatusCode::FORBIDDEN),
+        ("/api/users/99", json!({ "email": "ghost@example.com" }), StatusCode::NOT_FOUND),
+        // Another account's login email or username
+        ("/api/users/1", json!({ "email": "other@example.com" }), StatusCode::CONFLICT),
+        ("/api/users/1", json!({ "username": "other" }), StatusCode::CONFLICT),
+    ] {
+        let (status, _) = send(&app, "PATCH", uri, Some(body)).await;
+        assert_eq!(status, expected, "{}", uri);
+    }
+
+    let (status, user) = send(&app, "PATCH", "/api/users/1", Some(json!({ "email": "new@example.com" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((user["username"].as_str(), user["email"].as_str()), (Some("lifter"), Some("new@example.com")));
+    assert!(user.get("password_hash").is_none());
+
+    // Keeping your own email isn't a conflict
+    let (status, _) = send(&app, "PATCH", "/api/users/1", Some(json!({ "email": "new@example.com", "username": "lifter" }))).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn patches_entries_and_leaves_other_fields_alone() {
+    let app = setup("patch_entries").await;
+
+    let (status, entry) = send(&app, "PATCH", "/api/workout_entries/1", Some(json!({ "weight": 102.5 }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        (entry["sets"].as_i64(), entry["weight"].as_f64(), entry["rpe"].as_f64(), entry["group_id"].as_i64()),
+        (Some(3), Some(102.5), Some(8.0), Some(1))
+    );
+
+    // Clearing rpe and moving to another workout, which drops the entry from its superset
+    let (_, entry) = send(&app, "PATCH", "/api/workout_entries/1", Some(json!({ "rpe": null, "workout_id": 2 }))).await;
+    assert_eq!(
+        (entry["workout_id"].as_i64(), entry["rpe"].is_null(), entry["group_id"].is_null(), entry["weight"].as_f64()),
+        (Some(2), true, true, Some(102.5))
+    );
+
+    for (uri, body, expected) in [
+        ("/api/workout_entries/99", json!({ "reps": 6 }), StatusCode::NOT_FOUND),
+        ("/api/workout_entries/1", json!({ "workout_id": 3 }), StatusCode::NOT_FOUND),
+        ("/api/workout_entries/1", json!({ "exercise_id": 42 }), StatusCode::BAD_REQUEST),
+        ("/api/workout_entries/1", json!({ "rpe": 11 }), StatusCode::BAD_REQUEST),
+    ] {
+        let (status, _) = send(&app, "PATCH", uri, Some(body.clone())).await;
+        assert_eq!(status, expected, "{}", body);
+    }
+}
@@ -0,0 +1,57 @@
+use axum::{
+    body::Body,
+    http::{header, StatusCode},
+    Router,
+};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('<coach>', 'coach@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-02-20'), (1, '2024-03-04'), (1, '2024-03-14')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100), (1, 2, 3, 5, 140), (2, 1, 3, 5, 105), (3, 2, 3, 5, 120)",
+    ])
+    .await
+    .0
+}
+
+async fn report(app: &Router, query: &str) -> (StatusCode, Option<String>, String) {
+    let uri = format!("/api/reports/progress{}", query);
+    let request = common::request("GET", &uri, Some(1)).body(Body::empty()).unwrap();
+
+    let (status, headers, bytes) = common::call(app, request).await;
+    let content_type = headers.get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    (status, content_type, common::text(bytes))
+}
+
+#[tokio::test]
+async fn renders_self_contained_html_for_range() {
+    let app = setup("progress_report_html").await;
+
+    let (status, content_type, html) = report(&app, "?from=2024-03-01&to=2024-03-31").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("text/html; charset=utf-8"));
+
+    assert!(html.contains("Progress report for &lt;coach&gt;"));
+    assert!(html.contains("<b>2</b>workouts"));
+    assert!(html.contains("<svg"));
+    assert!(!html.contains("<script") && !html.contains("<link"));
+
+    // Bench beat February's 100 kg, Squat went down
+    assert!(html.contains("<td>Bench Press</td><td class=\"num\">105 kg &times; 5</td>"));
+    assert!(!html.contains("<td>Squat</td><td class=\"num\">120"));
+    assert!(html.contains("<td>Chest</td>") && html.contains("<td>Legs</td>"));
+}
+
+#[tokio::test]
+async fn rejects_invalid_ranges() {
+    let app = setup("progress_report_range").await;
+
+    let (status, _, _) = report(&app, "?from=2024-03-31&to=2024-03-01").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _, _) = report(&app, "?from=March").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,72 @@
+use axum::{http::StatusCode, Router};
+use serde_json::json;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+    ])
+    .await
+    .0
+}
+
+#[tokio::test]
+async fn double_progression_adds_weight_at_top_of_range() {
+    let app = setup("progression_double").await;
+
+    send(&app, "POST", "/api/exercises", Some(json!([
+        { "name": "Bench Press", "muscle_group": "Chest", "description": null }
+    ]))).await;
+    send(&app, "POST", "/api/workouts", Some(json!({ "user_id": 1, "date": "2024-01-01", "notes": null }))).await;
+    send(&app, "POST", "/api/workout_entries", Some(json!({
+        "workout_id": 1, "exercise_id": 1, "sets": 3, "reps": 12, "weight": 60.0
+    }))).await;
+
+    let (status, next) = send(&app, "GET", "/api/exercises/1/next", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(next["weight"], json!(62.5));
+    assert_eq!(next["reps"], json!(8));
+    assert_eq!(next["last_session"]["reps"], json!(12));
+
+    let (status, _) = send(&app, "GET", "/api/exercises/99/next", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn rpe_rule_and_template_start_include_recommendations() {
+    let app = setup("progression_rpe").await;
+
+    send(&app, "POST", "/api/exercises", Some(json!([
+        { "name": "Squat", "muscle_group": "Legs", "description": null }
+    ]))).await;
+    send(&app, "POST", "/api/workouts", Some(json!({ "user_id": 1, "date": "2024-01-01", "notes": null }))).await;
+    send(&app, "POST", "/api/workout_entries", Some(json!({
+        "workout_id": 1, "exercise_id": 1, "sets": 5, "reps": 5, "weight": 100.0, "rpe": 6.0
+    }))).await;
+
+    let (status, _) = send(&app, "PUT", "/api/exercises/1/progression", Some(json!({
+        "rule": "rpe_based", "target_rpe": 11.0, "weight_increment": 2.5
+    }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _) = send(&app, "PUT", "/api/exercises/1/progression", Some(json!({
+        "rule": "rpe_based", "target_rpe": 8.0, "weight_increment": 2.5
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, template) = send(&app, "POST", "/api/templates", Some(json!({
+        "name": "Leg day", "notes": null, "exercises": [{ "exercise_id": 1, "sets": 4, "reps": 5 }]
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(template["exercises"].as_array().unwrap().len(), 1);
+
+    let (status, started) = send(&app, "POST", "/api/templates/1/start", Some(json!({ "date": "2024-01-08" }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(started["workout"]["date"], json!("2024-01-08"));
+
+    let recommendation = &started["recommendations"][0];
+    assert_eq!(recommendation["sets"], json!(4));
+    assert_eq!(recommendation["weight"], json!(105.0));
+}
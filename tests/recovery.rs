@@ -0,0 +1,85 @@
+use axum::{http::StatusCode, Router};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+    ])
+    .await
+}
+
+async fn log_workout(pool: &SqlitePool, days_ago: i64, exercise_id: i64, sets: i32) {
+    let date = (Utc::now().date_naive() - Duration::days(days_ago)).to_string();
+    let workout_id = sqlx::query("INSERT INTO workouts (user_id, date) VALUES (1, ?)")
+        .bind(date)
+        .execute(pool)
+        .await
+        .unwrap()
+        .last_insert_rowid();
+
+    sqlx::query("INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (?, ?, ?, 8, 60)")
+        .bind(workout_id)
+        .bind(exercise_id)
+        .bind(sets)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+fn muscle<'a>(report: &'a Value, list: &str, name: &str) -> &'a Value {
+    report[list]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["muscle_group"] == json!(name))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn recent_sessions_lower_recovery_until_half_lives_pass() {
+    let (app, pool) = setup("recovery_decay").await;
+
+    log_workout(&pool, 1, 1, 12).await;
+    log_workout(&pool, 10, 2, 6).await;
+
+    let (status, report) = send(&app, "GET", "/api/recovery", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(muscle(&report, "muscles", "Chest")["recovery_percent"].as_f64().unwrap() < 50.0);
+    assert!(muscle(&report, "muscles", "Legs")["recovery_percent"].as_f64().unwrap() > 99.0);
+    assert_eq!(muscle(&report, "muscles", "Legs")["days_since_last"], json!(10));
+
+    let (status, _) = send(&app, "PUT", "/api/recovery/half_lives/Chest", Some(json!({ "half_life_hours": 1.0 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (_, report) = send(&app, "GET", "/api/recovery", None).await;
+    assert!(muscle(&report, "muscles", "Chest")["recovery_percent"].as_f64().unwrap() > 99.0);
+
+    let (status, _) = send(&app, "PUT", "/api/recovery/half_lives/Chest", Some(json!({ "half_life_hours": 0.0 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn suggestions_rank_recovered_and_due_templates_first() {
+    let (app, pool) = setup("recovery_suggestions").await;
+
+    log_workout(&pool, 1, 1, 12).await;
+    log_workout(&pool, 10, 2, 6).await;
+
+    for (name, exercise_id) in [("Push", 1), ("Leg day", 2)] {
+        send(&app, "POST", "/api/templates", Some(json!({
+            "name": name, "notes": null, "exercises": [{ "exercise_id": exercise_id, "sets": 3, "reps": 8 }]
+        }))).await;
+    }
+
+    let (status, suggestions) = send(&app, "GET", "/api/recovery/suggestions", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(suggestions["muscle_groups"][0]["muscle_group"], json!("Legs"));
+    assert_eq!(suggestions["templates"][0]["name"], json!("Leg day"));
+    assert_eq!(suggestions["templates"][1]["name"], json!("Push"));
+}
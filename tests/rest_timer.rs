@@ -0,0 +1,124 @@
+use std::time::Duration;
+
+use axum::{
+    body::{BoxBody, HttpBody},
+    http::StatusCode,
+    Router,
+};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use tower::ServiceExt;
+
+use gymbuddy::api::routes::create_app;
+
+mod common;
+use common::send;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Bench Press', 'Chest'), ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (1, '2024-03-02')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 1, 5, 100), (2, 2, 1, 5, 140)",
+    ])
+    .await
+}
+
+// The response body left open, for event streams
+async fn request(app: &Router, method: &str, uri: &str, body: Option<Value>) -> (StatusCode, BoxBody) {
+    let request = common::request(method, uri, Some(1))
+        .header("Content-Type", "application/json")
+        .body(common::json_body(body))
+        .unwrap();
+
+    let response = app.clone().oneshot(request).await.unwrap();
+    (response.status(), response.into_body())
+}
+
+// Reads the event stream until the named event arrives and returns its data;
+// `buffer` carries over whatever arrived after it for the next call
+async fn wait_for_event(events: &mut BoxBody, buffer: &mut String, name: &str) -> Value {
+    let marker = format!("event:{}\ndata:", name);
+
+    loop {
+        if let Some(start) = buffer.find(&marker) {
+            let data_start = start + marker.len();
+            if let Some(end) = buffer[data_start..].find("\n\n") {
+                let data = serde_json::from_str(&buffer[data_start..data_start + end]).unwrap();
+                buffer.drain(..data_start + end + 2);
+                return data;
+            }
+        }
+
+        let chunk = tokio::time::timeout(Duration::from_secs(5), events.data())
+            .await
+            .expect("no event in time")
+            .unwrap()
+            .unwrap();
+        buffer.push_str(&String::from_utf8_lossy(&chunk));
+    }
+}
+
+#[tokio::test]
+async fn timer_expires_over_sse_and_next_set_records_the_rest() {
+    let (app, pool) = setup("rest_timer_events").await;
+
+    let (status, _) = send(&app, "PUT", "/api/rest_defaults/1", Some(json!({ "seconds": 1 }))).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, mut events) = request(&app, "GET", "/api/sessions/1/events", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut buffer = String::new();
+
+    let (_, timer) = send(&app, "POST", "/api/sessions/1/rest", Some(json!({ "entry_id": 1 }))).await;
+    assert_eq!(timer["seconds"].as_i64(), Some(1));
+
+    let started = wait_for_event(&mut events, &mut buffer, "rest_timer_started").await;
+    assert_eq!(started["timer"]["entry_id"].as_i64(), Some(1));
+    let expired = wait_for_event(&mut events, &mut buffer, "rest_timer_expired").await;
+    assert_eq!((expired["entry_id"].as_i64(), expired["seconds"].as_i64()), (Some(1), Some(1)));
+
+    let set = json!({ "workout_id": 1, "exercise_id": 1, "sets": 1, "reps": 5, "weight": 100 });
+    send(&app, "POST", "/api/workout_entries", Some(set)).await;
+
+    let stopped = wait_for_event(&mut events, &mut buffer, "rest_timer_stopped").await;
+    assert!(stopped["rest_seconds"].as_i64().unwrap() >= 1);
+
+    let (_, entry) = send(&app, "GET", "/api/workout_entries/1", None).await;
+    assert_eq!(entry["rest_seconds"], stopped["rest_seconds"]);
+    let (_, running) = send(&app, "GET", "/api/sessions/1/rest", None).await;
+    assert!(running.is_null());
+
+    // A timer still running when the server stopped expires on time after a restart
+    sqlx::query("INSERT INTO rest_timers (workout_id, entry_id, started_at, duration_seconds) VALUES (2, 2, ?, 60)")
+        .bind(chrono::Utc::now().timestamp() - 59)
+        .execute(&pool)
+        .await
+        .unwrap();
+    let restarted = create_app(pool);
+    let (_, mut events) = request(&restarted, "GET", "/api/sessions/2/events", None).await;
+    let expired = wait_for_event(&mut events, &mut String::new(), "rest_timer_expired").await;
+    assert_eq!((expired["entry_id"].as_i64(), expired["seconds"].as_i64()), (Some(2), Some(60)));
+}
+
+#[tokio::test]
+async fn timers_belong_to_their_workout() {
+    let (app, _) = setup("rest_timer_rules").await;
+
+    let (status, _) = send(&app, "POST", "/api/sessions/1/rest", Some(json!({ "entry_id": 2 }))).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send(&app, "POST", "/api/sessions/1/rest", Some(json!({ "entry_id": 1, "seconds": 0 }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send(&app, "DELETE", "/api/sessions/1/rest", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    // Without a default of its own the exercise rests 90 seconds
+    let (_, timer) = send(&app, "POST", "/api/sessions/2/rest", Some(json!({ "entry_id": 2 }))).await;
+    assert_eq!(timer["seconds"].as_i64(), Some(90));
+
+    let (_, running) = send(&app, "GET", "/api/sessions/2/rest", None).await;
+    assert!(running["remaining_seconds"].as_i64().unwrap() > 80);
+
+    let (status, finished) = send(&app, "DELETE", "/api/sessions/2/rest", None).await;
+    assert_eq!((status, finished["entry_id"].as_i64()), (StatusCode::OK, Some(2)));
+}
@@ -0,0 +1,122 @@
+use axum::{http::StatusCode, Router};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+use gymbuddy::db::connection::init_db;
+use gymbuddy::db::rollups::{check_rollups, rebuild_rollups};
+
+mod common;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs'), ('Bench Press', 'Chest')",
+        // Monday and Wednesday of one week, then the next Monday
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-04'), (1, '2024-03-06'), (1, '2024-03-11')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, position) VALUES
+            (1, 1, 3, 5, 100, 1), (1, 2, 3, 8, 60, 2), (2, 1, 5, 5, 105, 1), (3, 2, 2, 10, NULL, 1)",
+    ])
+    .await
+}
+
+async fn rollups(app: &Router, query: &str) -> Value {
+    let (status, rollups) = common::send(app, "GET", &format!("/api/analytics/rollups{}", query), None).await;
+    assert_eq!(status, StatusCode::OK);
+    rollups
+}
+
+#[tokio::test]
+async fn every_write_keeps_rollups_consistent() {
+    let (app, pool) = setup("rollups_incremental").await;
+
+    let steps = [
+        ("superset", "INSERT INTO entry_groups (workout_id, kind, rounds) VALUES (1, 'superset', 2)"),
+        ("join group", "UPDATE workout_entries SET group_id = 1 WHERE id = 2"),
+        ("more rounds", "UPDATE entry_groups SET rounds = 3 WHERE id = 1"),
+    ];
+    for (step, query) in steps {
+        sqlx::query(query).execute(&pool).await.unwrap();
+        assert!(check_rollups(&pool).await.unwrap().is_empty(), "after {}", step);
+    }
+
+    // Bench counts three rounds: 9 sets x 8 x 60
+    let weeks = rollups(&app, "").await;
+    assert_eq!(weeks, json!([
+        { "period": "2024-03-04", "exercise_id": null, "exercise_name": null, "muscle_group": null,
+          "entries": 3, "sets": 17, "reps": 18, "volume": 8445.0 },
+        { "period": "2024-03-11", "exercise_id": null, "exercise_name": null, "muscle_group": null,
+          "entries": 1, "sets": 2, "reps": 10, "volume": 0.0 },
+    ]));
+    let muscles = rollups(&app, "?period=week&by=muscle&to=2024-03-04").await;
+    let volumes: Vec<(&str, f64)> = muscles.as_array().unwrap().iter()
+        .map(|m| (m["muscle_group"].as_str().unwrap(), m["volume"].as_f64().unwrap()))
+        .collect();
+    assert_eq!(volumes, [("Chest", 4320.0), ("Legs", 4125.0)]);
+
+    let steps = [
+        ("heavier", "UPDATE workout_entries SET weight = 110 WHERE id = 1"),
+        ("moved workout", "UPDATE workouts SET date = '2024-03-12' WHERE id = 2"),
+        ("renamed muscle", "UPDATE exercises SET muscle_group = 'Pecs' WHERE id = 2"),
+        ("ungrouped", "DELETE FROM entry_groups WHERE id = 1"),
+        ("deleted entry", "DELETE FROM workout_entries WHERE id = 1"),
+        ("deleted workout", "DELETE FROM workouts WHERE id = 3"),
+    ];
+    for (step, query) in steps {
+        sqlx::query(query).execute(&pool).await.unwrap();
+        assert!(check_rollups(&pool).await.unwrap().is_empty(), "after {}", step);
+    }
+
+    // Without enforced foreign keys an exercise can go while it still has entries
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await.unwrap();
+    sqlx::query("INSERT INTO exercises (name, muscle_group) VALUES ('Curl', 'Arms')").execute(&mut *conn).await.unwrap();
+    sqlx::query("INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (2, 3, 3, 10, 20)")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM exercises WHERE id = 3").execute(&mut *conn).await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await.unwrap();
+    drop(conn);
+    assert!(check_rollups(&pool).await.unwrap().is_empty(), "after deleted exercise");
+
+    // Left: bench on the 4th (one round again), squat moved to the 12th
+    let days = rollups(&app, "?period=day&by=exercise").await;
+    let days: Vec<(&str, &str, &str, f64)> = days.as_array().unwrap().iter()
+        .map(|d| (
+            d["period"].as_str().unwrap(),
+            d["exercise_name"].as_str().unwrap(),
+            d["muscle_group"].as_str().unwrap(),
+            d["volume"].as_f64().unwrap(),
+        ))
+        .collect();
+    assert_eq!(days, [("2024-03-04", "Bench Press", "Pecs", 1440.0), ("2024-03-12", "Squat", "Legs", 2625.0)]);
+
+    let (status, _) = common::send(&app, "GET", "/api/analytics/rollups?from=last-week", None).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rebuild_backfills_and_repairs() {
+    let (_, pool) = setup("rollups_rebuild").await;
+
+    // A database from before rollups existed is backfilled on startup
+    sqlx::query("DROP TABLE analytics_daily").execute(&pool).await.unwrap();
+    sqlx::query("DROP TABLE analytics_weekly").execute(&pool).await.unwrap();
+    init_db(&pool).await.unwrap();
+    assert!(check_rollups(&pool).await.unwrap().is_empty());
+
+    sqlx::query("DELETE FROM analytics_daily WHERE day = '2024-03-11'").execute(&pool).await.unwrap();
+    sqlx::query("UPDATE analytics_weekly SET volume = volume + 1 WHERE exercise_id = 1").execute(&pool).await.unwrap();
+
+    let mismatches = check_rollups(&pool).await.unwrap();
+    let found: Vec<(&str, i64, bool)> = mismatches
+        .iter()
+        .map(|m| (m.start.as_str(), m.exercise_id, m.stored.is_some()))
+        .collect();
+    assert_eq!(found, [("2024-03-11", 2, false), ("2024-03-04", 1, true)]);
+    assert_eq!(mismatches[1].expected.as_ref().unwrap().volume, 4125.0);
+
+    let rebuilt = rebuild_rollups(&pool).await.unwrap();
+    assert_eq!((rebuilt.daily, rebuilt.weekly), (4, 3));
+    assert!(check_rollups(&pool).await.unwrap().is_empty());
+}
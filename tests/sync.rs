@@ -0,0 +1,94 @@
+use axum::{http::StatusCode, Router};
+use serde_json::{json, Value};
+
+mod common;
+use common::send;
+
+const LEG_DAY: &str = "6f1c2b9e-1d4a-4c7e-9a51-0b2e8d3f7a10";
+const SQUATS: &str = "0d8e4f3a-7b21-4c9d-8e6f-5a1b2c3d4e5f";
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (2, '2024-03-01')",
+    ])
+    .await
+    .0
+}
+
+fn ids(records: &Value) -> Vec<&str> {
+    records.as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect()
+}
+
+#[tokio::test]
+async fn offline_changes_show_up_in_the_change_feed() {
+    let app = setup("sync_feed").await;
+
+    let (_, first) = send(&app, "GET", "/api/sync/pull", None).await;
+    assert_eq!((first["seq"].as_i64(), first["workouts"].as_array().map(Vec::len)), (Some(1), Some(1)));
+    let hook = json!({ "url": "https://example.com/hook", "events": ["workout.created", "entry.created"] });
+    send(&app, "POST", "/api/webhooks", Some(hook)).await;
+
+    let (status, pushed) = send(&app, "POST", "/api/sync/push", Some(json!({ "changes": [
+        { "kind": "workout", "id": LEG_DAY, "data": { "date": "2024-03-04", "notes": "Basement gym" } },
+        { "kind": "entry", "id": SQUATS, "data": { "workout_id": LEG_DAY, "exercise_id": 1, "sets": 5, "reps": 5, "weight": 140 } },
+    ] }))).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(pushed["applied"].as_array().map(Vec::len), Some(2));
+    assert_eq!(pushed["seq"].as_i64(), Some(3));
+
+    // Offline writes notify webhooks like online ones
+    let (_, deliveries) = send(&app, "GET", "/api/webhooks/1/deliveries", None).await;
+    let events: Vec<_> = deliveries.as_array().unwrap().iter().map(|d| d["event"].as_str().unwrap()).collect();
+    assert_eq!(events, ["entry.created", "workout.created"]);
+
+    let (_, pull) = send(&app, "GET", "/api/sync/pull?since=1", None).await;
+    assert_eq!((ids(&pull["workouts"]), ids(&pull["entries"])), (vec![LEG_DAY], vec![SQUATS]));
+    assert_eq!(pull["entries"][0]["workout_id"].as_str(), Some(LEG_DAY));
+
+    // Writes through the REST API land in the same feed, deletes as tombstones
+    let entry_id = pull["entries"][0]["server_id"].as_i64().unwrap();
+    send(&app, "PATCH", &format!("/api/workout_entries/{}", entry_id), Some(json!({ "reps": 6 }))).await;
+    send(&app, "DELETE", "/api/workouts/1", None).await;
+
+    let (_, pull) = send(&app, "GET", "/api/sync/pull?since=3&limit=1", None).await;
+    assert_eq!((pull["seq"].as_i64(), pull["has_more"].as_bool()), (Some(4), Some(true)));
+    assert_eq!(pull["entries"][0]["reps"].as_i64(), Some(6));
+
+    let (_, pull) = send(&app, "GET", "/api/sync/pull?since=4", None).await;
+    assert_eq!((pull["seq"].as_i64(), pull["has_more"].as_bool()), (Some(5), Some(false)));
+    assert_eq!(pull["deleted"][0]["kind"].as_str(), Some("workout"));
+    assert_eq!(pull["deleted"][0]["id"], first["workouts"][0]["id"]);
+}
+
+#[tokio::test]
+async fn conflicts_resolve_in_favour_of_the_server() {
+    let app = setup("sync_conflicts").await;
+
+    send(&app, "POST", "/api/sync/push", Some(json!({ "changes": [
+        { "kind": "workout", "id": LEG_DAY, "data": { "date": "2024-03-04", "notes": null } },
+        { "kind": "entry", "id": SQUATS, "data": { "workout_id": LEG_DAY, "exercise_id": 1, "sets": 5, "reps": 5 } },
+    ] }))).await;
+
+    // An invalid change rejects the whole batch
+    let (status, _) = send(&app, "POST", "/api/sync/push", Some(json!({ "changes": [
+        { "kind": "workout", "id": LEG_DAY, "base_version": 1, "deleted": true },
+        { "kind": "entry", "id": SQUATS, "base_version": 1, "data": { "workout_id": LEG_DAY, "exercise_id": 1, "sets": 0, "reps": 5 } },
+    ] }))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (_, result) = send(&app, "POST", "/api/sync/push", Some(json!({ "changes": [
+        { "kind": "entry", "id": SQUATS, "base_version": 1, "data": { "workout_id": LEG_DAY, "exercise_id": 1, "sets": 5, "reps": 3 } },
+        { "kind": "entry", "id": SQUATS, "base_version": 1, "data": { "workout_id": LEG_DAY, "exercise_id": 1, "sets": 5, "reps": 8 } },
+        { "kind": "workout", "id": LEG_DAY, "base_version": 1, "deleted": true },
+        { "kind": "workout", "id": LEG_DAY, "base_version": 1, "data": { "date": "2024-03-05", "notes": null } },
+    ] }))).await;
+
+    assert_eq!(result["applied"][0]["version"].as_i64(), Some(2));
+    let conflicts = result["conflicts"].as_array().unwrap();
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0]["reason"].as_str(), Some("version_mismatch"));
+    assert_eq!(conflicts[0]["server"]["reps"].as_i64(), Some(3));
+    assert_eq!(conflicts[1]["reason"].as_str(), Some("deleted"));
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Router,
+};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+
+use gymbuddy::webhooks::{deliver_due, sign, webhook_client};
+
+mod common;
+use common::send_as;
+
+type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+async fn setup(name: &str) -> (Router, SqlitePool) {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs')",
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-01'), (1, '2024-03-08')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (1, 1, 3, 5, 100)",
+    ])
+    .await
+}
+
+// Stand-in receiver that answers 500 to its first request and 200 after that
+async fn receiver() -> (String, Received) {
+    // SAFETY: only read through std::env, which serializes access to the environment
+    unsafe { std::env::set_var("WEBHOOK_ALLOW_LOOPBACK", "1") };
+    let received = Received::default();
+
+    let app = Router::new()
+        .route(
+            "/hook",
+            post(|State(received): State<Received>, headers: HeaderMap, body: String| async move {
+                let mut received = received.lock().unwrap();
+                received.push((headers, body));
+                if received.len() == 1 { StatusCode::INTERNAL_SERVER_ERROR } else { StatusCode::OK }
+            }),
+        )
+        .with_state(received.clone());
+
+    let server = axum::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(app.into_make_service());
+    let url = format!("http://{}/hook", server.local_addr());
+    tokio::spawn(server);
+
+    (url, received)
+}
+
+#[tokio::test]
+async fn events_are_signed_and_retried_until_delivered() {
+    let (app, pool) = setup("webhook_delivery").await;
+    let (url, received) = receiver().await;
+
+    let (status, hook) = send_as(&app, 1, "POST", "/api/webhooks", Some(json!({
+        "url": url, "events": ["entry.created", "pr.achieved", "workout.finished"],
+    }))).await;
+    assert_eq!(status, StatusCode::OK);
+    let secret = hook["secret"].as_str().unwrap().to_string();
+
+    // A heavier set than last week is also a personal record; history logged for a later date
+    // doesn't count against it
+    for query in [
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-15')",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight) VALUES (3, 1, 1, 1, 200)",
+    ] {
+        sqlx::query(query).execute(&pool).await.unwrap();
+    }
+    let set = json!({ "workout_id": 2, "exercise_id": 1, "sets": 3, "reps": 5, "weight": 110 });
+    send_as(&app, 1, "POST", "/api/workout_entries", Some(set)).await;
+    let (status, workout) = send_as(&app, 1, "POST", "/api/workouts/2/finish", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(workout["finished_at"].is_string());
+    let (status, _) = send_as(&app, 1, "POST", "/api/workouts/2/finish", None).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+
+    // workout.created isn't subscribed to, so only three are queued
+    let client = webhook_client(true);
+    let now = chrono::Utc::now().timestamp();
+    assert_eq!(deliver_due(&pool, &client, now).await.unwrap(), 3);
+    assert_eq!(deliver_due(&pool, &client, now).await.unwrap(), 0);
+
+    let (_, log) = send_as(&app, 1, "GET", "/api/webhooks/1/deliveries", None).await;
+    let events: Vec<_> = log.as_array().unwrap().iter().map(|d| d["event"].as_str().unwrap()).collect();
+    assert_eq!(events, ["workout.finished", "pr.achieved", "entry.created"]);
+    let failed = &log[2];
+    assert_eq!((failed["status"].as_str(), failed["attempts"].as_i64()), (Some("pending"), Some(1)));
+    assert_eq!((failed["response_status"].as_i64(), failed["next_attempt_at"].as_i64()), (Some(500), Some(now + 30)));
+
+    assert_eq!(deliver_due(&pool, &client, now + 30).await.unwrap(), 1);
+    let (_, log) = send_as(&app, 1, "GET", "/api/webhooks/1/deliveries", None).await;
+    assert!(log.as_array().unwrap().iter().all(|d| d["status"] == "delivered"));
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 4);
+    for (headers, body) in received.iter() {
+        let timestamp: i64 = headers["x-gymbuddy-timestamp"].to_str().unwrap().parse().unwrap();
+        let signature = format!("sha256={}", sign(&secret, timestamp, body));
+        assert_eq!(headers["x-gymbuddy-signature"].to_str().unwrap(), signature);
+    }
+
+    let record: Value = serde_json::from_str(&received[1].1).unwrap();
+    assert_eq!((record["event"].as_str(), record["user_id"].as_i64()), (Some("pr.achieved"), Some(1)));
+    assert_eq!((record["data"]["entry_id"].as_i64(), record["data"]["weight"].as_f64()), (Some(3), Some(110.0)));
+    assert!((record["data"]["previous_best"].as_f64().unwrap() - 116.67).abs() < 0.01);
+}
+
+#[tokio::test]
+async fn webhooks_are_validated_and_private() {
+    let (app, pool) = setup("webhook_rules").await;
+
+    let hook = |url: &str, events: Value| Some(json!({ "url": url, "events": events }));
+    let (status, _) = send_as(&app, 1, "POST", "/api/webhooks", hook("ftp://example.com", json!(["entry.created"]))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    let (status, _) = send_as(&app, 1, "POST", "/api/webhooks", hook("https://example.com", json!(["entry.deleted"]))).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let all_users = Some(json!({ "url": "https://example.com", "events": ["workout.created"], "all_users": true }));
+    let (status, _) = send_as(&app, 1, "POST", "/api/webhooks", all_users).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // Nothing on the server's own network, however it is written
+    for url in ["http://169.254.169.254/latest/meta-data", "http://10.0.0.5/hook", "http://[::ffff:192.168.1.10]/hook"] {
+        let (status, _) = send_as(&app, 1, "POST", "/api/webhooks", hook(url, json!(["entry.created"]))).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", url);
+    }
+
+    let (_, created) = send_as(&app, 1, "POST", "/api/webhooks", hook("https://example.com", json!(["workout.created"]))).await;
+    let (_, listed) = send_as(&app, 1, "GET", "/api/webhooks", None).await;
+    assert_eq!(listed[0]["events"], json!(["workout.created"]));
+    assert!(listed[0].get("secret").is_none());
+
+    // The worker checks again before sending, for rows that predate the check
+    sqlx::query("UPDATE webhooks SET url = 'http://169.254.169.254/latest/meta-data'").execute(&pool).await.unwrap();
+    send_as(&app, 1, "POST", "/api/workouts", Some(json!({ "user_id": 1, "date": "2024-03-15" }))).await;
+    deliver_due(&pool, &webhook_client(true), chrono::Utc::now().timestamp()).await.unwrap();
+    let (_, log) = send_as(&app, 1, "GET", &format!("/api/webhooks/{}/deliveries", created["id"]), None).await;
+    assert_eq!(log[0]["last_error"], "Webhooks can't be sent to 169.254.169.254");
+
+    let path = format!("/api/webhooks/{}", created["id"]);
+    let (status, _) = send_as(&app, 2, "GET", &format!("{}/deliveries", path), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_as(&app, 2, "DELETE", &path, None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = send_as(&app, 1, "DELETE", &path, None).await;
+    assert_eq!(status, StatusCode::OK);
+}
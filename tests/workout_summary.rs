@@ -0,0 +1,77 @@
+use axum::{body::Body, http::StatusCode, Router};
+use serde::de::DeserializeOwned;
+
+use gymbuddy::models::analytics::{WorkoutProgress, WorkoutSummary};
+
+mod common;
+
+async fn setup(name: &str) -> Router {
+    common::setup(name, &[
+        "INSERT INTO users (username, email, password_hash) VALUES ('lifter', 'lifter@example.com', 'x'), ('other', 'other@example.com', 'x')",
+        "INSERT INTO exercises (name, muscle_group) VALUES ('Squat', 'Legs'), ('Pull Up', 'Back'), ('Bench Press', 'Chest')",
+        // Inserted out of date order on purpose
+        "INSERT INTO workouts (user_id, date) VALUES (1, '2024-03-08'), (1, '2024-03-01'), (1, '2024-03-10')",
+        "INSERT INTO entry_groups (workout_id, kind, rounds, duration_seconds) VALUES (1, 'superset', 2, 300)",
+        "INSERT INTO workout_entries (workout_id, exercise_id, sets, reps, weight, position, group_id) VALUES
+            (1, 1, 3, 5, 100, 1, NULL), (1, 2, 1, 10, NULL, 2, 1), (1, 3, 1, 10, 50, 3, 1),
+            (2, 3, 3, 8, 60, 1, NULL)",
+    ])
+    .await
+    .0
+}
+
+// Deserializing into the response structs is the point: the shape is part of the API
+async fn get<T: DeserializeOwned>(app: &Router, uri: &str) -> T {
+    let request = common::request("GET", uri, Some(1)).body(Body::empty()).unwrap();
+
+    let (status, _, bytes) = common::call(app, request).await;
+    assert_eq!(status, StatusCode::OK);
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+#[tokio::test]
+async fn summary_applies_rounds_and_counts_bodyweight() {
+    let app = setup("summary_typed").await;
+
+    let summary: WorkoutSummary = get(&app, "/api/workouts/1/summary").await;
+    assert_eq!(summary.workout_id, 1);
+    // 3 + 1x2 + 1x2 sets; the pull ups add reps but no volume
+    assert_eq!((summary.total_sets, summary.total_reps), (7, 25));
+    assert_eq!(summary.total_volume, 1500.0 + 1000.0);
+
+    let names: Vec<&str> = summary.exercises.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["Squat", "Pull Up", "Bench Press"]);
+    assert_eq!(summary.exercises[1].weight, 0.0);
+
+    let group = &summary.groups[0];
+    assert_eq!(group.exercises, ["Pull Up", "Bench Press"]);
+    assert_eq!((group.rounds, group.volume, group.density), (2, 1000.0, Some(200.0)));
+
+    let (status, _) = common::send_as(&app, 2, "GET", "/api/workouts/1/summary", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn progress_is_oldest_first_with_top_exercises() {
+    let app = setup("progress_typed").await;
+
+    let progress: Vec<WorkoutProgress> = get(&app, "/api/users/1/progress").await;
+    let dates: Vec<&str> = progress.iter().map(|w| w.totals.date.as_str()).collect();
+    assert_eq!(dates, ["2024-03-01", "2024-03-08", "2024-03-10"]);
+
+    let supersets = &progress[1];
+    assert_eq!(supersets.totals.total_volume, 2500.0);
+    assert_eq!(supersets.muscle_groups["Legs"], 1500.0);
+    assert_eq!(supersets.muscle_groups["Back"], 0.0);
+
+    let top: Vec<&str> = supersets.top_exercises.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(top, ["Squat", "Bench Press", "Pull Up"]);
+
+    // Nothing logged yet still shows up, with zeros
+    let empty = &progress[2];
+    assert_eq!((empty.totals.total_sets, empty.totals.total_volume), (0, 0.0));
+    assert!(empty.muscle_groups.is_empty() && empty.top_exercises.is_empty());
+
+    let (status, _) = common::send_as(&app, 2, "GET", "/api/users/1/progress", None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+}